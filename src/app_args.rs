@@ -0,0 +1,109 @@
+//!
+//! Notespace-Editor
+//!
+//! Command line arguments.
+//!
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+Usage: notespace-editor [OPTIONS] [FILE[:LINE[:COLUMN]]]... [-]
+
+Arguments:
+  FILE[:LINE[:COLUMN]]  Open FILE, optionally with the cursor at LINE and COLUMN
+  -                     Read standard input into a new unsaved document
+
+Options:
+  -w, --wait  Exit when the documents opened from the command line are closed
+  -h, --help  Print this help";
+
+///
+/// A file to open, with an optional 1-based `(line, column)` for the cursor.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FileArgument {
+    pub path: PathBuf,
+    pub position: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct AppArguments {
+    pub files: Vec<FileArgument>,
+    pub read_stdin: bool,
+    pub wait: bool,
+    pub help: bool,
+}
+
+impl AppArguments {
+    ///
+    /// Parse the command line arguments, not including the program name.
+    ///
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut app_arguments = AppArguments::default();
+        let mut options_ended = false;
+        for arg in args {
+            match arg.as_str() {
+                "-" if !options_ended => app_arguments.read_stdin = true,
+                "--" if !options_ended => options_ended = true,
+                "-w" | "--wait" if !options_ended => app_arguments.wait = true,
+                "-h" | "--help" if !options_ended => app_arguments.help = true,
+                option if option.starts_with('-') && !options_ended => {
+                    return Err(format!("Unknown option: {}", option));
+                }
+                path => app_arguments.files.push(parse_file_argument(path)),
+            }
+        }
+        Ok(app_arguments)
+    }
+
+    pub fn usage() -> &'static str {
+        USAGE
+    }
+}
+
+///
+/// Read standard input until it is closed. Invalid UTF-8 is replaced.
+///
+pub(crate) fn read_stdin() -> String {
+    let mut bytes = Vec::new();
+    if let Err(error) = std::io::stdin().read_to_end(&mut bytes) {
+        eprintln!("Could not read standard input: {}", error);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+///
+/// Split an optional `:LINE` or `:LINE:COLUMN` suffix off `arg`. A path that exists
+/// as given is never split, so file names that contain `:` can still be opened.
+///
+fn parse_file_argument(arg: &str) -> FileArgument {
+    let file_argument = |path: &str, position| FileArgument {
+        path: absolute_path(Path::new(path)),
+        position,
+    };
+    if Path::new(arg).exists() {
+        return file_argument(arg, None);
+    }
+    let parts: Vec<&str> = arg.rsplitn(3, ':').collect();
+    let number = |text: &str| text.parse::<usize>().ok().filter(|value| *value > 0);
+    match parts.as_slice() {
+        [last, middle, path] if !path.is_empty() => match (number(middle), number(last)) {
+            (Some(line), Some(column)) => file_argument(path, Some((line, column))),
+            (None, Some(line)) => file_argument(&format!("{}:{}", path, middle), Some((line, 1))),
+            _ => file_argument(arg, None),
+        },
+        [line, path] if !path.is_empty() => match number(line) {
+            Some(line) => file_argument(path, Some((line, 1))),
+            None => file_argument(arg, None),
+        },
+        _ => file_argument(arg, None),
+    }
+}
+
+///
+/// Make `path` absolute, so the same file is recognized when it is opened again.
+///
+fn absolute_path(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
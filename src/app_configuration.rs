@@ -4,16 +4,140 @@
 //! User configuration settings.
 //!
 
+use crate::app_const::MAX_RECENT_FILES;
+use crate::app_io::FileBackup;
+use crate::document::line_ending::LineEnding;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const SETTINGS_FILE: &str = "notespace_settings.toml";
 
+///
+/// The directory for application data, in `dirs::data_dir()`.
+///
+const DATA_DIRECTORY: &str = "notespace-editor";
+
+///
+/// The default directory for timestamped backups, in `DATA_DIRECTORY`.
+///
+const BACKUP_DIRECTORY: &str = "backups";
+
+///
+/// How the previous version of a file is kept when it is saved.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) enum BackupMode {
+    #[default]
+    Off,
+    ///
+    /// Copy `file.md` to `file.md~`.
+    ///
+    Tilde,
+    ///
+    /// Copy `file.md` to `file.md.<hash>.<timestamp>` in the backup directory.
+    ///
+    Timestamped,
+}
+
+///
+/// When documents with a file are saved automatically.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) enum AutosaveMode {
+    #[default]
+    Off,
+    ///
+    /// After no edits were made for `autosave_delay_seconds`.
+    ///
+    AfterDelay,
+    ///
+    /// When the window loses focus, e.g. when switching to another application.
+    ///
+    OnFocusLost,
+    OnWindowClose,
+}
+
+///
+/// The paper size of exported PDF files.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) enum PdfPageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl AutosaveMode {
+    pub const ALL: [AutosaveMode; 4] = [
+        AutosaveMode::Off,
+        AutosaveMode::AfterDelay,
+        AutosaveMode::OnFocusLost,
+        AutosaveMode::OnWindowClose,
+    ];
+
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            AutosaveMode::Off => "autosave_mode_off",
+            AutosaveMode::AfterDelay => "autosave_mode_after_delay",
+            AutosaveMode::OnFocusLost => "autosave_mode_on_focus_lost",
+            AutosaveMode::OnWindowClose => "autosave_mode_on_window_close",
+        }
+    }
+}
+
+impl PdfPageSize {
+    pub const ALL: [PdfPageSize; 4] = [
+        PdfPageSize::A4,
+        PdfPageSize::A5,
+        PdfPageSize::Letter,
+        PdfPageSize::Legal,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PdfPageSize::A4 => "A4",
+            PdfPageSize::A5 => "A5",
+            PdfPageSize::Letter => "Letter",
+            PdfPageSize::Legal => "Legal",
+        }
+    }
+
+    ///
+    /// The width and height of the page in millimeters, in portrait orientation.
+    ///
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            PdfPageSize::A4 => (210.0, 297.0),
+            PdfPageSize::A5 => (148.0, 210.0),
+            PdfPageSize::Letter => (215.9, 279.4),
+            PdfPageSize::Legal => (215.9, 355.6),
+        }
+    }
+}
+
+impl BackupMode {
+    pub const ALL: [BackupMode; 3] = [BackupMode::Off, BackupMode::Tilde, BackupMode::Timestamped];
+
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            BackupMode::Off => "backup_mode_off",
+            BackupMode::Tilde => "backup_mode_tilde",
+            BackupMode::Timestamped => "backup_mode_timestamped",
+        }
+    }
+}
+
 ///
 /// User configuration settings.
 ///
+/// Settings missing from the file keep their default value, so a file written by
+/// an older version can still be loaded.
+///
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct AppConfiguration {
     pub window_x: f32,
     pub window_y: f32,
@@ -22,6 +146,32 @@ pub(crate) struct AppConfiguration {
     pub scale_factor: f64,
     pub window_theme_name: String,
     pub syntax_theme_name: String,
+    ///
+    /// The line ending of new files.
+    ///
+    pub default_line_ending: LineEnding,
+    pub backup_mode: BackupMode,
+    ///
+    /// The directory for timestamped backups. Defaults to `BACKUP_DIRECTORY`.
+    ///
+    pub backup_directory: Option<PathBuf>,
+    pub autosave_mode: AutosaveMode,
+    pub autosave_delay_seconds: u64,
+    ///
+    /// Recently opened or saved files, the most recent first.
+    ///
+    pub recent_files: Vec<PathBuf>,
+    pub sidebar_width: f32,
+    ///
+    /// Start exported HTML files with a table of contents.
+    ///
+    pub export_table_of_contents: bool,
+    pub pdf_page_size: PdfPageSize,
+    ///
+    /// The margins of exported PDF pages in millimeters. The header and footer
+    /// are in the top and bottom margin.
+    ///
+    pub pdf_margin: f32,
 }
 
 impl Default for AppConfiguration {
@@ -34,6 +184,16 @@ impl Default for AppConfiguration {
             scale_factor: 1.0,
             window_theme_name: iced::Theme::default().to_string(),
             syntax_theme_name: iced::highlighter::Theme::SolarizedDark.to_string(),
+            default_line_ending: LineEnding::default(),
+            backup_mode: BackupMode::default(),
+            backup_directory: None,
+            autosave_mode: AutosaveMode::default(),
+            autosave_delay_seconds: 5,
+            recent_files: Vec::new(),
+            sidebar_width: 240.0,
+            export_table_of_contents: true,
+            pdf_page_size: PdfPageSize::default(),
+            pdf_margin: 20.0,
         }
     }
 }
@@ -59,6 +219,61 @@ impl AppConfiguration {
         })
     }
 
+    ///
+    /// Where the previous version of a file is copied when it is saved.
+    ///
+    pub(crate) fn get_file_backup(&self) -> FileBackup {
+        match self.backup_mode {
+            BackupMode::Off => FileBackup::None,
+            BackupMode::Tilde => FileBackup::Tilde,
+            BackupMode::Timestamped => FileBackup::Timestamped(
+                self.backup_directory
+                    .clone()
+                    .unwrap_or_else(|| Self::data_path().join(BACKUP_DIRECTORY)),
+            ),
+        }
+    }
+
+    ///
+    /// Move `file_path` to the front of the recent files, and save the
+    /// configuration if the list changed.
+    ///
+    pub(crate) fn add_recent_file(&mut self, file_path: &Path) {
+        let file_path = std::path::absolute(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+        if self.recent_files.first() == Some(&file_path) {
+            return;
+        }
+        self.recent_files
+            .retain(|recent_file| *recent_file != file_path);
+        self.recent_files.insert(0, file_path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+        self.save();
+    }
+
+    ///
+    /// Remove `file_path` from the recent files, e.g. when it could not be opened.
+    ///
+    pub(crate) fn remove_recent_file(&mut self, file_path: &Path) {
+        let len = self.recent_files.len();
+        self.recent_files
+            .retain(|recent_file| recent_file.as_path() != file_path);
+        if self.recent_files.len() != len {
+            self.save();
+        }
+    }
+
+    ///
+    /// Remove the recent files that no longer exist.
+    ///
+    pub(crate) fn prune_recent_files(&mut self) {
+        let len = self.recent_files.len();
+        self.recent_files
+            .retain(|recent_file| recent_file.is_file());
+        if self.recent_files.len() != len {
+            self.save();
+        }
+    }
+
     ///
     /// Returns the configuration file path:
     ///
@@ -70,6 +285,17 @@ impl AppConfiguration {
             .join(SETTINGS_FILE)
     }
 
+    ///
+    /// Returns the directory for application data, such as backups:
+    ///
+    /// `dirs::data_dir() + DATA_DIRECTORY`
+    ///
+    pub fn data_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(DATA_DIRECTORY)
+    }
+
     ///
     /// Attempts to load `AppConfiguration` from the configuration file.
     ///
@@ -4,5 +4,94 @@
 //! Constants for the application.
 //!
 
+use std::time::Duration;
+
 //pub(crate) const FONT_MONOSPACED_BYTES: &[u8] = include_bytes!("../fonts/FiraCode-Regular.ttf");
 //pub const FONT_MONOSPACED: iced::Font = iced::Font(FONT_MONOSPACED_BYTES);
+
+///
+/// Files larger than this are not opened, the editor keeps the whole text in memory.
+///
+pub(crate) const MAX_OPEN_FILE_SIZE: u64 = 32 * 1024 * 1024;
+
+///
+/// The number of bytes at the start of a file that are checked for NUL bytes and
+/// control characters to recognize binary files.
+///
+pub(crate) const BINARY_CHECK_LENGTH: usize = 8 * 1024;
+
+///
+/// The percentage of control characters, other than whitespace and escape, above
+/// which a file without NUL bytes is binary.
+///
+pub(crate) const BINARY_CONTROL_PERCENT: usize = 10;
+
+///
+/// The number of files kept in the list of recent files.
+///
+pub(crate) const MAX_RECENT_FILES: usize = 20;
+
+///
+/// The number of closed files that can be reopened.
+///
+pub(crate) const MAX_CLOSED_FILES: usize = 20;
+
+///
+/// A search in the open folder stops after this many matching lines.
+///
+pub(crate) const MAX_FOLDER_SEARCH_MATCHES: usize = 10_000;
+
+///
+/// How often autosave checks for a pause in editing.
+///
+pub(crate) const AUTOSAVE_TICK: Duration = Duration::from_millis(500);
+
+///
+/// How long the statusbar shows that documents were autosaved.
+///
+pub(crate) const AUTOSAVED_NOTICE_DURATION: Duration = Duration::from_secs(2);
+
+///
+/// How often modified documents are written to the crash recovery journal.
+///
+pub(crate) const RECOVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+///
+/// A recovery journal whose heartbeat is older than this belongs to an editor that
+/// is no longer running.
+///
+pub(crate) const RECOVERY_SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+///
+/// The syntax modes that can be chosen for a document: the name shown to the user,
+/// and the file extension that selects the syntax highlighter.
+///
+pub(crate) const SYNTAX_MODES: [(&str, &str); 16] = [
+    ("Plain Text", "txt"),
+    ("Markdown", "md"),
+    ("Rust", "rs"),
+    ("Python", "py"),
+    ("JavaScript", "js"),
+    ("HTML", "html"),
+    ("CSS", "css"),
+    ("JSON", "json"),
+    ("YAML", "yaml"),
+    ("XML", "xml"),
+    ("Shell", "sh"),
+    ("C", "c"),
+    ("C++", "cpp"),
+    ("Java", "java"),
+    ("Go", "go"),
+    ("SQL", "sql"),
+];
+
+///
+/// The margins of exported PDF pages offered in the command palette, in
+/// millimeters.
+///
+pub(crate) const PDF_MARGINS: [f32; 5] = [10.0, 15.0, 20.0, 25.0, 30.0];
+
+///
+/// The number of timestamped backups kept of each file, older ones are deleted.
+///
+pub(crate) const MAX_BACKUPS_PER_FILE: usize = 20;
@@ -0,0 +1,129 @@
+//!
+//! Notespace-Editor
+//!
+//! Watches the files of the open documents, and the folder open in the sidebar, for
+//! changes made by other programs.
+//!
+//! The directories of the files are watched instead of the files themselves, so a
+//! file that is replaced by renaming another file over it (as most editors and
+//! sync tools save files) is still noticed.
+//!
+
+use crate::app_message::AppMessage;
+use iced::Subscription;
+use iced::futures::channel::mpsc;
+use iced::futures::{FutureExt, SinkExt, StreamExt};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+///
+/// A subscription that sends `AppMessage::WatchedFileChanged` when one of the
+/// `file_paths` is created, modified, removed or renamed.
+///
+/// The subscription is identified by the paths, so a new watcher is started when
+/// a document is opened or closed.
+///
+pub fn watch_files(file_paths: Vec<PathBuf>) -> Subscription<AppMessage> {
+    Subscription::run_with_id(
+        file_paths.clone(),
+        iced::stream::channel(100, move |mut output| async move {
+            //
+            // Events name the absolute path, documents may have a relative one.
+            //
+            let watched: HashMap<PathBuf, PathBuf> = file_paths
+                .into_iter()
+                .filter_map(|file_path| Some((std::path::absolute(&file_path).ok()?, file_path)))
+                .collect();
+            let (sender, mut receiver) = mpsc::unbounded();
+            let watcher =
+                notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                    if let Ok(event) = result {
+                        let _ = sender.unbounded_send(event);
+                    }
+                });
+            let mut watcher = match watcher {
+                Ok(watcher) => watcher,
+                Err(error) => {
+                    eprintln!("Could not watch files: {:?}", error);
+                    return;
+                }
+            };
+            let directories: HashSet<&Path> =
+                watched.keys().filter_map(|path| path.parent()).collect();
+            for directory in directories {
+                let _ = watcher.watch(directory, RecursiveMode::NonRecursive);
+            }
+
+            while let Some(event) = receiver.next().await {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                for path in event.paths {
+                    if let Some(file_path) = watched.get(&path) {
+                        let _ = output
+                            .send(AppMessage::WatchedFileChanged(file_path.clone()))
+                            .await;
+                    }
+                }
+            }
+        }),
+    )
+}
+
+///
+/// A subscription that sends `AppMessage::FolderChanged` with the paths of the
+/// files and directories that were changed under `root`. Events that arrive
+/// together are sent as one message. Changes in `.git` directories are ignored.
+///
+pub fn watch_folder(root: PathBuf) -> Subscription<AppMessage> {
+    Subscription::run_with_id(
+        root.clone(),
+        iced::stream::channel(100, move |mut output| async move {
+            let (sender, mut receiver) = mpsc::unbounded();
+            let watcher =
+                notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                    if let Ok(event) = result {
+                        let _ = sender.unbounded_send(event);
+                    }
+                });
+            let mut watcher = match watcher {
+                Ok(watcher) => watcher,
+                Err(error) => {
+                    eprintln!("Could not watch folder: {:?}", error);
+                    return;
+                }
+            };
+            if let Err(error) = watcher.watch(&root, RecursiveMode::Recursive) {
+                eprintln!("Could not watch folder: {:?}", error);
+                return;
+            }
+
+            while let Some(event) = receiver.next().await {
+                let mut events = vec![event];
+                //
+                // Take the events that are already queued without waiting, so a
+                // burst of events is reported at once.
+                //
+                while let Some(Some(event)) = receiver.next().now_or_never() {
+                    events.push(event);
+                }
+                let mut paths: Vec<PathBuf> = events
+                    .into_iter()
+                    .filter(|event| !matches!(event.kind, EventKind::Access(_)))
+                    .flat_map(|event| event.paths)
+                    .filter(|path| {
+                        !path
+                            .components()
+                            .any(|component| component == Component::Normal(".git".as_ref()))
+                    })
+                    .collect();
+                paths.sort();
+                paths.dedup();
+                if !paths.is_empty() {
+                    let _ = output.send(AppMessage::FolderChanged(paths)).await;
+                }
+            }
+        }),
+    )
+}
@@ -4,28 +4,103 @@
 //! IO components and functions for the application.
 //!
 
+use crate::app_const::{MAX_BACKUPS_PER_FILE, MAX_OPEN_FILE_SIZE};
+use crate::document::text_encoding::TextEncoding;
+use crate::workspace::file_tree::FileOperation;
 use rfd::*;
 use rust_i18n::t;
 use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::result::Result;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
 
 ///
-/// Load the specified `path` into a `String`.
+/// A file that was read and decoded.
+///
+#[derive(Debug, Clone)]
+pub struct LoadedFile {
+    pub path: PathBuf,
+    pub text: Arc<String>,
+    pub encoding: TextEncoding,
+    ///
+    /// The modification time of the file when it was read.
+    ///
+    pub modified: Option<SystemTime>,
+}
+
+///
+/// A file that was written.
+///
+#[derive(Debug, Clone)]
+pub struct SavedFile {
+    pub path: PathBuf,
+    ///
+    /// The modification time of the file after it was written.
+    ///
+    pub modified: Option<SystemTime>,
+}
+
+///
+/// Load the specified `path` into a `String`, detecting the text encoding.
+///
+/// Files larger than `MAX_OPEN_FILE_SIZE` and binary files are refused.
 ///
 /// Uses Tokio async IO functions.
 ///
-pub async fn async_open_file_from_path(
+pub async fn async_open_file_from_path(file_path: PathBuf) -> Result<LoadedFile, AppIOError> {
+    let (bytes, modified) = read_file(&file_path).await?;
+    let Some((file_contents, encoding)) = TextEncoding::detect_and_decode(&bytes) else {
+        return Err(AppIOError::BinaryFileError(file_path));
+    };
+
+    Ok(LoadedFile {
+        path: file_path,
+        text: Arc::new(file_contents),
+        encoding,
+        modified,
+    })
+}
+
+///
+/// Load the specified `path` into a `String` using the `encoding` chosen by the
+/// user instead of the detected one.
+///
+pub async fn async_open_file_with_encoding(
     file_path: PathBuf,
-) -> Result<(PathBuf, Arc<String>), AppIOError> {
-    let file_contents = tokio::fs::read_to_string(&file_path)
-        .await
-        .map(Arc::new)
-        .map_err(|error| error.kind())
-        .map_err(AppIOError::IOFailedError)?;
+    encoding: TextEncoding,
+) -> Result<LoadedFile, AppIOError> {
+    let (bytes, modified) = read_file(&file_path).await?;
+    let encoding = TextEncoding::new(encoding.encoding, encoding.has_bom(&bytes));
+    let file_contents = encoding.decode(&bytes);
 
-    Ok((file_path, file_contents))
+    Ok(LoadedFile {
+        path: file_path,
+        text: Arc::new(file_contents),
+        encoding,
+        modified,
+    })
+}
+
+///
+/// Read the bytes and the modification time of a file that is small enough to be
+/// edited.
+///
+async fn read_file(file_path: &Path) -> Result<(Vec<u8>, Option<SystemTime>), AppIOError> {
+    let metadata = tokio::fs::metadata(file_path)
+        .await
+        .map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    if metadata.len() > MAX_OPEN_FILE_SIZE {
+        return Err(AppIOError::FileTooLargeError(
+            file_path.to_path_buf(),
+            metadata.len(),
+        ));
+    }
+    let bytes = tokio::fs::read(file_path)
+        .await
+        .map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    Ok((bytes, metadata.modified().ok()))
 }
 
 ///
@@ -33,7 +108,7 @@ pub async fn async_open_file_from_path(
 ///
 /// Uses Rusty File Dialogs (RFD).
 ///
-pub async fn async_open_file_from_dialog() -> Result<(PathBuf, Arc<String>), AppIOError> {
+pub async fn async_open_file_from_dialog() -> Result<LoadedFile, AppIOError> {
     let file_handle = AsyncFileDialog::new()
         .set_title(t!("choose_file_open"))
         .pick_file()
@@ -43,10 +118,92 @@ pub async fn async_open_file_from_dialog() -> Result<(PathBuf, Arc<String>), App
     async_open_file_from_path(file_path.to_owned()).await
 }
 
+///
+/// Let the user choose a folder to open in the sidebar.
+///
+pub async fn async_open_folder_from_dialog() -> Result<PathBuf, AppIOError> {
+    AsyncFileDialog::new()
+        .set_title(t!("choose_folder_open"))
+        .pick_folder()
+        .await
+        .map(PathBuf::from)
+        .ok_or(AppIOError::FileDialogClosedError)
+}
+
+///
+/// Create an empty file at `path`, and the directories it is in. An existing file
+/// is never replaced.
+///
+pub async fn async_create_file(path: PathBuf) -> Result<FileOperation, AppIOError> {
+    let io_error = |error: std::io::Error| AppIOError::IOFailedError(error.kind());
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+    }
+    tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await
+        .map_err(io_error)?;
+    Ok(FileOperation::CreatedFile(path))
+}
+
+///
+/// Create the directory at `path`, and the directories it is in.
+///
+pub async fn async_create_folder(path: PathBuf) -> Result<FileOperation, AppIOError> {
+    if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        return Err(AppIOError::IOFailedError(ErrorKind::AlreadyExists));
+    }
+    tokio::fs::create_dir_all(&path)
+        .await
+        .map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    Ok(FileOperation::CreatedFolder(path))
+}
+
+///
+/// Rename or move the file or directory at `from` to `to`. An existing file or
+/// directory at `to` is never replaced.
+///
+pub async fn async_rename_path(from: PathBuf, to: PathBuf) -> Result<FileOperation, AppIOError> {
+    if tokio::fs::try_exists(&to).await.unwrap_or(false) {
+        return Err(AppIOError::IOFailedError(ErrorKind::AlreadyExists));
+    }
+    tokio::fs::rename(&from, &to)
+        .await
+        .map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    Ok(FileOperation::Renamed(from, to))
+}
+
+///
+/// Delete the file, or the directory with everything in it, at `path`.
+///
+pub async fn async_delete_path(path: PathBuf) -> Result<FileOperation, AppIOError> {
+    let metadata = tokio::fs::symlink_metadata(&path)
+        .await
+        .map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    let result = match metadata.is_dir() {
+        true => tokio::fs::remove_dir_all(&path).await,
+        false => tokio::fs::remove_file(&path).await,
+    };
+    result.map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    Ok(FileOperation::Deleted(path))
+}
+
+///
+/// Save `file_contents` in the `encoding` of the document, asking for a file name
+/// if the document has none yet. The previous version of the file is kept as
+/// configured by `file_backup`.
+///
 pub async fn async_save_file_to_path(
     file_path: Option<PathBuf>,
     file_contents: String,
-) -> Result<PathBuf, AppIOError> {
+    encoding: TextEncoding,
+    file_backup: FileBackup,
+) -> Result<SavedFile, AppIOError> {
+    let file_bytes = encoding
+        .encode(&file_contents)
+        .ok_or_else(|| AppIOError::EncodingError(encoding.label()))?;
     let path = if let Some(file_path) = file_path {
         file_path
     } else {
@@ -57,21 +214,240 @@ pub async fn async_save_file_to_path(
             .ok_or(AppIOError::FileDialogClosedError)
             .map(|handle| handle.path().to_owned())?
     };
-    tokio::fs::write(&path, &file_contents)
+    async_save_bytes_to_path(path, &file_bytes, &file_backup).await
+}
+
+///
+/// Write `file_bytes` to the file at `path`, keeping the previous version as
+/// configured by `file_backup`. Used for documents, and for exports that are not
+/// text.
+///
+pub async fn async_save_bytes_to_path(
+    path: PathBuf,
+    file_bytes: &[u8],
+    file_backup: &FileBackup,
+) -> Result<SavedFile, AppIOError> {
+    //
+    // Write to the file a symlink points to, instead of replacing the symlink.
+    //
+    let target = match tokio::fs::canonicalize(&path).await {
+        Ok(target) => target,
+        Err(_) => path.clone(),
+    };
+    if tokio::fs::try_exists(&target).await.unwrap_or(false) {
+        backup_file(&target, file_backup)
+            .await
+            .map_err(|error| AppIOError::BackupFailedError(error.kind()))?;
+    }
+    write_file_atomic(&target, file_bytes)
         .await
         .map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+    let modified = tokio::fs::metadata(&target)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    Ok(SavedFile { path, modified })
+}
+
+///
+/// Let the user choose the file to export a document to, suggesting the name of
+/// the document's `file_path` with the `extension`, e.g. `notes.html` for
+/// `notes.md`, in the same directory.
+///
+pub async fn async_choose_export_path(
+    file_path: Option<PathBuf>,
+    extension: &str,
+) -> Result<PathBuf, AppIOError> {
+    let file_stem = file_path
+        .as_deref()
+        .and_then(Path::file_stem)
+        .map(|file_stem| file_stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| t!("untitled").to_string());
+    let mut dialog = AsyncFileDialog::new()
+        .set_title(t!("choose_export_file_name"))
+        .set_file_name(format!("{}.{}", file_stem, extension))
+        .add_filter(extension.to_uppercase(), &[extension]);
+    if let Some(directory) = file_path.as_deref().and_then(Path::parent) {
+        dialog = dialog.set_directory(directory);
+    }
+    dialog
+        .save_file()
+        .await
+        .map(|handle| handle.path().to_owned())
+        .ok_or(AppIOError::FileDialogClosedError)
+}
+
+///
+/// Where the previous version of a file is copied before it is overwritten.
+///
+#[derive(Debug, Clone)]
+pub enum FileBackup {
+    None,
+    ///
+    /// `file.md~` next to the file.
+    ///
+    Tilde,
+    ///
+    /// `file.md.<hash of its directory>.20250101T120000.000Z` in the directory,
+    /// keeping the `MAX_BACKUPS_PER_FILE` most recent ones.
+    ///
+    Timestamped(PathBuf),
+}
+
+async fn backup_file(path: &Path, file_backup: &FileBackup) -> std::io::Result<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let directory = match file_backup {
+        FileBackup::None => return Ok(()),
+        FileBackup::Tilde => {
+            let backup_path = path.with_file_name(format!("{}~", file_name));
+            return tokio::fs::copy(path, backup_path).await.map(|_| ());
+        }
+        FileBackup::Timestamped(directory) => directory,
+    };
+    tokio::fs::create_dir_all(directory).await?;
+    //
+    // Files with the same name in different directories are told apart by a hash
+    // of their directory.
+    //
+    let prefix = format!(
+        "{}.{:08x}.",
+        file_name,
+        path_hash(path.parent().unwrap_or(Path::new(""))) as u32
+    );
+    let now = time::OffsetDateTime::now_utc();
+    let timestamp = format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}.{:03}Z",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second(),
+        now.millisecond()
+    );
+    let mut backup_path = directory.join(format!("{}{}", prefix, timestamp));
+    let mut count = 1;
+    while tokio::fs::try_exists(&backup_path).await.unwrap_or(false) {
+        backup_path = directory.join(format!("{}{}-{}", prefix, timestamp, count));
+        count += 1;
+    }
+    tokio::fs::copy(path, &backup_path).await?;
+    remove_old_backups(directory, &prefix).await
+}
+
+///
+/// Delete the oldest backups with the name `prefix` in `directory`, keeping
+/// `MAX_BACKUPS_PER_FILE`. The timestamps in the names sort by age.
+///
+async fn remove_old_backups(directory: &Path, prefix: &str) -> std::io::Result<()> {
+    let mut backups = Vec::new();
+    let mut entries = tokio::fs::read_dir(directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name().to_string_lossy().starts_with(prefix) {
+            backups.push(entry.path());
+        }
+    }
+    backups.sort();
+    let excess = backups.len().saturating_sub(MAX_BACKUPS_PER_FILE);
+    for backup in backups.into_iter().take(excess) {
+        tokio::fs::remove_file(backup).await?;
+    }
+    Ok(())
+}
+
+///
+/// A hash of `path` that is the same in every build, unlike `DefaultHasher`
+/// (FNV-1a).
+///
+pub(crate) fn path_hash(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+///
+/// The number of temporary files created by `write_file_atomic`.
+///
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+///
+/// Replace the file at `path` with `bytes` without ever leaving a partly written
+/// file behind: the bytes are written to a temporary file in the same directory,
+/// flushed to disk and then renamed over `path`. The permissions of an existing
+/// file are kept.
+///
+pub(crate) async fn write_file_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let directory = path.parent().unwrap_or(Path::new("."));
+    //
+    // Every write has a temporary file of its own, so saves of the same file that
+    // overlap never write to the same temporary file.
+    //
+    let temp_path = directory.join(format!(
+        ".{}.{}.{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let permissions = tokio::fs::metadata(path)
+        .await
+        .ok()
+        .map(|metadata| metadata.permissions());
+
+    let result = async {
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+            .await?;
+        file.write_all(bytes).await?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions).await?;
+        }
+        file.sync_all().await?;
+        tokio::fs::rename(&temp_path, path).await
+    }
+    .await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return result;
+    }
 
-    Ok(path)
+    //
+    // Flush the rename to disk too. Directories cannot be opened on Windows.
+    //
+    #[cfg(unix)]
+    if let Ok(directory) = tokio::fs::File::open(directory).await {
+        let _ = directory.sync_all().await;
+    }
+    Ok(())
 }
 
 ///
 /// IO error definitions.
 ///
-#[allow(dead_code)]
+#[allow(dead_code, clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum AppIOError {
     FileDialogClosedError,
     IOFailedError(ErrorKind),
+    BinaryFileError(PathBuf),
+    FileTooLargeError(PathBuf, u64),
+    EncodingError(String),
+    BackupFailedError(ErrorKind),
+    ///
+    /// The file was changed by another program after it was read.
+    ///
+    FileChangedError(PathBuf),
+    ///
+    /// The document could not be converted to the format it is exported to.
+    ///
+    ExportError(String),
 }
 
 impl std::fmt::Display for AppIOError {
@@ -81,6 +457,38 @@ impl std::fmt::Display for AppIOError {
                 write!(f, "AppIOError {}", "FileDialogClosedError")
             }
             AppIOError::IOFailedError(err) => write!(f, "AppIOError {}", err),
+            AppIOError::BinaryFileError(path) => {
+                write!(f, "{}", t!("error_binary_file", file = file_name(path)))
+            }
+            AppIOError::FileTooLargeError(path, size) => write!(
+                f,
+                "{}",
+                t!(
+                    "error_file_too_large",
+                    file = file_name(path),
+                    size = size / (1024 * 1024),
+                    max = MAX_OPEN_FILE_SIZE / (1024 * 1024)
+                )
+            ),
+            AppIOError::EncodingError(encoding) => {
+                write!(f, "{}", t!("error_encoding", encoding = encoding))
+            }
+            AppIOError::BackupFailedError(err) => {
+                write!(f, "{}", t!("error_backup", error = err))
+            }
+            AppIOError::FileChangedError(path) => {
+                write!(f, "{}", t!("error_file_changed", file = file_name(path)))
+            }
+            AppIOError::ExportError(error) => {
+                write!(f, "{}", t!("error_export", error = error))
+            }
         }
     }
 }
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
@@ -4,19 +4,152 @@
 //! Iced application messages.
 //!
 
-use crate::app_io::AppIOError;
+use crate::app_configuration::{AutosaveMode, BackupMode, PdfPageSize};
+use crate::app_io::{AppIOError, LoadedFile, SavedFile};
+use crate::app_session::SessionDocument;
+use crate::document::edit_history::EditStateId;
+use crate::document::line_ending::LineEnding;
+use crate::document::recovery_journal::RecoveredSnapshot;
+use crate::document::text_document::DocumentId;
+use crate::document::text_encoding::TextEncoding;
+use crate::find::find_state::FindMatch;
+use crate::keyboard::keybind_action::KeybindAction;
+use crate::workspace::file_index::FileIndexChanges;
+use crate::workspace::file_tree::{FileOperation, FileTreeEdit, FileTreeEntry};
+use crate::workspace::folder_search::{FileReplacementResult, FolderSearchEvent};
+use crate::workspace::search_index::{IndexUpdate, SearchIndex};
+use iced::widget::scrollable::Viewport;
 use iced::widget::text_editor;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub enum AppMessage {
     TextEdited(text_editor::Action),
     OpenFileFromDialog,
-    FileOpened(Result<(PathBuf, Arc<String>), AppIOError>),
+    OpenRecentFile(PathBuf),
+    RecentFileOpened(PathBuf, Result<LoadedFile, AppIOError>),
+    RecentFilesShown,
+    ClosedFileReopened(Result<LoadedFile, AppIOError>, SessionDocument),
+    OpenFolderFromDialog,
+    FolderOpened(Result<PathBuf, AppIOError>),
+    CloseFolder,
+    FolderDirectoryRead(PathBuf, Result<Vec<FileTreeEntry>, AppIOError>),
+    FolderChanged(Vec<PathBuf>),
+    FileTreeToggle(PathBuf),
+    FileTreeOpenFile(PathBuf),
+    FileTreeContextMenu(PathBuf),
+    FileTreeStartEdit(FileTreeEdit),
+    FileTreeEditChanged(String),
+    FileTreeEditSubmit,
+    FileTreeEditCancel,
+    FileTreeDelete(PathBuf),
+    FileTreeDeleteConfirm,
+    FileTreeDeleteCancel,
+    FileOperationFinished(Result<FileOperation, AppIOError>),
+    FileIndexBuilt(PathBuf, Vec<PathBuf>),
+    FileIndexChanged(PathBuf, FileIndexChanges),
+    SearchIndexLoaded(Arc<SearchIndex>),
+    SearchIndexScanned(PathBuf, IndexUpdate),
+    SearchIndexUpdated(PathBuf, IndexUpdate),
+    SearchIndexSaved,
+    CloseQuickOpen,
+    QuickOpenQueryChanged(String),
+    QuickOpenSelectNext,
+    QuickOpenSelectPrevious,
+    QuickOpenSubmit,
+    QuickOpenOpen(usize),
+    QuickOpenPreviewLoaded(PathBuf, Result<LoadedFile, AppIOError>),
+    OpenFolderSearch,
+    CloseFolderSearch,
+    FolderSearchQueryChanged(String),
+    FolderSearchReplacementChanged(String),
+    FolderSearchIncludeChanged(String),
+    FolderSearchExcludeChanged(String),
+    ToggleFolderSearchCaseSensitive,
+    ToggleFolderSearchWholeWord,
+    ToggleFolderSearchRegex,
+    FolderSearchSubmit,
+    FolderSearchEvent(u64, FolderSearchEvent),
+    FolderSearchOpenMatch(PathBuf, FindMatch),
+    FolderSearchFileOpened(Result<LoadedFile, AppIOError>, FindMatch),
+    FolderReplacePreview,
+    FolderReplacePreviewed(u64, Vec<FileReplacementResult>),
+    FolderReplaceConfirm,
+    FolderReplaceCancel,
+    FolderReplaceWritten(Result<SavedFile, AppIOError>),
+    SidebarResizeStarted,
+    SidebarResized(f32),
+    SidebarResizeFinished,
+    FileOpened(Result<LoadedFile, AppIOError>),
+    CommandLineFileOpened(Result<LoadedFile, AppIOError>, Option<(usize, usize)>),
+    CommandLineFilesOpened,
+    ReopenWithEncoding(TextEncoding),
+    FileReopened(DocumentId, Result<LoadedFile, AppIOError>),
+    SaveWithEncoding(TextEncoding),
+    ConvertLineEnding(LineEnding),
+    UpdateDefaultLineEnding(LineEnding),
+    UpdateBackupMode(BackupMode),
+    UpdateAutosaveMode(AutosaveMode),
+    UpdateExportTableOfContents(bool),
+    UpdatePdfPageSize(PdfPageSize),
+    UpdatePdfMargin(f32),
     NewFile,
     SaveFile,
-    FileSaved(Result<PathBuf, AppIOError>),
+    FileSaved(DocumentId, EditStateId, Result<SavedFile, AppIOError>),
+    FileAutosaved(DocumentId, EditStateId, Result<SavedFile, AppIOError>),
+    FileExported(Result<SavedFile, AppIOError>),
+    AutosaveTick(Instant),
+    WatchedFileChanged(PathBuf),
+    WatchedFileLoaded(DocumentId, Result<LoadedFile, AppIOError>),
+    FileChangedReload,
+    FileChangedKeepMine,
+    FileChangedShowDiff,
+    FileChangedHideDiff,
+    SessionFileOpened(Result<LoadedFile, AppIOError>, SessionDocument),
+    SessionRestored(Option<PathBuf>),
+    UpdateSyntaxMode(Option<String>),
+    RecoveryTick,
+    RecoveryJournalWritten(Result<(), AppIOError>),
+    RecoverySnapshotsFound(Vec<RecoveredSnapshot>),
+    RecoveryRestore(usize),
+    RecoveryDiscard(usize),
+    RecoveryCompare(usize),
+    RecoveryCompareLoaded(usize, Result<LoadedFile, AppIOError>),
+    RecoveryCompareClosed,
+    RecoveryRestoreAll,
+    RecoveryDiscardAll,
+    RecoveryLater,
+    SelectDocument(DocumentId),
+    CloseDocument(DocumentId),
+    UnsavedChangesSave,
+    UnsavedChangesDiscard,
+    UnsavedChangesCancel,
+    EditorScrolled(Viewport),
+    OpenFindPanel,
+    CloseFindPanel,
+    FindQueryChanged(String),
+    ReplacementChanged(String),
+    ToggleFindCaseSensitive,
+    ToggleFindWholeWord,
+    ToggleFindRegex,
+    FindNext,
+    FindPrevious,
+    ReplaceOne,
+    ReplaceAll,
+    RecordKeybind(KeybindAction),
+    ResetKeybind(KeybindAction),
+    ResetAllKeybinds,
+    ClipboardPasted(Option<String>),
+    RunKeybindAction(KeybindAction),
+    CloseCommandPalette,
+    CommandPaletteQueryChanged(String),
+    CommandPaletteSelectNext,
+    CommandPaletteSelectPrevious,
+    CommandPaletteSubmit,
+    RunCommand(usize),
+    OpenCommandPaletteWithQuery(String),
     UpdateLanguage(String),
     UpdateWindowTheme(iced::Theme),
     UpdateSyntaxTheme(iced::highlighter::Theme),
@@ -0,0 +1,118 @@
+//!
+//! Notespace-Editor
+//!
+//! The documents that were open when the application was closed, restored on the
+//! next start.
+//!
+
+use crate::app_configuration::AppConfiguration;
+use crate::app_io::write_file_atomic;
+use crate::document::document_manager::DocumentManager;
+use crate::document::text_cursor::TextPosition;
+use crate::document::text_document::TextDocument;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const SESSION_FILE: &str = "notespace_session.toml";
+
+///
+/// An open document with a file, and where the user was in it.
+///
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SessionDocument {
+    pub file_path: PathBuf,
+    pub cursor: TextPosition,
+    ///
+    /// The other end of the selection, if text was selected.
+    ///
+    pub selection_anchor: Option<TextPosition>,
+    pub scroll_offset: (f32, f32),
+    pub syntax_mode: Option<String>,
+}
+
+impl SessionDocument {
+    ///
+    /// Where the user is in `document`, or `None` for an untitled document.
+    ///
+    pub fn of(document: &TextDocument) -> Option<Self> {
+        Some(Self {
+            file_path: document.file_path.clone()?,
+            cursor: document.file_content.cursor_position(),
+            selection_anchor: document.history.selection_anchor(&document.file_content),
+            scroll_offset: (document.scroll_offset.x, document.scroll_offset.y),
+            syntax_mode: document.syntax_mode.clone(),
+        })
+    }
+}
+
+///
+/// The open documents, in tab order, and the folder open in the sidebar. Untitled
+/// documents are not part of the session; their unsaved text is kept by the
+/// recovery journal.
+///
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AppSession {
+    pub documents: Vec<SessionDocument>,
+    pub active_file: Option<PathBuf>,
+    pub folder: Option<PathBuf>,
+}
+
+impl AppSession {
+    ///
+    /// The session of the documents open in the `document_manager`.
+    ///
+    pub fn of(document_manager: &DocumentManager) -> Self {
+        let documents = document_manager
+            .documents()
+            .iter()
+            .filter_map(SessionDocument::of)
+            .collect();
+        Self {
+            documents,
+            active_file: document_manager.active().file_path.clone(),
+            folder: None,
+        }
+    }
+
+    ///
+    /// Returns the session file path, in the same directory as the settings file.
+    ///
+    pub fn path() -> PathBuf {
+        AppConfiguration::path().with_file_name(SESSION_FILE)
+    }
+
+    ///
+    /// Attempts to load `AppSession` from the session file.
+    ///
+    /// Returns an empty session if no session file is found.
+    ///
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Saves the session to the session file. The file is replaced atomically, so
+    /// an exit during the save keeps the previous session.
+    ///
+    pub async fn save(self) {
+        let path = Self::path();
+        let result = async {
+            let toml = toml::to_string_pretty(&self)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            write_file_atomic(&path, toml.as_bytes()).await
+        }
+        .await;
+        if let Err(error) = result {
+            eprintln!("Could not save the session: {:?}", error);
+        }
+    }
+}
@@ -7,20 +7,54 @@
 //!
 
 use crate::app_io::AppIOError;
+use crate::document::document_manager::DocumentManager;
+use crate::find::find_state::FindState;
 use crate::keyboard::keybind_manager::KeybindManager;
+use crate::markdown::preview_state::PreviewState;
+use crate::palette::palette_state::PaletteState;
+use crate::workspace::file_index::FileIndex;
+use crate::workspace::file_tree::FileTree;
+use crate::workspace::folder_search_state::FolderSearchState;
+use crate::workspace::quick_open_state::QuickOpenState;
+use crate::workspace::search_index::SearchIndex;
 use iced::Theme;
 use iced::font::Font;
-use iced::widget::text_editor::Content;
-use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
 
 pub struct AppState {
     pub(crate) scale_factor: f64,
     pub(crate) window_theme: Theme,
     pub(crate) syntax_theme: iced::highlighter::Theme,
-    pub(crate) file_path: Option<PathBuf>,
-    pub(crate) file_dirty: bool,
-    pub(crate) file_content: Content,
+    pub(crate) document_manager: DocumentManager,
+    pub(crate) find_state: FindState,
+    pub(crate) palette_state: PaletteState,
+    pub(crate) preview_state: PreviewState,
+    ///
+    /// The folder open in the sidebar.
+    ///
+    pub(crate) file_tree: Option<FileTree>,
+    ///
+    /// Every file of the folder open in the sidebar, for quick open.
+    ///
+    pub(crate) file_index: Option<FileIndex>,
+    ///
+    /// The words in the files of the folder open in the sidebar, for the folder
+    /// search and quick open. Shared with the task that saves it.
+    ///
+    pub(crate) search_index: Option<Arc<SearchIndex>>,
+    pub(crate) quick_open_state: QuickOpenState,
+    pub(crate) folder_search_state: FolderSearchState,
     pub(crate) error: Option<AppIOError>,
+    ///
+    /// A message for the user shown in the statusbar until the next edit, e.g.
+    /// that files of the last session were not found.
+    ///
+    pub(crate) notice: Option<String>,
+    ///
+    /// When documents were last saved by autosave, to show it in the statusbar.
+    ///
+    pub(crate) autosaved_at: Option<Instant>,
     pub(crate) font_monospaced: Option<Font>,
     //pub(crate) app_configuration_changed: bool,
     pub(crate) keybind_manager: KeybindManager,
@@ -32,13 +66,21 @@ impl Default for AppState {
             scale_factor: 1.5,
             window_theme: Theme::Dark,
             syntax_theme: iced::highlighter::Theme::SolarizedDark,
-            file_path: None,
-            file_dirty: false,
-            file_content: Content::default(),
+            document_manager: DocumentManager::default(),
+            find_state: FindState::default(),
+            palette_state: PaletteState::default(),
+            preview_state: PreviewState::default(),
+            file_tree: None,
+            file_index: None,
+            search_index: None,
+            quick_open_state: QuickOpenState::default(),
+            folder_search_state: FolderSearchState::default(),
             error: None,
+            notice: None,
+            autosaved_at: None,
             font_monospaced: None,
             //app_configuration_changed: false,
-            keybind_manager: KeybindManager::default(),
+            keybind_manager: KeybindManager::load(),
         }
     }
 }
@@ -0,0 +1,76 @@
+//!
+//! Notespace-Editor
+//!
+//! Command palette, shown as a modal over the main window.
+//!
+
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::ui_const::{
+    UI_CONTROL_SPACING, UI_PALETTE_HEIGHT, UI_PALETTE_INPUT_ID, UI_PALETTE_SCROLLABLE_ID,
+    UI_STATUSBAR_TEXT_SIZE,
+};
+use crate::ui_style::AppStyle;
+use iced::widget::{Column, button, column, container, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length};
+use rust_i18n::t;
+
+pub struct AppCommandPalette;
+
+impl AppCommandPalette {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn view<'a>(&self, app_state: &'a AppState) -> Element<'a, AppMessage> {
+        let palette_state = &app_state.palette_state;
+        let results: Element<'a, AppMessage> = if palette_state.results.is_empty() {
+            text(t!("command_palette_no_results"))
+                .size(UI_STATUSBAR_TEXT_SIZE)
+                .into()
+        } else {
+            let rows = palette_state
+                .results
+                .iter()
+                .enumerate()
+                .map(|(index, command)| {
+                    button(
+                        row![
+                            text(command.label()).width(Length::Fill),
+                            text(command.shortcut(&app_state.keybind_manager))
+                                .size(UI_STATUSBAR_TEXT_SIZE),
+                        ]
+                        .spacing(UI_CONTROL_SPACING)
+                        .align_y(Alignment::Center),
+                    )
+                    .style(if index == palette_state.selected {
+                        AppStyle::button_style_primary
+                    } else {
+                        button::text
+                    })
+                    .width(Length::Fill)
+                    .on_press(AppMessage::RunCommand(index))
+                    .into()
+                });
+            scrollable(Column::with_children(rows))
+                .id(scrollable::Id::new(UI_PALETTE_SCROLLABLE_ID))
+                .height(UI_PALETTE_HEIGHT)
+                .into()
+        };
+
+        container(
+            column![
+                text_input(&t!("command_palette_placeholder"), &palette_state.query)
+                    .id(text_input::Id::new(UI_PALETTE_INPUT_ID))
+                    .on_input(AppMessage::CommandPaletteQueryChanged)
+                    .on_submit(AppMessage::CommandPaletteSubmit),
+                results,
+            ]
+            .spacing(UI_CONTROL_SPACING),
+        )
+        .width(600)
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+}
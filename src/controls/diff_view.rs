@@ -0,0 +1,107 @@
+//!
+//! Notespace-Editor
+//!
+//! Side-by-side view of the differences between two versions of a text.
+//!
+
+use crate::app_message::AppMessage;
+use crate::document::line_diff::{DiffRow, diff_lines};
+use crate::ui_const::{UI_DIFF_CONTEXT_LINES, UI_DIFF_HEIGHT, UI_DIFF_TEXT_SIZE};
+use crate::ui_style::AppStyle;
+use iced::widget::{Column, column, container, row, scrollable, text};
+use iced::{Element, Font, Length};
+
+///
+/// The lines of two texts and the rows that pair them up. Only the changed lines
+/// and `UI_DIFF_CONTEXT_LINES` around them are shown.
+///
+pub struct DiffView {
+    old_title: String,
+    new_title: String,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    rows: Vec<DiffRow>,
+}
+
+impl DiffView {
+    pub fn new(old_title: String, old_text: &str, new_title: String, new_text: &str) -> Self {
+        let old_lines: Vec<String> = old_text.lines().map(str::to_string).collect();
+        let new_lines: Vec<String> = new_text.lines().map(str::to_string).collect();
+        let rows = diff_lines(&old_lines, &new_lines);
+        Self {
+            old_title,
+            new_title,
+            old_lines,
+            new_lines,
+            rows,
+        }
+    }
+
+    pub fn view(&self, font: Font) -> Element<'_, AppMessage> {
+        let changed: Vec<bool> = self.rows.iter().map(|row| row.changed).collect();
+        let is_visible = |index: usize| {
+            let start = index.saturating_sub(UI_DIFF_CONTEXT_LINES);
+            let end = (index + UI_DIFF_CONTEXT_LINES + 1).min(changed.len());
+            changed[start..end].contains(&true)
+        };
+
+        let mut lines = Column::new();
+        let mut skipped = false;
+        for (index, diff_row) in self.rows.iter().enumerate() {
+            if !is_visible(index) {
+                skipped = true;
+                continue;
+            }
+            if skipped {
+                lines = lines.push(text("⋯").size(UI_DIFF_TEXT_SIZE));
+                skipped = false;
+            }
+            lines = lines.push(row![
+                Self::line_view(&self.old_lines, diff_row.old, changed[index], false, font),
+                Self::line_view(&self.new_lines, diff_row.new, changed[index], true, font),
+            ]);
+        }
+        if skipped {
+            lines = lines.push(text("⋯").size(UI_DIFF_TEXT_SIZE));
+        }
+
+        column![
+            row![
+                text(&self.old_title).width(Length::Fill),
+                text(&self.new_title).width(Length::Fill),
+            ],
+            scrollable(lines).height(UI_DIFF_HEIGHT),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    ///
+    /// One side of a row: the line number and the line, if the side has a line.
+    ///
+    fn line_view<'a>(
+        lines: &'a [String],
+        index: Option<usize>,
+        changed: bool,
+        added: bool,
+        font: Font,
+    ) -> Element<'a, AppMessage> {
+        let cell = match index {
+            Some(index) => container(row![
+                text(index + 1)
+                    .font(font)
+                    .size(UI_DIFF_TEXT_SIZE)
+                    .width(40)
+                    .style(AppStyle::text_style_secondary_weak),
+                text(&lines[index]).font(font).size(UI_DIFF_TEXT_SIZE),
+            ]),
+            None => container(text("").size(UI_DIFF_TEXT_SIZE)),
+        }
+        .width(Length::Fill);
+        match (index.is_some() && changed, added) {
+            (true, false) => cell.style(AppStyle::style_diff_removed).into(),
+            (true, true) => cell.style(AppStyle::style_diff_added).into(),
+            (false, _) => cell.into(),
+        }
+    }
+}
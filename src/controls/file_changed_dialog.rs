@@ -0,0 +1,83 @@
+//!
+//! Notespace-Editor
+//!
+//! Reload / Keep Mine / Compare dialog shown when the file of a document with
+//! unsaved changes was changed by another program.
+//!
+
+use crate::app_io::LoadedFile;
+use crate::app_message::AppMessage;
+use crate::controls::diff_view::DiffView;
+use crate::document::text_document::{DocumentId, TextDocument};
+use crate::ui_const::UI_CONTROL_SPACING;
+use crate::ui_style::AppStyle;
+use iced::widget::{button, column, container, horizontal_space, row, text};
+use iced::{Element, Font, Length};
+use rust_i18n::t;
+
+///
+/// A pending prompt for the document whose file was changed, with the file as it
+/// is now. `diff_view` is set while the changes are compared side by side.
+///
+pub struct FileChangedPrompt {
+    pub(crate) document_id: DocumentId,
+    pub(crate) loaded_file: LoadedFile,
+    pub(crate) diff_view: Option<DiffView>,
+}
+
+impl FileChangedPrompt {
+    pub fn new(document_id: DocumentId, loaded_file: LoadedFile) -> Self {
+        Self {
+            document_id,
+            loaded_file,
+            diff_view: None,
+        }
+    }
+
+    ///
+    /// The contents of the modal dialog for `document`.
+    ///
+    pub fn view<'a>(&'a self, document: &TextDocument, font: Font) -> Element<'a, AppMessage> {
+        let compare_button = match self.diff_view {
+            Some(_) => button(text(t!("file_changed_hide_diff")))
+                .style(AppStyle::button_style_secondary)
+                .on_press(AppMessage::FileChangedHideDiff),
+            None => button(text(t!("file_changed_show_diff")))
+                .style(AppStyle::button_style_secondary)
+                .on_press(AppMessage::FileChangedShowDiff),
+        };
+        let diff_view = self
+            .diff_view
+            .as_ref()
+            .map(|diff_view| diff_view.view(font));
+        container(
+            column![
+                text(t!("file_changed_title")).size(20),
+                text(t!("file_changed_message", file = document.title())),
+            ]
+            .push_maybe(diff_view)
+            .push(
+                row![
+                    horizontal_space(),
+                    button(text(t!("file_changed_reload")))
+                        .style(AppStyle::button_style_danger)
+                        .on_press(AppMessage::FileChangedReload),
+                    button(text(t!("file_changed_keep_mine")))
+                        .style(AppStyle::button_style_ok)
+                        .on_press(AppMessage::FileChangedKeepMine),
+                    compare_button,
+                ]
+                .spacing(UI_CONTROL_SPACING)
+                .width(Length::Fill),
+            )
+            .spacing(20),
+        )
+        .width(match self.diff_view {
+            Some(_) => 900,
+            None => 500,
+        })
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+}
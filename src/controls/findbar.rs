@@ -0,0 +1,140 @@
+//!
+//! Notespace-Editor
+//!
+//! Find and replace bar, shown under the toolbar.
+//!
+
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::keyboard::keybind_action::KeybindAction;
+use crate::ui_const::{
+    UI_CONTROL_PADDING, UI_CONTROL_SPACING, UI_FIND_INPUT_ID, UI_ICON_ARROW_DOWN, UI_ICON_ARROW_UP,
+    UI_STATUSBAR_TEXT_SIZE,
+};
+use crate::ui_style::AppStyle;
+use crate::ui_util::create_toolbar_button_small;
+use iced::widget::tooltip::Position;
+use iced::widget::{Column, button, column, container, horizontal_space, row, text, text_input};
+use iced::{Alignment, Element, Length};
+use rust_i18n::t;
+
+pub struct AppFindbar;
+
+impl AppFindbar {
+    pub fn new() -> Self {
+        Self {}
+    }
+    pub fn view<'a>(&self, app_state: &'a AppState) -> Element<'a, AppMessage> {
+        let find_state = &app_state.find_state;
+        let options = find_state.options;
+
+        let match_count = match (&find_state.error, find_state.current_match) {
+            (Some(_), _) => t!("find_invalid_pattern").to_string(),
+            (None, Some(index)) => format!("{}/{}", index + 1, find_state.matches.len()),
+            (None, None) if find_state.query.is_empty() => String::new(),
+            (None, None) => t!("find_no_results").to_string(),
+        };
+
+        let find_row = row![
+            text_input(&t!("find"), &find_state.query)
+                .id(text_input::Id::new(UI_FIND_INPUT_ID))
+                .on_input(AppMessage::FindQueryChanged)
+                .on_submit(AppMessage::FindNext)
+                .width(Length::FillPortion(1)),
+            AppFindbar::option_toggle(
+                "Aa",
+                "find_case_sensitive",
+                options.case_sensitive,
+                AppMessage::ToggleFindCaseSensitive,
+            ),
+            AppFindbar::option_toggle(
+                "W",
+                "find_whole_word",
+                options.whole_word,
+                AppMessage::ToggleFindWholeWord,
+            ),
+            AppFindbar::option_toggle(
+                ".*",
+                "find_regex",
+                options.regex,
+                AppMessage::ToggleFindRegex,
+            ),
+            text(match_count).size(UI_STATUSBAR_TEXT_SIZE).width(80),
+            create_toolbar_button_small(
+                UI_ICON_ARROW_UP,
+                "find_previous",
+                Some(AppMessage::FindPrevious),
+                !find_state.matches.is_empty(),
+                KeybindAction::FindPrevious,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_ARROW_DOWN,
+                "find_next",
+                Some(AppMessage::FindNext),
+                !find_state.matches.is_empty(),
+                KeybindAction::FindNext,
+                app_state,
+            ),
+            horizontal_space().width(Length::FillPortion(1)),
+            button(text("×"))
+                .style(button::text)
+                .on_press(AppMessage::CloseFindPanel),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .align_y(Alignment::Center);
+
+        let mut rows: Column<'a, AppMessage> = column![find_row].spacing(UI_CONTROL_SPACING);
+        if find_state.show_replace {
+            let has_matches = !find_state.matches.is_empty();
+            rows = rows.push(
+                row![
+                    text_input(&t!("replace"), &find_state.replacement)
+                        .on_input(AppMessage::ReplacementChanged)
+                        .on_submit(AppMessage::ReplaceOne)
+                        .width(Length::FillPortion(1)),
+                    button(text(t!("replace")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press_maybe(has_matches.then_some(AppMessage::ReplaceOne)),
+                    button(text(t!("replace_all")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press_maybe(has_matches.then_some(AppMessage::ReplaceAll)),
+                    horizontal_space().width(Length::FillPortion(1)),
+                ]
+                .spacing(UI_CONTROL_SPACING)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        container(rows)
+            .style(AppStyle::style_tabbar)
+            .padding(UI_CONTROL_PADDING)
+            .width(Length::Fill)
+            .into()
+    }
+
+    ///
+    /// A small button that shows whether a find option is enabled.
+    ///
+    pub fn option_toggle<'a>(
+        label: &'a str,
+        i18n_key: &'a str,
+        enabled: bool,
+        on_press: AppMessage,
+    ) -> Element<'a, AppMessage> {
+        let btn = button(text(label).size(UI_STATUSBAR_TEXT_SIZE))
+            .style(if enabled {
+                AppStyle::button_style_primary
+            } else {
+                AppStyle::button_style_secondary
+            })
+            .on_press(on_press);
+        iced::widget::tooltip(
+            btn,
+            iced::widget::Text::new(t!(i18n_key)),
+            Position::FollowCursor,
+        )
+        .style(AppStyle::style_tooltip)
+        .into()
+    }
+}
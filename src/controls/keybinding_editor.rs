@@ -0,0 +1,111 @@
+//!
+//! Notespace-Editor
+//!
+//! Keybinding editor, shown in the settings modal.
+//!
+//! Click an action and press a key combination to rebind it. The new keybind
+//! replaces the keybinds of the action, and is taken from any other action that
+//! used it.
+//!
+
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::keyboard::keybind_action::KeybindAction;
+use crate::keyboard::keybind_manager::KeybindManager;
+use crate::ui_const::{UI_CONTROL_SPACING, UI_KEYBINDING_EDITOR_HEIGHT, UI_STATUSBAR_TEXT_SIZE};
+use crate::ui_style::AppStyle;
+use iced::widget::tooltip::Position;
+use iced::widget::{Column, button, column, horizontal_space, row, scrollable, text, tooltip};
+use iced::{Alignment, Element, Length};
+use rust_i18n::t;
+
+pub struct AppKeybindingEditor {
+    ///
+    /// The action waiting for a new key combination.
+    ///
+    pub(crate) recording: Option<KeybindAction>,
+    ///
+    /// Keybinds taken from other actions by the last change.
+    ///
+    pub(crate) warning: Option<String>,
+}
+
+impl AppKeybindingEditor {
+    pub fn new() -> Self {
+        Self {
+            recording: None,
+            warning: None,
+        }
+    }
+
+    pub fn view<'a>(&self, app_state: &'a AppState) -> Element<'a, AppMessage> {
+        let keybind_manager = &app_state.keybind_manager;
+        let rows = KeybindAction::ALL.into_iter().map(|action| {
+            let keybinds = if self.recording == Some(action) {
+                t!("keybind_press_keys").to_string()
+            } else {
+                let keys = keybind_manager.get_keys_for_action(action);
+                if keys.is_empty() {
+                    t!("keybind_unbound").to_string()
+                } else {
+                    keys.iter()
+                        .map(|(mods, code)| {
+                            KeybindManager::get_display_text_for_keybind(*mods, *code)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            };
+            let is_default = keybind_manager.is_default(action);
+            row![
+                button(
+                    row![
+                        text(t!(action.i18n_key())).width(Length::Fill),
+                        text(keybinds).font(app_state.font_monospaced.unwrap_or_default()),
+                    ]
+                    .spacing(UI_CONTROL_SPACING),
+                )
+                .style(if self.recording == Some(action) {
+                    AppStyle::button_style_primary
+                } else {
+                    AppStyle::button_style_secondary
+                })
+                .width(Length::Fill)
+                .on_press(AppMessage::RecordKeybind(action)),
+                tooltip(
+                    button(text("↺"))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press_maybe((!is_default).then_some(AppMessage::ResetKeybind(action))),
+                    text(t!("keybind_reset")),
+                    Position::Left,
+                )
+                .style(AppStyle::style_tooltip),
+            ]
+            .spacing(UI_CONTROL_SPACING)
+            .align_y(Alignment::Center)
+            .into()
+        });
+
+        let warning = self.warning.clone().map(|warning| {
+            text(warning)
+                .size(UI_STATUSBAR_TEXT_SIZE)
+                .style(text::danger)
+        });
+
+        column![
+            row![
+                text(t!("keybindings")).size(16),
+                horizontal_space(),
+                button(text(t!("keybind_reset_all")))
+                    .style(AppStyle::button_style_secondary)
+                    .on_press(AppMessage::ResetAllKeybinds),
+            ]
+            .align_y(Alignment::Center),
+            scrollable(Column::with_children(rows).spacing(UI_CONTROL_SPACING))
+                .height(UI_KEYBINDING_EDITOR_HEIGHT),
+        ]
+        .push_maybe(warning)
+        .spacing(UI_CONTROL_SPACING)
+        .into()
+    }
+}
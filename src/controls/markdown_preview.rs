@@ -0,0 +1,254 @@
+//!
+//! Notespace-Editor
+//!
+//! The Markdown preview, the rendered active document shown beside the editor.
+//!
+
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::markdown::code_highlight::{HighlightedLine, code_colors};
+use crate::markdown::markdown_document::{Inlines, ListItem, MarkdownBlock, local_image_path};
+use crate::ui_const::{
+    UI_CONTROL_SPACING, UI_MARKDOWN_HEADING_SIZES, UI_MARKDOWN_LIST_MARKER_WIDTH,
+    UI_MARKDOWN_PADDING, UI_MARKDOWN_PREVIEW_SCROLLABLE_ID, UI_MARKDOWN_SPACING,
+    UI_MARKDOWN_TEXT_SIZE,
+};
+use crate::ui_style::AppStyle;
+use iced::alignment::Horizontal;
+use iced::font::{Style, Weight};
+use iced::widget::text::Span;
+use iced::widget::{
+    Column, Row, checkbox, column, container, horizontal_rule, image, rich_text, row, scrollable,
+    span, text,
+};
+use iced::{Element, Font, Length, border};
+use pulldown_cmark::Alignment;
+use std::path::Path;
+
+pub struct AppMarkdownPreview;
+
+///
+/// What the blocks of the preview are rendered with.
+///
+struct RenderContext<'a> {
+    font_monospaced: Font,
+    link_color: iced::Color,
+    code_background: iced::Color,
+    code_foreground: iced::Color,
+    base_directory: Option<&'a Path>,
+}
+
+impl AppMarkdownPreview {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn view<'a>(&self, app_state: &'a AppState) -> Element<'a, AppMessage> {
+        let preview_state = &app_state.preview_state;
+        let (code_background, code_foreground) = code_colors(app_state.syntax_theme);
+        let context = RenderContext {
+            font_monospaced: app_state.font_monospaced.unwrap_or(Font::MONOSPACE),
+            link_color: app_state.window_theme.palette().primary,
+            code_background,
+            code_foreground,
+            base_directory: preview_state.base_directory.as_deref(),
+        };
+        let blocks = AppMarkdownPreview::blocks(&preview_state.document.blocks, &context)
+            .padding(UI_MARKDOWN_PADDING);
+        scrollable(blocks)
+            .id(scrollable::Id::new(UI_MARKDOWN_PREVIEW_SCROLLABLE_ID))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn blocks<'a>(blocks: &'a [MarkdownBlock], context: &RenderContext) -> Column<'a, AppMessage> {
+        Column::with_children(
+            blocks
+                .iter()
+                .map(|block| AppMarkdownPreview::block(block, context)),
+        )
+        .spacing(UI_MARKDOWN_SPACING)
+        .width(Length::Fill)
+    }
+
+    fn block<'a>(block: &'a MarkdownBlock, context: &RenderContext) -> Element<'a, AppMessage> {
+        match block {
+            MarkdownBlock::Heading { level, content } => {
+                let bold = Font {
+                    weight: Weight::Bold,
+                    ..Font::DEFAULT
+                };
+                let size = UI_MARKDOWN_HEADING_SIZES[(*level as usize).clamp(1, 6) - 1];
+                rich_text(AppMarkdownPreview::spans(content, context, bold))
+                    .size(size)
+                    .into()
+            }
+            MarkdownBlock::Paragraph(content) => {
+                rich_text(AppMarkdownPreview::spans(content, context, Font::DEFAULT))
+                    .size(UI_MARKDOWN_TEXT_SIZE)
+                    .into()
+            }
+            MarkdownBlock::CodeBlock(lines) => AppMarkdownPreview::code_block(lines, context),
+            MarkdownBlock::List { start, items } => {
+                Column::with_children(items.iter().enumerate().map(|(index, item)| {
+                    let number = start.map(|start| start + index as u64);
+                    AppMarkdownPreview::list_item(item, number, context)
+                }))
+                .spacing(UI_CONTROL_SPACING)
+                .into()
+            }
+            MarkdownBlock::Quote(blocks) => container(AppMarkdownPreview::blocks(blocks, context))
+                .padding([8, 12])
+                .width(Length::Fill)
+                .style(AppStyle::style_markdown_quote)
+                .into(),
+            MarkdownBlock::Table {
+                alignments,
+                header,
+                rows,
+            } => AppMarkdownPreview::table(alignments, header, rows, context),
+            MarkdownBlock::Image { url, alt } => {
+                match local_image_path(url, context.base_directory) {
+                    Some(path) => image(image::Handle::from_path(path)).into(),
+                    //
+                    // Images on the web are not downloaded, they are shown by their
+                    // description.
+                    //
+                    None => text(format!("[{}]", alt))
+                        .size(UI_MARKDOWN_TEXT_SIZE)
+                        .color(context.link_color)
+                        .into(),
+                }
+            }
+            MarkdownBlock::Rule => horizontal_rule(1).into(),
+        }
+    }
+
+    ///
+    /// The spans of inline content, in the `font` of the block unless the content
+    /// is styled.
+    ///
+    fn spans<'a>(
+        inlines: &'a Inlines,
+        context: &RenderContext,
+        font: Font,
+    ) -> Vec<Span<'a, AppMessage>> {
+        inlines
+            .iter()
+            .map(|inline_text| {
+                let style = &inline_text.style;
+                let mut inline_font = match style.code {
+                    true => context.font_monospaced,
+                    false => font,
+                };
+                if style.bold {
+                    inline_font.weight = Weight::Bold;
+                }
+                if style.italic {
+                    inline_font.style = Style::Italic;
+                }
+                let color = match (&style.link, style.code) {
+                    (Some(_), _) => Some(context.link_color),
+                    (None, true) => Some(context.code_foreground),
+                    (None, false) => None,
+                };
+                span(inline_text.text.as_str())
+                    .font(inline_font)
+                    .strikethrough(style.strikethrough)
+                    .underline(style.link.is_some())
+                    .color_maybe(color)
+                    .background_maybe(style.code.then_some(context.code_background))
+            })
+            .collect()
+    }
+
+    fn code_block<'a>(
+        lines: &'a [HighlightedLine],
+        context: &RenderContext,
+    ) -> Element<'a, AppMessage> {
+        let mut spans: Vec<Span<'a, AppMessage>> = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                spans.push(span("\n"));
+            }
+            for (part, color) in line {
+                spans.push(span(part.as_str()).color(color.unwrap_or(context.code_foreground)));
+            }
+        }
+        let code_background = context.code_background;
+        container(
+            rich_text(spans)
+                .font(context.font_monospaced)
+                .size(UI_MARKDOWN_TEXT_SIZE - 2),
+        )
+        .padding(10)
+        .width(Length::Fill)
+        .style(move |_| container::Style {
+            background: Some(code_background.into()),
+            border: border::rounded(4),
+            ..container::Style::default()
+        })
+        .into()
+    }
+
+    ///
+    /// A list item, with a bullet, its `number` in an ordered list, or a checkbox
+    /// for a task.
+    ///
+    fn list_item<'a>(
+        item: &'a ListItem,
+        number: Option<u64>,
+        context: &RenderContext,
+    ) -> Element<'a, AppMessage> {
+        let marker: Element<'a, AppMessage> = match (item.task, number) {
+            (Some(done), _) => checkbox("", done).into(),
+            (None, Some(number)) => text(format!("{}.", number))
+                .size(UI_MARKDOWN_TEXT_SIZE)
+                .into(),
+            (None, None) => text("•").size(UI_MARKDOWN_TEXT_SIZE).into(),
+        };
+        row![
+            container(marker).width(UI_MARKDOWN_LIST_MARKER_WIDTH),
+            AppMarkdownPreview::blocks(&item.blocks, context).spacing(UI_CONTROL_SPACING),
+        ]
+        .into()
+    }
+
+    fn table<'a>(
+        alignments: &[Alignment],
+        header: &'a [Inlines],
+        rows: &'a [Vec<Inlines>],
+        context: &RenderContext,
+    ) -> Element<'a, AppMessage> {
+        let table_row = |cells: &'a [Inlines], font: Font| {
+            Row::with_children(cells.iter().enumerate().map(|(index, cell)| {
+                let align_x = match alignments.get(index) {
+                    Some(Alignment::Center) => Horizontal::Center,
+                    Some(Alignment::Right) => Horizontal::Right,
+                    _ => Horizontal::Left,
+                };
+                container(
+                    rich_text(AppMarkdownPreview::spans(cell, context, font))
+                        .size(UI_MARKDOWN_TEXT_SIZE)
+                        .width(Length::Fill)
+                        .align_x(align_x),
+                )
+                .padding([4, 8])
+                .width(Length::FillPortion(1))
+                .style(AppStyle::style_markdown_table_cell)
+                .into()
+            }))
+        };
+        let bold = Font {
+            weight: Weight::Bold,
+            ..Font::DEFAULT
+        };
+        column![table_row(header, bold)]
+            .extend(
+                rows.iter()
+                    .map(|cells| table_row(cells, Font::DEFAULT).into()),
+            )
+            .into()
+    }
+}
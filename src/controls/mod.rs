@@ -1,5 +1,17 @@
 //!
 //! GUI controls.
 //!
+pub(crate) mod command_palette;
+pub(crate) mod diff_view;
+pub(crate) mod file_changed_dialog;
+pub(crate) mod findbar;
+pub(crate) mod keybinding_editor;
+pub(crate) mod markdown_preview;
+pub(crate) mod quick_open;
+pub(crate) mod recovery_dialog;
+pub(crate) mod search_panel;
+pub(crate) mod sidebar;
 pub(crate) mod statusbar;
+pub(crate) mod tabbar;
 pub(crate) mod toolbar;
+pub(crate) mod unsaved_changes_dialog;
@@ -0,0 +1,104 @@
+//!
+//! Notespace-Editor
+//!
+//! Quick open, the fuzzy finder for the files of the open folder, shown as a modal
+//! over the main window.
+//!
+
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::ui_const::{
+    UI_CONTROL_SPACING, UI_PALETTE_HEIGHT, UI_QUICK_OPEN_INPUT_ID, UI_QUICK_OPEN_SCROLLABLE_ID,
+    UI_STATUSBAR_TEXT_SIZE,
+};
+use crate::ui_style::AppStyle;
+use iced::widget::{Column, button, column, container, row, scrollable, text, text_input};
+use iced::{Element, Font, Length};
+use rust_i18n::t;
+
+pub struct AppQuickOpen;
+
+impl AppQuickOpen {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn view<'a>(&self, app_state: &'a AppState) -> Element<'a, AppMessage> {
+        let quick_open_state = &app_state.quick_open_state;
+        let building = app_state
+            .file_index
+            .as_ref()
+            .is_some_and(|file_index| file_index.building);
+        let results: Element<'a, AppMessage> = if quick_open_state.results.is_empty() {
+            text(match building {
+                true => t!("quick_open_indexing"),
+                false => t!("quick_open_no_results"),
+            })
+            .size(UI_STATUSBAR_TEXT_SIZE)
+            .into()
+        } else {
+            let mut rows = Column::new();
+            for (index, (_, relative)) in quick_open_state.results.iter().enumerate() {
+                //
+                // The files found in the search index follow the files whose path
+                // matches, under a heading.
+                //
+                if index == quick_open_state.path_matches {
+                    rows = rows.push(
+                        text(t!("quick_open_in_contents"))
+                            .size(UI_STATUSBAR_TEXT_SIZE)
+                            .style(AppStyle::text_style_secondary_weak),
+                    );
+                }
+                rows = rows.push(
+                    button(text(relative.as_str()))
+                        .style(if index == quick_open_state.selected {
+                            AppStyle::button_style_primary
+                        } else {
+                            button::text
+                        })
+                        .width(Length::Fill)
+                        .on_press(AppMessage::QuickOpenOpen(index)),
+                );
+            }
+            scrollable(rows)
+                .id(scrollable::Id::new(UI_QUICK_OPEN_SCROLLABLE_ID))
+                .height(UI_PALETTE_HEIGHT)
+                .into()
+        };
+        let preview = quick_open_state
+            .preview
+            .as_ref()
+            .filter(|preview| Some(preview.path.as_path()) == quick_open_state.selected_path())
+            .map(|preview| {
+                text(preview.text.as_str())
+                    .font(app_state.font_monospaced.unwrap_or(Font::MONOSPACE))
+                    .size(UI_STATUSBAR_TEXT_SIZE)
+            })
+            .unwrap_or_else(|| text(""));
+
+        container(
+            column![
+                text_input(&t!("quick_open_placeholder"), &quick_open_state.query)
+                    .id(text_input::Id::new(UI_QUICK_OPEN_INPUT_ID))
+                    .on_input(AppMessage::QuickOpenQueryChanged)
+                    .on_submit(AppMessage::QuickOpenSubmit),
+                row![
+                    container(results).width(Length::FillPortion(2)),
+                    container(preview)
+                        .width(Length::FillPortion(3))
+                        .height(UI_PALETTE_HEIGHT)
+                        .clip(true)
+                        .padding(5)
+                        .style(container::bordered_box),
+                ]
+                .spacing(UI_CONTROL_SPACING),
+            ]
+            .spacing(UI_CONTROL_SPACING),
+        )
+        .width(900)
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+}
@@ -0,0 +1,140 @@
+//!
+//! Notespace-Editor
+//!
+//! Dialog shown on start when the recovery journal has snapshots of documents that
+//! were not saved.
+//!
+
+use crate::app_message::AppMessage;
+use crate::controls::diff_view::DiffView;
+use crate::document::recovery_journal::RecoveredSnapshot;
+use crate::ui_const::{UI_CONTROL_SPACING, UI_RECOVERY_LIST_HEIGHT};
+use crate::ui_style::AppStyle;
+use iced::widget::{Column, button, column, container, horizontal_space, row, scrollable, text};
+use iced::{Element, Font, Length};
+use rust_i18n::t;
+use std::path::Path;
+
+///
+/// The snapshots that have not been restored or discarded yet, and the snapshot
+/// being compared with its file.
+///
+pub struct RecoveryPrompt {
+    pub(crate) snapshots: Vec<RecoveredSnapshot>,
+    pub(crate) compare: Option<(usize, DiffView)>,
+}
+
+impl RecoveryPrompt {
+    pub fn new(snapshots: Vec<RecoveredSnapshot>) -> Self {
+        Self {
+            snapshots,
+            compare: None,
+        }
+    }
+
+    ///
+    /// The contents of the modal dialog.
+    ///
+    pub fn view(&self, font: Font) -> Element<'_, AppMessage> {
+        let contents = match &self.compare {
+            Some((index, diff_view)) => column![
+                text(t!("recovery_compare_title", file = self.title(*index))).size(20),
+                diff_view.view(font),
+                row![
+                    horizontal_space(),
+                    button(text(t!("recovery_restore")))
+                        .style(AppStyle::button_style_ok)
+                        .on_press(AppMessage::RecoveryRestore(*index)),
+                    button(text(t!("recovery_discard")))
+                        .style(AppStyle::button_style_danger)
+                        .on_press(AppMessage::RecoveryDiscard(*index)),
+                    button(text(t!("recovery_back")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press(AppMessage::RecoveryCompareClosed),
+                ]
+                .spacing(UI_CONTROL_SPACING),
+            ],
+            None => column![
+                text(t!("recovery_title")).size(20),
+                text(t!("recovery_message")),
+                container(scrollable(Column::with_children(
+                    (0..self.snapshots.len()).map(|index| self.snapshot_view(index))
+                )))
+                .max_height(UI_RECOVERY_LIST_HEIGHT),
+                row![
+                    horizontal_space(),
+                    button(text(t!("recovery_restore_all")))
+                        .style(AppStyle::button_style_ok)
+                        .on_press(AppMessage::RecoveryRestoreAll),
+                    button(text(t!("recovery_discard_all")))
+                        .style(AppStyle::button_style_danger)
+                        .on_press(AppMessage::RecoveryDiscardAll),
+                    button(text(t!("recovery_later")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press(AppMessage::RecoveryLater),
+                ]
+                .spacing(UI_CONTROL_SPACING),
+            ],
+        };
+        container(contents.spacing(20))
+            .width(800)
+            .padding(10)
+            .style(container::rounded_box)
+            .into()
+    }
+
+    ///
+    /// A row for the snapshot at `index`, with Restore / Compare / Discard buttons.
+    /// Untitled snapshots have no file to compare with.
+    ///
+    fn snapshot_view(&self, index: usize) -> Element<'_, AppMessage> {
+        let recovered = &self.snapshots[index];
+        let details = match &recovered.snapshot.file_path {
+            Some(file_path) => format!(
+                "{} - {}",
+                file_path.display(),
+                recovered.snapshot.saved_at_text()
+            ),
+            None => recovered.snapshot.saved_at_text(),
+        };
+        let warning = recovered
+            .file_changed
+            .then(|| text(t!("recovery_file_changed")).style(AppStyle::text_style_primary_weak));
+        row![
+            column![
+                text(self.title(index)),
+                text(details).style(AppStyle::text_style_secondary_weak),
+            ]
+            .push_maybe(warning)
+            .width(Length::Fill),
+            button(text(t!("recovery_restore")))
+                .style(AppStyle::button_style_ok)
+                .on_press(AppMessage::RecoveryRestore(index)),
+            button(text(t!("recovery_compare")))
+                .style(AppStyle::button_style_secondary)
+                .on_press_maybe(
+                    recovered
+                        .snapshot
+                        .file_path
+                        .is_some()
+                        .then_some(AppMessage::RecoveryCompare(index))
+                ),
+            button(text(t!("recovery_discard")))
+                .style(AppStyle::button_style_danger)
+                .on_press(AppMessage::RecoveryDiscard(index)),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .padding(5)
+        .into()
+    }
+
+    fn title(&self, index: usize) -> String {
+        self.snapshots[index]
+            .snapshot
+            .file_path
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| t!("untitled").to_string())
+    }
+}
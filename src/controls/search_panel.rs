@@ -0,0 +1,307 @@
+//!
+//! Notespace-Editor
+//!
+//! Search in the files of the open folder, shown in the sidebar instead of the file
+//! tree. Results are grouped by file and shown while the search runs.
+//!
+
+use crate::app_const::MAX_FOLDER_SEARCH_MATCHES;
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::controls::findbar::AppFindbar;
+use crate::ui_const::{
+    UI_CONTROL_SPACING, UI_FILE_TREE_TEXT_SIZE, UI_FOLDER_SEARCH_INPUT_ID,
+    UI_FOLDER_SEARCH_LINE_NUMBER_WIDTH, UI_ICON_FILE, UI_ICON_XMARK, UI_PALETTE_HEIGHT,
+};
+use crate::ui_style::AppStyle;
+use crate::workspace::folder_search::{FileMatches, LineMatch};
+use crate::workspace::folder_search_state::{FolderSearchState, ReplacePreview};
+use fa_iced as fa;
+use iced::font::Weight;
+use iced::widget::text::Span;
+use iced::widget::{
+    Column, button, column, container, horizontal_space, rich_text, row, scrollable, span, text,
+    text_input,
+};
+use iced::{Alignment, Element, Font, Length};
+use rust_i18n::t;
+use std::path::Path;
+
+pub struct AppSearchPanel;
+
+impl AppSearchPanel {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    ///
+    /// The query, replacement and glob inputs, the search options, and the results.
+    /// Paths are shown relative to `root`.
+    ///
+    pub fn view<'a>(&self, app_state: &'a AppState, root: &'a Path) -> Element<'a, AppMessage> {
+        let state = &app_state.folder_search_state;
+        let options = state.options;
+        let input = |placeholder: String, value: &'a str| {
+            text_input(&placeholder, value).size(UI_FILE_TREE_TEXT_SIZE)
+        };
+
+        let header = row![
+            text(t!("folder_search")).size(UI_FILE_TREE_TEXT_SIZE + 2),
+            horizontal_space(),
+            button(fa::iced_text_icon_solid(
+                UI_ICON_XMARK,
+                UI_FILE_TREE_TEXT_SIZE
+            ))
+            .padding([2, 4])
+            .style(button::text)
+            .on_press(AppMessage::CloseFolderSearch),
+        ]
+        .align_y(Alignment::Center);
+        let options_row = row![
+            AppFindbar::option_toggle(
+                "Aa",
+                "find_case_sensitive",
+                options.case_sensitive,
+                AppMessage::ToggleFolderSearchCaseSensitive,
+            ),
+            AppFindbar::option_toggle(
+                "W",
+                "find_whole_word",
+                options.whole_word,
+                AppMessage::ToggleFolderSearchWholeWord,
+            ),
+            AppFindbar::option_toggle(
+                ".*",
+                "find_regex",
+                options.regex,
+                AppMessage::ToggleFolderSearchRegex,
+            ),
+            horizontal_space(),
+            button(text(t!("folder_search_run")).size(UI_FILE_TREE_TEXT_SIZE - 2))
+                .style(AppStyle::button_style_secondary)
+                .on_press_maybe(
+                    (!state.query.is_empty()).then_some(AppMessage::FolderSearchSubmit),
+                ),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .align_y(Alignment::Center);
+        let can_replace = !state.running && !state.results.is_empty();
+        let replace_row = row![
+            input(t!("replace").to_string(), &state.replacement)
+                .on_input(AppMessage::FolderSearchReplacementChanged),
+            button(text(t!("replace_all")).size(UI_FILE_TREE_TEXT_SIZE - 2))
+                .style(AppStyle::button_style_secondary)
+                .on_press_maybe(can_replace.then_some(AppMessage::FolderReplacePreview)),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .align_y(Alignment::Center);
+
+        let mut results = Column::new().spacing(UI_CONTROL_SPACING);
+        for file_matches in &state.results {
+            results = results.push(AppSearchPanel::file_results(file_matches, root));
+        }
+
+        column![
+            header,
+            input(t!("find").to_string(), &state.query)
+                .id(text_input::Id::new(UI_FOLDER_SEARCH_INPUT_ID))
+                .on_input(AppMessage::FolderSearchQueryChanged)
+                .on_submit(AppMessage::FolderSearchSubmit),
+            options_row,
+            replace_row,
+            input(t!("folder_search_include").to_string(), &state.include)
+                .on_input(AppMessage::FolderSearchIncludeChanged)
+                .on_submit(AppMessage::FolderSearchSubmit),
+            input(t!("folder_search_exclude").to_string(), &state.exclude)
+                .on_input(AppMessage::FolderSearchExcludeChanged)
+                .on_submit(AppMessage::FolderSearchSubmit),
+            text(AppSearchPanel::status(state))
+                .size(UI_FILE_TREE_TEXT_SIZE - 2)
+                .style(AppStyle::text_style_secondary_weak),
+            scrollable(results).height(Length::Fill),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .into()
+    }
+
+    ///
+    /// The number of matches, or why there are none.
+    ///
+    fn status(state: &FolderSearchState) -> String {
+        let summary = |i18n_key: &str| {
+            t!(
+                i18n_key,
+                matches = state.match_count(),
+                files = state.results.len()
+            )
+            .to_string()
+        };
+        match (&state.error, state.running, state.searched) {
+            (Some(error), _, _) => error.clone(),
+            (None, true, _) => summary("folder_search_running"),
+            (None, false, Some(_)) if state.results.is_empty() => t!("find_no_results").to_string(),
+            (None, false, Some(_)) if state.truncated => format!(
+                "{} {}",
+                summary("folder_search_summary"),
+                t!("folder_search_truncated", max = MAX_FOLDER_SEARCH_MATCHES)
+            ),
+            (None, false, Some(_)) => summary("folder_search_summary"),
+            (None, false, None) => String::new(),
+        }
+    }
+
+    ///
+    /// The path of a file and its matching lines. Clicking a line opens the file at
+    /// the first match in the line.
+    ///
+    fn file_results<'a>(file_matches: &'a FileMatches, root: &Path) -> Element<'a, AppMessage> {
+        let relative = file_matches
+            .path
+            .strip_prefix(root)
+            .unwrap_or(&file_matches.path)
+            .display()
+            .to_string();
+        let mut lines = column![
+            row![
+                fa::iced_text_icon_solid(UI_ICON_FILE, UI_FILE_TREE_TEXT_SIZE - 2),
+                text(relative).size(UI_FILE_TREE_TEXT_SIZE),
+            ]
+            .spacing(UI_CONTROL_SPACING)
+            .align_y(Alignment::Center)
+        ];
+        for line_match in &file_matches.lines {
+            lines = lines.push(
+                button(
+                    row![
+                        text(line_match.matches[0].line + 1)
+                            .size(UI_FILE_TREE_TEXT_SIZE - 2)
+                            .width(UI_FOLDER_SEARCH_LINE_NUMBER_WIDTH)
+                            .style(AppStyle::text_style_secondary_weak),
+                        rich_text(AppSearchPanel::excerpt_spans(line_match))
+                            .size(UI_FILE_TREE_TEXT_SIZE - 2),
+                    ]
+                    .spacing(UI_CONTROL_SPACING),
+                )
+                .padding([1, 4])
+                .width(Length::Fill)
+                .style(button::text)
+                .on_press(AppMessage::FolderSearchOpenMatch(
+                    file_matches.path.clone(),
+                    line_match.matches[0],
+                )),
+            );
+        }
+        lines.into()
+    }
+
+    ///
+    /// The excerpt of a matching line, with the matches in bold.
+    ///
+    fn excerpt_spans(line_match: &LineMatch) -> Vec<Span<'_, AppMessage>> {
+        let excerpt = line_match.excerpt.as_str();
+        let bold = Font {
+            weight: Weight::Bold,
+            ..Font::DEFAULT
+        };
+        let mut spans = Vec::new();
+        let mut position = 0;
+        for find_match in &line_match.matches {
+            let start = find_match.start.saturating_sub(line_match.excerpt_start);
+            let end = find_match.end.saturating_sub(line_match.excerpt_start);
+            if start < position || end > excerpt.len() {
+                break;
+            }
+            spans.push(span(&excerpt[position..start]));
+            spans.push(span(&excerpt[start..end]).font(bold));
+            position = end;
+        }
+        spans.push(span(&excerpt[position..]));
+        spans
+    }
+
+    ///
+    /// The contents of the modal dialog that shows every change of a replacement
+    /// across files before they are written.
+    ///
+    pub fn replace_preview_view<'a>(
+        replace_preview: &'a ReplacePreview,
+        root: &Path,
+        font: Font,
+    ) -> Element<'a, AppMessage> {
+        let relative = |path: &Path| {
+            path.strip_prefix(root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        let mut files = Column::new().spacing(UI_CONTROL_SPACING);
+        for file_replacement in &replace_preview.files {
+            let mut changes = column![text(relative(&file_replacement.path))];
+            for (line, old, new) in &file_replacement.changes {
+                changes = changes
+                    .push(
+                        AppSearchPanel::change_line(*line, old, font)
+                            .style(AppStyle::style_diff_removed),
+                    )
+                    .push(
+                        AppSearchPanel::change_line(*line, new, font)
+                            .style(AppStyle::style_diff_added),
+                    );
+            }
+            files = files.push(changes);
+        }
+        for (path, reason) in &replace_preview.skipped {
+            files = files.push(
+                text(format!("{}: {}", relative(path), reason))
+                    .style(AppStyle::text_style_secondary_weak),
+            );
+        }
+
+        container(
+            column![
+                text(t!("folder_replace_title")).size(20),
+                text(t!(
+                    "folder_replace_message",
+                    matches = replace_preview.count(),
+                    files = replace_preview.files.len()
+                )),
+                scrollable(files).height(UI_PALETTE_HEIGHT),
+                row![
+                    horizontal_space(),
+                    button(text(t!("replace_all")))
+                        .style(AppStyle::button_style_danger)
+                        .on_press_maybe(
+                            (!replace_preview.files.is_empty())
+                                .then_some(AppMessage::FolderReplaceConfirm)
+                        ),
+                    button(text(t!("unsaved_changes_cancel")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press(AppMessage::FolderReplaceCancel),
+                ]
+                .spacing(UI_CONTROL_SPACING)
+                .width(Length::Fill),
+            ]
+            .spacing(20),
+        )
+        .width(900)
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    fn change_line<'a>(
+        line: usize,
+        line_text: &'a str,
+        font: Font,
+    ) -> container::Container<'a, AppMessage> {
+        container(row![
+            text(line + 1)
+                .font(font)
+                .size(UI_FILE_TREE_TEXT_SIZE - 2)
+                .width(UI_FOLDER_SEARCH_LINE_NUMBER_WIDTH)
+                .style(AppStyle::text_style_secondary_weak),
+            text(line_text).font(font).size(UI_FILE_TREE_TEXT_SIZE - 2),
+        ])
+        .width(Length::Fill)
+    }
+}
@@ -0,0 +1,261 @@
+//!
+//! Notespace-Editor
+//!
+//! Sidebar with the file tree of the open folder, shown left of the editor.
+//!
+
+use crate::app_message::AppMessage;
+use crate::ui_const::{
+    UI_CONTROL_SPACING, UI_FILE_TREE_INDENT, UI_FILE_TREE_INPUT_ID, UI_FILE_TREE_TEXT_SIZE,
+    UI_ICON_CHEVRON_DOWN, UI_ICON_CHEVRON_RIGHT, UI_ICON_FILE, UI_ICON_FIND, UI_ICON_FOLDER,
+    UI_ICON_XMARK,
+};
+use crate::ui_style::AppStyle;
+use crate::workspace::file_tree::{FileTree, FileTreeEdit, FileTreeEntry};
+use fa_iced as fa;
+use iced::widget::{
+    Column, Space, button, column, container, horizontal_space, mouse_area, row, scrollable, text,
+    text_input,
+};
+use iced::{Alignment, Element, Length};
+use rust_i18n::t;
+use std::path::Path;
+
+pub struct AppSidebar;
+
+impl AppSidebar {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    ///
+    /// The header with the folder name, the entry being created, renamed or moved,
+    /// and the tree. The file of the active document is highlighted.
+    ///
+    pub fn view<'a>(
+        &self,
+        file_tree: &'a FileTree,
+        active_file: Option<&Path>,
+    ) -> Element<'a, AppMessage> {
+        let root = file_tree.root.clone();
+        let header = row![
+            text(file_tree.root_name()).size(UI_FILE_TREE_TEXT_SIZE + 2),
+            horizontal_space(),
+            AppSidebar::header_button(
+                UI_ICON_FILE,
+                AppMessage::FileTreeStartEdit(FileTreeEdit::NewFile(root.clone())),
+            ),
+            AppSidebar::header_button(
+                UI_ICON_FOLDER,
+                AppMessage::FileTreeStartEdit(FileTreeEdit::NewFolder(root)),
+            ),
+            AppSidebar::header_button(UI_ICON_FIND, AppMessage::OpenFolderSearch),
+            AppSidebar::header_button(UI_ICON_XMARK, AppMessage::CloseFolder),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .align_y(Alignment::Center);
+
+        let mut entries = Column::new();
+        for (depth, entry) in file_tree.visible_entries() {
+            let highlighted = file_tree.selected.as_deref() == Some(entry.path.as_path())
+                || active_file == Some(entry.path.as_path());
+            entries = entries.push(AppSidebar::entry(file_tree, depth, entry, highlighted));
+            if file_tree.context_menu.as_ref() == Some(&entry.path) {
+                entries = entries.push(AppSidebar::context_menu(file_tree, depth, entry));
+            }
+        }
+
+        column![header]
+            .push_maybe(
+                file_tree
+                    .edit
+                    .as_ref()
+                    .map(|(edit, value)| AppSidebar::edit_input(file_tree, edit, value)),
+            )
+            .push(scrollable(entries).height(Length::Fill))
+            .spacing(UI_CONTROL_SPACING)
+            .into()
+    }
+
+    fn header_button<'a>(icon: &'a str, on_press: AppMessage) -> Element<'a, AppMessage> {
+        button(fa::iced_text_icon_solid(icon, UI_FILE_TREE_TEXT_SIZE))
+            .padding([2, 4])
+            .style(button::text)
+            .on_press(on_press)
+            .into()
+    }
+
+    ///
+    /// A row of the tree. Directories are expanded or collapsed on click, files
+    /// are opened. A right-click shows the actions for the entry.
+    ///
+    fn entry<'a>(
+        file_tree: &FileTree,
+        depth: usize,
+        entry: &'a FileTreeEntry,
+        highlighted: bool,
+    ) -> Element<'a, AppMessage> {
+        let (icon, on_press) = match (entry.is_dir, file_tree.is_expanded(&entry.path)) {
+            (true, true) => (
+                UI_ICON_CHEVRON_DOWN,
+                AppMessage::FileTreeToggle(entry.path.clone()),
+            ),
+            (true, false) => (
+                UI_ICON_CHEVRON_RIGHT,
+                AppMessage::FileTreeToggle(entry.path.clone()),
+            ),
+            (false, _) => (
+                UI_ICON_FILE,
+                AppMessage::FileTreeOpenFile(entry.path.clone()),
+            ),
+        };
+        let entry_button = button(
+            row![
+                Space::with_width(UI_FILE_TREE_INDENT * depth as u16),
+                fa::iced_text_icon_solid(icon, UI_FILE_TREE_TEXT_SIZE - 2),
+                text(entry.name()).size(UI_FILE_TREE_TEXT_SIZE),
+            ]
+            .spacing(UI_CONTROL_SPACING)
+            .align_y(Alignment::Center),
+        )
+        .padding([1, 4])
+        .width(Length::Fill)
+        .on_press(on_press);
+        let entry_button = match highlighted {
+            true => entry_button.style(AppStyle::button_style_primary),
+            false => entry_button.style(button::text),
+        };
+        mouse_area(entry_button)
+            .on_right_press(AppMessage::FileTreeContextMenu(entry.path.clone()))
+            .into()
+    }
+
+    ///
+    /// The actions for the right-clicked entry, shown below it.
+    ///
+    fn context_menu<'a>(
+        file_tree: &FileTree,
+        depth: usize,
+        entry: &'a FileTreeEntry,
+    ) -> Element<'a, AppMessage> {
+        let directory = file_tree.directory_of(&entry.path);
+        let action = |i18n_key: &'static str, message: AppMessage| {
+            button(text(t!(i18n_key)).size(UI_FILE_TREE_TEXT_SIZE - 2))
+                .padding([1, 4])
+                .style(AppStyle::button_style_secondary)
+                .on_press(message)
+        };
+        let actions = column![
+            action(
+                "file_tree_new_file",
+                AppMessage::FileTreeStartEdit(FileTreeEdit::NewFile(directory.clone())),
+            ),
+            action(
+                "file_tree_new_folder",
+                AppMessage::FileTreeStartEdit(FileTreeEdit::NewFolder(directory)),
+            ),
+            action(
+                "file_tree_rename",
+                AppMessage::FileTreeStartEdit(FileTreeEdit::Rename(entry.path.clone())),
+            ),
+            action(
+                "file_tree_move",
+                AppMessage::FileTreeStartEdit(FileTreeEdit::Move(entry.path.clone())),
+            ),
+            action(
+                "file_tree_delete",
+                AppMessage::FileTreeDelete(entry.path.clone())
+            )
+            .style(AppStyle::button_style_danger),
+        ]
+        .spacing(2);
+        row![
+            Space::with_width(UI_FILE_TREE_INDENT * (depth as u16 + 1)),
+            actions
+        ]
+        .padding([2, 4])
+        .into()
+    }
+
+    ///
+    /// The input for the name of a new entry, the new name of an entry, or the
+    /// directory an entry is moved to.
+    ///
+    fn edit_input<'a>(
+        file_tree: &FileTree,
+        edit: &FileTreeEdit,
+        value: &'a str,
+    ) -> Element<'a, AppMessage> {
+        let relative = |path: &Path| {
+            path.strip_prefix(&file_tree.root)
+                .ok()
+                .filter(|relative| !relative.as_os_str().is_empty())
+                .map(|relative| relative.display().to_string())
+                .unwrap_or_else(|| file_tree.root_name())
+        };
+        let label = match edit {
+            FileTreeEdit::NewFile(directory) => {
+                t!("file_tree_new_file_in", folder = relative(directory))
+            }
+            FileTreeEdit::NewFolder(directory) => {
+                t!("file_tree_new_folder_in", folder = relative(directory))
+            }
+            FileTreeEdit::Rename(path) => t!("file_tree_rename_to", name = relative(path)),
+            FileTreeEdit::Move(path) => t!("file_tree_move_to", name = relative(path)),
+        };
+        let valid = file_tree.edit_target(edit, value).is_some();
+        column![
+            text(label).size(UI_FILE_TREE_TEXT_SIZE - 2),
+            text_input("", value)
+                .id(text_input::Id::new(UI_FILE_TREE_INPUT_ID))
+                .size(UI_FILE_TREE_TEXT_SIZE)
+                .on_input(AppMessage::FileTreeEditChanged)
+                .on_submit(AppMessage::FileTreeEditSubmit),
+            row![
+                horizontal_space(),
+                button(text(t!("ok")).size(UI_FILE_TREE_TEXT_SIZE - 2))
+                    .style(AppStyle::button_style_ok)
+                    .on_press_maybe(valid.then_some(AppMessage::FileTreeEditSubmit)),
+                button(text(t!("unsaved_changes_cancel")).size(UI_FILE_TREE_TEXT_SIZE - 2))
+                    .style(AppStyle::button_style_secondary)
+                    .on_press(AppMessage::FileTreeEditCancel),
+            ]
+            .spacing(UI_CONTROL_SPACING),
+        ]
+        .spacing(UI_CONTROL_SPACING)
+        .into()
+    }
+
+    ///
+    /// The contents of the modal dialog that asks before `path` is deleted.
+    ///
+    pub fn delete_prompt_view<'a>(file_tree: &FileTree, path: &Path) -> Element<'a, AppMessage> {
+        let name = path
+            .strip_prefix(&file_tree.root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        container(
+            column![
+                text(t!("file_tree_delete_title")).size(20),
+                text(t!("file_tree_delete_message", name = name)),
+                row![
+                    horizontal_space(),
+                    button(text(t!("file_tree_delete")))
+                        .style(AppStyle::button_style_danger)
+                        .on_press(AppMessage::FileTreeDeleteConfirm),
+                    button(text(t!("unsaved_changes_cancel")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press(AppMessage::FileTreeDeleteCancel),
+                ]
+                .spacing(UI_CONTROL_SPACING)
+                .width(Length::Fill),
+            ]
+            .spacing(20),
+        )
+        .width(500)
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+}
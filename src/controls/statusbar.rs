@@ -9,11 +9,14 @@ use crate::app_state::AppState;
 use crate::ui_const::{
     UI_CONTROL_PADDING, UI_CONTROL_SPACING, UI_STATUSBAR_TEXT_SIZE, UI_TOOLBAR_ICON_SIZE,
 };
+use crate::ui_style::AppStyle;
 use fa_iced as fa;
+use iced::widget::tooltip::Position;
 use iced::{
     Element, Font, Renderer, Theme,
-    widget::{Text, horizontal_space, row, text},
+    widget::{Text, button, horizontal_space, row, text, tooltip},
 };
+use rust_i18n::t;
 use std::path::Path;
 
 pub struct AppStatusbar;
@@ -23,8 +26,9 @@ impl AppStatusbar {
         Self {}
     }
     pub fn view<'a>(&self, app_state: &'a AppState) -> iced::Element<'a, AppMessage> {
+        let document = app_state.document_manager.active();
         let file_dirty = {
-            match app_state.file_dirty {
+            match document.file_dirty {
                 true => iced_text_icon_circle_xmark(),
                 false => iced_text_icon_circle_check(),
             }
@@ -38,9 +42,10 @@ impl AppStatusbar {
         // Create text() control with the file_path.
         // Handle paths that crash with and_then().
         //
-        let file_path_display: Text<Theme, Renderer> = match &app_state.error {
-            Some(e) => Text::new(format!("Error: {}", e.to_string())),
-            None => match app_state.file_path.as_deref().and_then(Path::to_str) {
+        let file_path_display: Text<Theme, Renderer> = match (&app_state.error, &app_state.notice) {
+            (Some(e), _) => Text::new(format!("Error: {}", e.to_string())),
+            (None, Some(notice)) => text(notice).size(UI_STATUSBAR_TEXT_SIZE),
+            (None, None) => match document.file_path.as_deref().and_then(Path::to_str) {
                 Some(file_path) => text(file_path)
                     .font(app_state.font_monospaced.unwrap_or(Font::MONOSPACE))
                     .size(UI_STATUSBAR_TEXT_SIZE),
@@ -49,20 +54,93 @@ impl AppStatusbar {
         };
 
         let cursor_position = {
-            let (l, c) = app_state.file_content.cursor_position();
+            let (l, c) = document.file_content.cursor_position();
             text(format!("[ {}:{} ]", l, c))
                 .font(app_state.font_monospaced.unwrap_or(Font::MONOSPACE))
         };
 
-        row![
-            file_dirty,
-            file_path_display,
-            horizontal_space(),
-            cursor_position,
-        ]
-        .spacing(UI_CONTROL_SPACING)
-        .padding(UI_CONTROL_PADDING)
-        .into()
+        //
+        // Problems in the keybindings file, listed in the tooltip.
+        //
+        let keymap_problems = &app_state.keybind_manager.problems;
+        let keymap_warning = (!keymap_problems.is_empty()).then(|| {
+            let details = keymap_problems
+                .iter()
+                .map(|problem| problem.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            tooltip(
+                text(t!("keymap_problems", count = keymap_problems.len()))
+                    .size(UI_STATUSBAR_TEXT_SIZE)
+                    .style(text::danger),
+                text(details).size(UI_STATUSBAR_TEXT_SIZE),
+                Position::Top,
+            )
+            .style(AppStyle::style_tooltip)
+        });
+
+        //
+        // The file was deleted or renamed by another program. Saving writes it again.
+        //
+        let file_missing = document.file_missing.then(|| {
+            tooltip(
+                text(t!("file_missing"))
+                    .size(UI_STATUSBAR_TEXT_SIZE)
+                    .style(text::danger),
+                text(t!("file_missing_tooltip")).size(UI_STATUSBAR_TEXT_SIZE),
+                Position::Top,
+            )
+            .style(AppStyle::style_tooltip)
+        });
+
+        //
+        // Clicking the encoding offers to reopen the file with another encoding.
+        //
+        let encoding = tooltip(
+            button(text(document.encoding.label()).size(UI_STATUSBAR_TEXT_SIZE))
+                .style(button::text)
+                .padding(0)
+                .on_press(AppMessage::OpenCommandPaletteWithQuery(
+                    t!("reopen_with_encoding").to_string(),
+                )),
+            text(t!("change_encoding")).size(UI_STATUSBAR_TEXT_SIZE),
+            Position::Top,
+        )
+        .style(AppStyle::style_tooltip);
+
+        //
+        // Clicking the line ending converts the document, e.g. from LF to CRLF.
+        //
+        let line_ending = tooltip(
+            button(text(document.line_ending.label()).size(UI_STATUSBAR_TEXT_SIZE))
+                .style(button::text)
+                .padding(0)
+                .on_press(AppMessage::ConvertLineEnding(
+                    document.line_ending.toggled(),
+                )),
+            text(t!(
+                "convert_line_ending",
+                line_ending = document.line_ending.toggled().label()
+            ))
+            .size(UI_STATUSBAR_TEXT_SIZE),
+            Position::Top,
+        )
+        .style(AppStyle::style_tooltip);
+
+        let autosaved = app_state
+            .autosaved_at
+            .map(|_| text(t!("autosaved")).size(UI_STATUSBAR_TEXT_SIZE));
+
+        row![file_dirty, file_path_display, horizontal_space(),]
+            .push_maybe(file_missing)
+            .push_maybe(autosaved)
+            .push_maybe(keymap_warning)
+            .push(encoding)
+            .push(line_ending)
+            .push(cursor_position)
+            .spacing(UI_CONTROL_SPACING)
+            .padding(UI_CONTROL_PADDING)
+            .into()
     }
 }
 
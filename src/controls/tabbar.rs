@@ -0,0 +1,71 @@
+//!
+//! Notespace-Editor
+//!
+//! Tab strip for the open documents, shown between the toolbar and the editor.
+//!
+
+use crate::app_message::AppMessage;
+use crate::app_state::AppState;
+use crate::document::text_document::TextDocument;
+use crate::ui_const::{UI_CONTROL_PADDING, UI_CONTROL_SPACING, UI_TAB_TEXT_SIZE};
+use crate::ui_style::AppStyle;
+use iced::widget::{Row, button, container, row, scrollable, text};
+use iced::{Element, Length};
+
+pub struct AppTabbar;
+
+impl AppTabbar {
+    pub fn new() -> Self {
+        Self {}
+    }
+    pub fn view<'a>(&self, app_state: &'a AppState) -> Element<'a, AppMessage> {
+        let document_manager = &app_state.document_manager;
+        let tabs = document_manager
+            .documents()
+            .iter()
+            .enumerate()
+            .map(|(index, document)| {
+                AppTabbar::tab(document, index == document_manager.active_index())
+            });
+
+        let tab_row = Row::with_children(tabs)
+            .spacing(UI_CONTROL_SPACING)
+            .padding(UI_CONTROL_PADDING);
+
+        container(
+            scrollable(tab_row).direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::new().width(2).scroller_width(2),
+            )),
+        )
+        .style(AppStyle::style_tabbar)
+        .width(Length::Fill)
+        .into()
+    }
+
+    ///
+    /// A single tab: the document title, a modified marker and a close button.
+    ///
+    fn tab(document: &TextDocument, active: bool) -> Element<'_, AppMessage> {
+        let title = if document.file_dirty {
+            format!("● {}", document.title())
+        } else {
+            document.title()
+        };
+        let close_button = button(text("×").size(UI_TAB_TEXT_SIZE))
+            .padding([0, 4])
+            .style(button::text)
+            .on_press(AppMessage::CloseDocument(document.id));
+        let tab_button = button(
+            row![text(title).size(UI_TAB_TEXT_SIZE), close_button]
+                .spacing(UI_CONTROL_SPACING)
+                .align_y(iced::Alignment::Center),
+        )
+        .padding([2, 8])
+        .on_press(AppMessage::SelectDocument(document.id));
+        if active {
+            tab_button.style(AppStyle::button_style_primary).into()
+        } else {
+            tab_button.style(AppStyle::button_style_secondary).into()
+        }
+    }
+}
@@ -8,7 +8,9 @@ use crate::app_message::AppMessage;
 use crate::app_state::AppState;
 use crate::keyboard::keybind_action::KeybindAction;
 use crate::ui_const::{
-    UI_CONTROL_PADDING, UI_CONTROL_SPACING, UI_TOOLBAR_BUTTON_SIZE, UI_TOOLBAR_ICON_SIZE,
+    UI_CONTROL_PADDING, UI_CONTROL_SPACING, UI_ICON_COPY, UI_ICON_CUT, UI_ICON_EYE, UI_ICON_FIND,
+    UI_ICON_FOLDER_OPEN, UI_ICON_PASTE, UI_ICON_REDO, UI_ICON_UNDO, UI_RECENT_FILES_WIDTH,
+    UI_SCALE_FACTORS, UI_TOOLBAR_BUTTON_SIZE, UI_TOOLBAR_ICON_SIZE,
 };
 use crate::ui_style::AppStyle;
 use crate::ui_util::create_toolbar_button_small;
@@ -20,16 +22,56 @@ use iced::{
     widget::{Container, button, container, horizontal_space, row, tooltip},
 };
 use rust_i18n::t;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 pub struct AppToolbar;
 
+///
+/// A file in the recent files list, shown by its name and directory.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecentFile(PathBuf);
+
+impl fmt::Display for RecentFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file_name = self.0.file_name().unwrap_or(self.0.as_os_str());
+        match self.0.parent().filter(|parent| *parent != Path::new("")) {
+            Some(parent) => write!(f, "{} ({})", file_name.to_string_lossy(), parent.display()),
+            None => write!(f, "{}", file_name.to_string_lossy()),
+        }
+    }
+}
+
 impl AppToolbar {
     pub fn new() -> Self {
         Self {}
     }
-    pub fn view(&self, app_state: &AppState) -> Element<AppMessage> {
+    pub fn view<'a>(
+        &'a self,
+        app_state: &'a AppState,
+        recent_files: &[PathBuf],
+    ) -> Element<'a, AppMessage> {
+        let recent_files: Vec<RecentFile> = recent_files.iter().cloned().map(RecentFile).collect();
+        let recent_files_placeholder = match recent_files.is_empty() {
+            true => t!("no_recent_files"),
+            false => t!("open_recent"),
+        };
+        let recent_files_picker = PickList::new(recent_files, None::<RecentFile>, |recent_file| {
+            AppMessage::OpenRecentFile(recent_file.0)
+        })
+        .placeholder(recent_files_placeholder)
+        .on_open(AppMessage::RecentFilesShown)
+        .width(UI_RECENT_FILES_WIDTH);
+        let recent_files_tooltip = tooltip(
+            recent_files_picker,
+            iced::widget::Text::new(t!("open_recent")),
+            Position::FollowCursor,
+        )
+        .style(AppStyle::style_tooltip);
+
         let scale_factor_picker = PickList::new(
-            vec![0.5, 0.75, 1.0, 1.25, 1.50, 1.75, 2.0, 2.25, 2.5, 3.0, 4.0],
+            UI_SCALE_FACTORS.to_vec(),
             Some(app_state.scale_factor),
             AppMessage::UpdateScale,
         );
@@ -84,6 +126,9 @@ impl AppToolbar {
         )
         .style(AppStyle::style_tooltip);
 
+        let document = app_state.document_manager.active();
+        let has_selection = document.file_content.selection().is_some();
+
         let row = row![
             create_toolbar_button_small(
                 fa::FA_ICON_NEW,
@@ -91,7 +136,7 @@ impl AppToolbar {
                 Some(AppMessage::NewFile),
                 true,
                 KeybindAction::NewFile,
-                &app_state,
+                app_state,
             ),
             create_toolbar_button_small(
                 fa::FA_ICON_OPEN,
@@ -99,15 +144,86 @@ impl AppToolbar {
                 Some(AppMessage::OpenFileFromDialog),
                 true,
                 KeybindAction::OpenFile,
-                &app_state,
+                app_state,
+            ),
+            recent_files_tooltip,
+            create_toolbar_button_small(
+                UI_ICON_FOLDER_OPEN,
+                "action_open_folder",
+                Some(AppMessage::OpenFolderFromDialog),
+                true,
+                KeybindAction::OpenFolder,
+                app_state,
             ),
             create_toolbar_button_small(
                 fa::FA_ICON_SAVE,
                 "file_save",
-                app_state.file_dirty.then_some(AppMessage::SaveFile),
+                app_state
+                    .document_manager
+                    .active()
+                    .file_dirty
+                    .then_some(AppMessage::SaveFile),
                 true,
                 KeybindAction::SaveFile,
-                &app_state,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_CUT,
+                "action_cut_text",
+                Some(AppMessage::RunKeybindAction(KeybindAction::CutText)),
+                has_selection,
+                KeybindAction::CutText,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_COPY,
+                "action_copy_text",
+                Some(AppMessage::RunKeybindAction(KeybindAction::CopyText)),
+                has_selection,
+                KeybindAction::CopyText,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_PASTE,
+                "action_paste_text",
+                Some(AppMessage::RunKeybindAction(KeybindAction::PasteText)),
+                true,
+                KeybindAction::PasteText,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_UNDO,
+                "action_undo",
+                Some(AppMessage::RunKeybindAction(KeybindAction::Undo)),
+                document.history.can_undo(),
+                KeybindAction::Undo,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_REDO,
+                "action_redo",
+                Some(AppMessage::RunKeybindAction(KeybindAction::Redo)),
+                document.history.can_redo(),
+                KeybindAction::Redo,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_FIND,
+                "find",
+                Some(AppMessage::OpenFindPanel),
+                true,
+                KeybindAction::Find,
+                app_state,
+            ),
+            create_toolbar_button_small(
+                UI_ICON_EYE,
+                "action_toggle_markdown_preview",
+                Some(AppMessage::RunKeybindAction(
+                    KeybindAction::ToggleMarkdownPreview
+                )),
+                true,
+                KeybindAction::ToggleMarkdownPreview,
+                app_state,
             ),
             create_toolbar_button_small(
                 fa::FA_ICON_GEAR,
@@ -115,7 +231,7 @@ impl AppToolbar {
                 Some(AppMessage::OpenAppConfigurationModal),
                 true,
                 KeybindAction::ShowSettings,
-                &app_state,
+                app_state,
             ),
             create_toolbar_button_small(
                 fa::FA_ICON_INFO,
@@ -123,7 +239,7 @@ impl AppToolbar {
                 Some(AppMessage::OpenAppConfigurationModal),
                 true,
                 KeybindAction::ShowSettings,
-                &app_state,
+                app_state,
             ),
             horizontal_space(),
             scale_factor_tooltip,
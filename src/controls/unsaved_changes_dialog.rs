@@ -0,0 +1,76 @@
+//!
+//! Notespace-Editor
+//!
+//! Save / Discard / Cancel dialog shown before unsaved changes would be lost.
+//!
+
+use crate::app_message::AppMessage;
+use crate::document::text_document::{DocumentId, TextDocument};
+use crate::ui_const::UI_CONTROL_SPACING;
+use crate::ui_style::AppStyle;
+use iced::widget::{button, column, container, horizontal_space, row, text};
+use iced::{Element, Length};
+use rust_i18n::t;
+
+///
+/// What the user was doing when the unsaved changes were found.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseIntent {
+    CloseDocument,
+    QuitApplication,
+}
+
+///
+/// A pending prompt for the document with unsaved changes.
+///
+/// `saving` is set while the document is being saved on behalf of the prompt. The
+/// dialog is hidden then, and the `intent` resumes once the save has finished.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct UnsavedChangesPrompt {
+    pub(crate) document_id: DocumentId,
+    pub(crate) intent: CloseIntent,
+    pub(crate) saving: bool,
+}
+
+impl UnsavedChangesPrompt {
+    pub fn new(document_id: DocumentId, intent: CloseIntent) -> Self {
+        Self {
+            document_id,
+            intent,
+            saving: false,
+        }
+    }
+
+    ///
+    /// The contents of the modal dialog for `document`.
+    ///
+    pub fn view<'a>(&self, document: &TextDocument) -> Element<'a, AppMessage> {
+        container(
+            column![
+                text(t!("unsaved_changes_title")).size(20),
+                text(t!("unsaved_changes_message", file = document.title())),
+                row![
+                    horizontal_space(),
+                    button(text(t!("unsaved_changes_save")))
+                        .style(AppStyle::button_style_ok)
+                        .on_press(AppMessage::UnsavedChangesSave),
+                    button(text(t!("unsaved_changes_discard")))
+                        .style(AppStyle::button_style_danger)
+                        .on_press(AppMessage::UnsavedChangesDiscard),
+                    button(text(t!("unsaved_changes_cancel")))
+                        .style(AppStyle::button_style_secondary)
+                        .on_press(AppMessage::UnsavedChangesCancel),
+                ]
+                .spacing(UI_CONTROL_SPACING)
+                .width(Length::Fill),
+            ]
+            .spacing(20),
+        )
+        .width(500)
+        .padding(10)
+        .style(container::rounded_box)
+        .into()
+    }
+}
@@ -0,0 +1,284 @@
+//!
+//! Notespace-Editor
+//!
+//! Document Manager. Owns the list of open documents and tracks the active tab.
+//!
+
+use crate::app_io::LoadedFile;
+use crate::document::line_ending::LineEnding;
+use crate::document::recovery_journal::RecoverySnapshot;
+use crate::document::text_cursor::move_cursor_to;
+use crate::document::text_document::{DocumentId, TextDocument};
+use std::path::Path;
+
+///
+/// The list of open documents. There is always at least one document, so the
+/// editor always has a buffer to show.
+///
+pub struct DocumentManager {
+    documents: Vec<TextDocument>,
+    active_index: usize,
+    next_id: DocumentId,
+    ///
+    /// The line ending of new documents and of files with a single line.
+    ///
+    default_line_ending: LineEnding,
+}
+
+impl Default for DocumentManager {
+    fn default() -> Self {
+        Self {
+            documents: vec![TextDocument::new(0, LineEnding::default())],
+            active_index: 0,
+            next_id: 1,
+            default_line_ending: LineEnding::default(),
+        }
+    }
+}
+
+impl DocumentManager {
+    ///
+    /// All open documents, in tab order.
+    ///
+    pub fn documents(&self) -> &[TextDocument] {
+        &self.documents
+    }
+
+    ///
+    /// The index of the active tab.
+    ///
+    pub fn active_index(&self) -> usize {
+        self.active_index
+    }
+
+    ///
+    /// The document shown in the editor.
+    ///
+    pub fn active(&self) -> &TextDocument {
+        &self.documents[self.active_index]
+    }
+
+    ///
+    /// The document shown in the editor.
+    ///
+    pub fn active_mut(&mut self) -> &mut TextDocument {
+        &mut self.documents[self.active_index]
+    }
+
+    ///
+    /// Get the document with the specified `id`, if it is still open.
+    ///
+    pub fn get(&self, id: DocumentId) -> Option<&TextDocument> {
+        self.documents.iter().find(|document| document.id == id)
+    }
+
+    ///
+    /// Get the document with the specified `id`, if it is still open.
+    ///
+    pub fn get_mut(&mut self, id: DocumentId) -> Option<&mut TextDocument> {
+        self.documents.iter_mut().find(|document| document.id == id)
+    }
+
+    ///
+    /// Get the document that has `file_path` open, if any.
+    ///
+    pub fn find_by_path(&self, file_path: &Path) -> Option<&TextDocument> {
+        self.documents
+            .iter()
+            .find(|document| document.file_path.as_deref() == Some(file_path))
+    }
+
+    ///
+    /// Open a new untitled document in a new tab and make it active.
+    ///
+    pub fn new_document(&mut self) -> DocumentId {
+        let document = TextDocument::new(self.allocate_id(), self.default_line_ending);
+        self.push_document(document)
+    }
+
+    ///
+    /// Open the `loaded_file` and make it active.
+    ///
+    /// If the file is already open its tab is activated instead. A pristine
+    /// untitled document in the active tab is replaced by the opened file.
+    ///
+    pub fn open_document(&mut self, loaded_file: &LoadedFile) -> DocumentId {
+        if let Some(id) = self
+            .find_by_path(&loaded_file.path)
+            .map(|document| document.id)
+        {
+            self.select_document(id);
+            return id;
+        }
+        let document =
+            TextDocument::with_file(self.allocate_id(), loaded_file, self.default_line_ending);
+        self.replace_pristine_or_push(document)
+    }
+
+    ///
+    /// Open an unsaved untitled document with `text` and make it active.
+    ///
+    pub fn open_untitled(&mut self, text: &str) -> DocumentId {
+        let document =
+            TextDocument::untitled_with_text(self.allocate_id(), text, self.default_line_ending);
+        self.replace_pristine_or_push(document)
+    }
+
+    ///
+    /// Open the text of a recovery `snapshot` as a modified document and make it
+    /// active. If its file is already open, the text of that document is replaced.
+    ///
+    pub fn restore_snapshot(&mut self, snapshot: &RecoverySnapshot) -> DocumentId {
+        let open_id = snapshot
+            .file_path
+            .as_deref()
+            .and_then(|file_path| self.find_by_path(file_path))
+            .map(|document| document.id);
+        let id = match (open_id, &snapshot.file_path) {
+            (Some(id), _) => {
+                //
+                // The replacement can be undone to get the text of the file back.
+                //
+                self.select_document(id);
+                let document = self.active_mut();
+                document.replace_text(&snapshot.text);
+                document.encoding = snapshot.encoding();
+                id
+            }
+            (None, Some(file_path)) => {
+                let loaded_file = snapshot.loaded_file(file_path.clone());
+                let mut document = TextDocument::with_file(
+                    self.allocate_id(),
+                    &loaded_file,
+                    self.default_line_ending,
+                );
+                document.history.mark_unsaved();
+                self.replace_pristine_or_push(document)
+            }
+            (None, None) => {
+                let mut document = TextDocument::untitled_with_text(
+                    self.allocate_id(),
+                    &snapshot.text,
+                    self.default_line_ending,
+                );
+                document.encoding = snapshot.encoding();
+                self.replace_pristine_or_push(document)
+            }
+        };
+        let document = self.active_mut();
+        document.line_ending = snapshot.line_ending;
+        document.file_dirty = !document.is_saved();
+        move_cursor_to(&mut document.file_content, snapshot.cursor);
+        id
+    }
+
+    ///
+    /// Set the line ending of new documents. Pristine untitled documents that are
+    /// already open use it too.
+    ///
+    pub fn set_default_line_ending(&mut self, line_ending: LineEnding) {
+        self.default_line_ending = line_ending;
+        for document in &mut self.documents {
+            if document.is_pristine() {
+                document.line_ending = line_ending;
+                document.saved_line_ending = line_ending;
+            }
+        }
+    }
+
+    ///
+    /// Returns `true` if the document with the specified `id` is open.
+    ///
+    pub fn contains(&self, id: DocumentId) -> bool {
+        self.index_of(id).is_some()
+    }
+
+    ///
+    /// Close the document with the specified `id` and return it.
+    ///
+    /// Closing the last document leaves a new untitled document in its place.
+    ///
+    pub fn close_document(&mut self, id: DocumentId) -> Option<TextDocument> {
+        let index = self.index_of(id)?;
+        let document = self.documents.remove(index);
+        if self.documents.is_empty() {
+            let replacement = TextDocument::new(self.allocate_id(), self.default_line_ending);
+            self.documents.push(replacement);
+        }
+        if self.active_index > index || self.active_index >= self.documents.len() {
+            self.active_index = self.active_index.saturating_sub(1);
+        }
+        Some(document)
+    }
+
+    ///
+    /// Update the files of the documents after the file or directory at `from` was
+    /// renamed or moved to `to`.
+    ///
+    pub fn path_renamed(&mut self, from: &Path, to: &Path) {
+        for document in &mut self.documents {
+            if let Some(file_path) = &document.file_path
+                && let Ok(rest) = file_path.strip_prefix(from)
+            {
+                document.file_path = Some(match rest.as_os_str().is_empty() {
+                    true => to.to_path_buf(),
+                    false => to.join(rest),
+                });
+            }
+        }
+    }
+
+    ///
+    /// Make the document with the specified `id` the active tab.
+    ///
+    pub fn select_document(&mut self, id: DocumentId) {
+        if let Some(index) = self.index_of(id) {
+            self.active_index = index;
+        }
+    }
+
+    ///
+    /// Activate the next tab, wrapping around to the first one.
+    ///
+    pub fn select_next(&mut self) {
+        self.active_index = (self.active_index + 1) % self.documents.len();
+    }
+
+    ///
+    /// Activate the previous tab, wrapping around to the last one.
+    ///
+    pub fn select_previous(&mut self) {
+        self.active_index = (self.active_index + self.documents.len() - 1) % self.documents.len();
+    }
+
+    fn index_of(&self, id: DocumentId) -> Option<usize> {
+        self.documents.iter().position(|document| document.id == id)
+    }
+
+    ///
+    /// Replace a pristine untitled document in the active tab with `document`, or
+    /// add it as a new tab.
+    ///
+    fn replace_pristine_or_push(&mut self, document: TextDocument) -> DocumentId {
+        let id = document.id;
+        if self.active().is_pristine() {
+            self.documents[self.active_index] = document;
+            id
+        } else {
+            self.push_document(document)
+        }
+    }
+
+    fn push_document(&mut self, document: TextDocument) -> DocumentId {
+        let id = document.id;
+        self.documents.push(document);
+        self.active_index = self.documents.len() - 1;
+        id
+    }
+
+    fn allocate_id(&mut self) -> DocumentId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
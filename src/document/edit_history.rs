@@ -0,0 +1,672 @@
+//!
+//! Notespace-Editor
+//!
+//! Undo/redo history of a document.
+//!
+//! `text_editor::Content` has no undo stack, so every edit is recorded as the range
+//! of lines it replaced. Undoing an edit replaces the new lines with the old ones
+//! again. The lines an edit can change are known from the cursor and selection
+//! before it, so only those lines are copied and compared after the edit.
+//!
+
+use crate::document::text_cursor::{TextPosition, move_cursor_to, select_range};
+use iced::widget::text_editor::{Action, Content, Edit};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+///
+/// The maximum number of undo steps kept per document.
+///
+const MAX_UNDO_STEPS: usize = 1000;
+
+///
+/// The maximum number of bytes of text kept in the undo steps of a document.
+///
+const MAX_UNDO_BYTES: usize = 16 * 1024 * 1024;
+
+///
+/// Identifies a state of the document text. Each undo step has its own id, so the
+/// saved state can be recognized again after undo or redo.
+///
+pub type EditStateId = u64;
+
+///
+/// The cursor, and the other end of the selection if there is one.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CursorState {
+    anchor: Option<TextPosition>,
+    cursor: TextPosition,
+}
+
+impl CursorState {
+    ///
+    /// The cursor state of `content`.
+    ///
+    fn of(content: &Content) -> Self {
+        let cursor = content.cursor_position();
+        let anchor = content
+            .selection()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| selection_anchor(content, cursor, &selection));
+        Self { anchor, cursor }
+    }
+
+    ///
+    /// The first and last line of the selection, or the cursor line.
+    ///
+    fn lines(&self) -> (usize, usize) {
+        let anchor_line = self.anchor.map_or(self.cursor.0, |anchor| anchor.0);
+        (
+            anchor_line.min(self.cursor.0),
+            anchor_line.max(self.cursor.0),
+        )
+    }
+
+    fn restore(&self, content: &mut Content) {
+        match self.anchor {
+            Some(anchor) => select_range(content, anchor, self.cursor),
+            None => move_cursor_to(content, self.cursor),
+        }
+    }
+}
+
+///
+/// Replace the `removed` lines, starting at `line`, with the `inserted` lines.
+/// Both lists contain at least one line.
+///
+#[derive(Debug, Clone)]
+struct LineEdit {
+    line: usize,
+    removed: Vec<String>,
+    inserted: Vec<String>,
+}
+
+impl LineEdit {
+    ///
+    /// The edit that replaced the `removed` lines, starting at `line`, with the
+    /// `inserted` lines, without the lines at the start and end that are the same.
+    /// Returns `None` if nothing changed.
+    ///
+    fn trimmed(line: usize, mut removed: Vec<String>, mut inserted: Vec<String>) -> Option<Self> {
+        //
+        // Keep at least one line on both sides, so the edit always has a line range
+        // to select when it is applied.
+        //
+        let max_common = removed.len().min(inserted.len()).saturating_sub(1);
+        let mut prefix = 0;
+        while prefix < max_common && removed[prefix] == inserted[prefix] {
+            prefix += 1;
+        }
+        let mut suffix = 0;
+        while prefix + suffix < max_common
+            && removed[removed.len() - 1 - suffix] == inserted[inserted.len() - 1 - suffix]
+        {
+            suffix += 1;
+        }
+        removed.truncate(removed.len() - suffix);
+        inserted.truncate(inserted.len() - suffix);
+        removed.drain(..prefix);
+        inserted.drain(..prefix);
+        (removed != inserted).then_some(Self {
+            line: line + prefix,
+            removed,
+            inserted,
+        })
+    }
+
+    fn inverse(&self) -> LineEdit {
+        LineEdit {
+            line: self.line,
+            removed: self.inserted.clone(),
+            inserted: self.removed.clone(),
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.removed
+            .iter()
+            .chain(&self.inserted)
+            .map(String::len)
+            .sum()
+    }
+
+    ///
+    /// Apply the edit to `content`.
+    ///
+    fn apply(&self, content: &mut Content) {
+        let last_line = self.line + self.removed.len() - 1;
+        let last_column = self.removed.last().map_or(0, String::len);
+        select_range(content, (self.line, 0), (last_line, last_column));
+        let text = self.inserted.join("\n");
+        if text.is_empty() {
+            if content.selection().is_some() {
+                content.perform(Action::Edit(Edit::Backspace));
+            }
+        } else {
+            content.perform(Action::Edit(Edit::Paste(Arc::new(text))));
+        }
+    }
+
+    ///
+    /// Combine `next`, an edit of the text produced by this edit, into this edit.
+    /// Returns `false` if the line ranges of the two edits do not touch.
+    ///
+    fn merge(&mut self, next: &LineEdit) -> bool {
+        let inserted_end = self.line + self.inserted.len();
+        let next_end = next.line + next.removed.len();
+        if next.line > inserted_end || next_end < self.line {
+            return false;
+        }
+        //
+        // The lines between `start` and `end` after this edit are known from the
+        // lines inserted by this edit and the lines removed by `next`.
+        //
+        let start = self.line.min(next.line);
+        let end = inserted_end.max(next_end);
+        let lines: Vec<String> = (start..end)
+            .map(|index| {
+                if (self.line..inserted_end).contains(&index) {
+                    self.inserted[index - self.line].clone()
+                } else {
+                    next.removed[index - next.line].clone()
+                }
+            })
+            .collect();
+        let mut removed = lines[..self.line - start].to_vec();
+        removed.extend(self.removed.iter().cloned());
+        removed.extend_from_slice(&lines[inserted_end - start..]);
+        let mut inserted = lines[..next.line - start].to_vec();
+        inserted.extend(next.inserted.iter().cloned());
+        inserted.extend_from_slice(&lines[next_end - start..]);
+        self.line = start;
+        self.removed = removed;
+        self.inserted = inserted;
+        true
+    }
+}
+
+///
+/// The kind of edit, used to group consecutive edits into one undo step.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditKind {
+    Typing,
+    Backspace,
+    Delete,
+    Other,
+}
+
+impl EditKind {
+    fn of(edit: &Edit) -> Self {
+        match edit {
+            Edit::Insert(_) => EditKind::Typing,
+            Edit::Backspace => EditKind::Backspace,
+            Edit::Delete => EditKind::Delete,
+            Edit::Paste(_) | Edit::Enter => EditKind::Other,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EditStep {
+    id: EditStateId,
+    kind: EditKind,
+    edit: LineEdit,
+    before: CursorState,
+    after: CursorState,
+}
+
+#[derive(Debug)]
+pub struct EditHistory {
+    undo_stack: VecDeque<EditStep>,
+    redo_stack: Vec<EditStep>,
+    ///
+    /// The state id when the undo stack is empty.
+    ///
+    base_id: EditStateId,
+    saved_id: Option<EditStateId>,
+    next_id: EditStateId,
+    size: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditHistory {
+    ///
+    /// Create an empty history. The initial text is the saved state.
+    ///
+    pub fn new() -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            base_id: 0,
+            saved_id: Some(0),
+            next_id: 1,
+            size: 0,
+        }
+    }
+
+    ///
+    /// Perform the `edit` on `content` and record it.
+    ///
+    /// Consecutive edits of the same kind (typing, backspace or delete) are grouped
+    /// into one step, as long as the cursor was not moved in between and the text
+    /// was not saved in between.
+    ///
+    pub fn perform(&mut self, content: &mut Content, edit: Edit) {
+        let kind = EditKind::of(&edit);
+        let before = CursorState::of(content);
+        //
+        // An edit replaces the selection, or changes the cursor line. Backspace
+        // and delete can join it with the line before or after.
+        //
+        let old_count = content.line_count();
+        let (first, last) = before.lines();
+        let first = first.saturating_sub(1);
+        let last = (last + 1).min(old_count.saturating_sub(1));
+        let removed = copy_lines(content, first, last + 1);
+        content.perform(Action::Edit(edit));
+        let new_end = (last + 1 + content.line_count()).saturating_sub(old_count);
+        let inserted = copy_lines(content, first, new_end.max(first + 1));
+        let Some(line_edit) = LineEdit::trimmed(first, removed, inserted) else {
+            return;
+        };
+        let after = CursorState::of(content);
+        self.redo_stack.clear();
+
+        let saved_id = self.saved_id;
+        if let Some(step) = self.undo_stack.back_mut() {
+            let size = step.edit.size();
+            if kind != EditKind::Other
+                && step.kind == kind
+                && step.after == before
+                && Some(step.id) != saved_id
+                && step.edit.merge(&line_edit)
+            {
+                //
+                // The merged step is a new state of the text, a save of the step
+                // before the merge must not mark this text as saved.
+                //
+                step.id = self.next_id;
+                self.next_id += 1;
+                step.after = after;
+                self.size = self.size - size + step.edit.size();
+                self.enforce_limits();
+                return;
+            }
+        }
+
+        self.size += line_edit.size();
+        let id = self.allocate_id();
+        self.undo_stack.push_back(EditStep {
+            id,
+            kind,
+            edit: line_edit,
+            before,
+            after,
+        });
+        self.enforce_limits();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    ///
+    /// Revert the last step, restoring the cursor and selection from before it.
+    /// Returns `false` if there is nothing to undo.
+    ///
+    pub fn undo(&mut self, content: &mut Content) -> bool {
+        let Some(step) = self.undo_stack.pop_back() else {
+            return false;
+        };
+        self.size -= step.edit.size();
+        step.edit.inverse().apply(content);
+        step.before.restore(content);
+        self.redo_stack.push(step);
+        true
+    }
+
+    ///
+    /// Apply the last undone step again, restoring the cursor and selection from
+    /// after it. Returns `false` if there is nothing to redo.
+    ///
+    pub fn redo(&mut self, content: &mut Content) -> bool {
+        let Some(step) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.size += step.edit.size();
+        step.edit.apply(content);
+        step.after.restore(content);
+        self.undo_stack.push_back(step);
+        self.enforce_limits();
+        true
+    }
+
+    ///
+    /// The id of the current state of the text.
+    ///
+    pub fn state_id(&self) -> EditStateId {
+        self.undo_stack.back().map_or(self.base_id, |step| step.id)
+    }
+
+    ///
+    /// Remember `state_id` as the state that was last written to the file.
+    ///
+    pub fn mark_saved(&mut self, state_id: EditStateId) {
+        self.saved_id = Some(state_id);
+    }
+
+    ///
+    /// Forget the saved state, for text that has never been written to a file.
+    ///
+    pub fn mark_unsaved(&mut self) {
+        self.saved_id = None;
+    }
+
+    ///
+    /// Returns `true` if the text is the same as when it was last saved.
+    ///
+    pub fn is_saved(&self) -> bool {
+        self.saved_id == Some(self.state_id())
+    }
+
+    ///
+    /// The other end of the selection in `content`, or `None` if no text is
+    /// selected.
+    ///
+    pub fn selection_anchor(&self, content: &Content) -> Option<TextPosition> {
+        CursorState::of(content).anchor
+    }
+
+    ///
+    /// Drop the oldest steps when the history is too large. The last step is always
+    /// kept.
+    ///
+    fn enforce_limits(&mut self) {
+        while self.undo_stack.len() > 1
+            && (self.undo_stack.len() > MAX_UNDO_STEPS || self.size > MAX_UNDO_BYTES)
+        {
+            if let Some(step) = self.undo_stack.pop_front() {
+                self.size -= step.edit.size();
+                self.base_id = step.id;
+            }
+        }
+    }
+
+    fn allocate_id(&mut self) -> EditStateId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+///
+/// Copy the lines `start..end` of `content`.
+///
+fn copy_lines(content: &Content, start: usize, end: usize) -> Vec<String> {
+    (start..end)
+        .map(|index| {
+            content
+                .line(index)
+                .map(|line| line.to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+///
+/// Find the other end of the `selection` that ends or starts at `cursor` in
+/// `content`.
+///
+fn selection_anchor(content: &Content, cursor: TextPosition, selection: &str) -> TextPosition {
+    let parts: Vec<&str> = selection.split('\n').collect();
+    let (line, column) = cursor;
+    let last = parts.len() - 1;
+
+    //
+    // The selection ends at the cursor if the text before the cursor matches it.
+    //
+    if let Some(start_line) = line.checked_sub(last) {
+        let ends_at_cursor = parts.iter().enumerate().all(|(index, part)| {
+            let Some(text) = content.line(start_line + index) else {
+                return false;
+            };
+            let text = if index == last {
+                text.get(..column).unwrap_or_default()
+            } else {
+                &text
+            };
+            if index == 0 {
+                text.ends_with(part)
+            } else {
+                text == *part
+            }
+        });
+        if ends_at_cursor {
+            return match last {
+                0 => (line, column - parts[0].len()),
+                _ => (
+                    start_line,
+                    content.line(start_line).map_or(0, |text| text.len()) - parts[0].len(),
+                ),
+            };
+        }
+    }
+
+    //
+    // Otherwise the selection starts at the cursor.
+    //
+    match last {
+        0 => (line, column + parts[0].len()),
+        _ => (line + last, parts[last].len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &Content) -> Vec<String> {
+        content.lines().map(|line| line.to_string()).collect()
+    }
+
+    fn type_text(history: &mut EditHistory, content: &mut Content, text: &str) {
+        for c in text.chars() {
+            let edit = match c {
+                '\n' => Edit::Enter,
+                c => Edit::Insert(c),
+            };
+            history.perform(content, edit);
+        }
+    }
+
+    #[test]
+    fn typing_is_one_undo_step() {
+        let mut content = Content::with_text("one\ntwo");
+        let mut history = EditHistory::new();
+        move_cursor_to(&mut content, (1, 3));
+        type_text(&mut history, &mut content, "xyz");
+        assert_eq!(lines(&content), ["one", "twoxyz"]);
+        assert!(history.undo(&mut content));
+        assert_eq!(lines(&content), ["one", "two"]);
+        assert_eq!(content.cursor_position(), (1, 3));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn moving_the_cursor_starts_a_new_step() {
+        let mut content = Content::with_text("ab");
+        let mut history = EditHistory::new();
+        type_text(&mut history, &mut content, "x");
+        move_cursor_to(&mut content, (0, 3));
+        type_text(&mut history, &mut content, "y");
+        assert_eq!(lines(&content), ["xaby"]);
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["xab"]);
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["ab"]);
+    }
+
+    #[test]
+    fn redo_applies_the_step_again() {
+        let mut content = Content::with_text("a\nb\nc");
+        let mut history = EditHistory::new();
+        move_cursor_to(&mut content, (1, 1));
+        type_text(&mut history, &mut content, "\nnew");
+        assert_eq!(lines(&content), ["a", "b", "new", "c"]);
+        //
+        // Enter is a step of its own.
+        //
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["a", "b", "", "c"]);
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["a", "b", "c"]);
+        assert!(history.redo(&mut content));
+        assert!(history.redo(&mut content));
+        assert_eq!(lines(&content), ["a", "b", "new", "c"]);
+        assert_eq!(content.cursor_position(), (2, 3));
+        assert!(!history.redo(&mut content));
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let mut content = Content::with_text("");
+        let mut history = EditHistory::new();
+        type_text(&mut history, &mut content, "a");
+        history.undo(&mut content);
+        type_text(&mut history, &mut content, "b");
+        assert!(!history.can_redo());
+        assert_eq!(lines(&content), ["b"]);
+    }
+
+    #[test]
+    fn backspace_joins_lines_and_undo_splits_them() {
+        let mut content = Content::with_text("one\ntwo\nthree");
+        let mut history = EditHistory::new();
+        move_cursor_to(&mut content, (1, 0));
+        history.perform(&mut content, Edit::Backspace);
+        assert_eq!(lines(&content), ["onetwo", "three"]);
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn delete_joins_lines_and_undo_splits_them() {
+        let mut content = Content::with_text("one\ntwo\nthree");
+        let mut history = EditHistory::new();
+        move_cursor_to(&mut content, (1, 3));
+        history.perform(&mut content, Edit::Delete);
+        assert_eq!(lines(&content), ["one", "twothree"]);
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn paste_over_a_selection_restores_the_selection_on_undo() {
+        let mut content = Content::with_text("one\ntwo\nthree");
+        let mut history = EditHistory::new();
+        select_range(&mut content, (0, 1), (2, 2));
+        history.perform(&mut content, Edit::Paste(Arc::new("X\nY".to_string())));
+        assert_eq!(lines(&content), ["oX", "Yree"]);
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["one", "two", "three"]);
+        assert_eq!(history.selection_anchor(&content), Some((0, 1)));
+        assert_eq!(content.cursor_position(), (2, 2));
+    }
+
+    #[test]
+    fn saved_state_is_recognized_after_undo_and_redo() {
+        let mut content = Content::with_text("text");
+        let mut history = EditHistory::new();
+        assert!(history.is_saved());
+        type_text(&mut history, &mut content, "a");
+        assert!(!history.is_saved());
+        history.mark_saved(history.state_id());
+        assert!(history.is_saved());
+        history.undo(&mut content);
+        assert!(!history.is_saved());
+        history.redo(&mut content);
+        assert!(history.is_saved());
+    }
+
+    #[test]
+    fn typing_after_a_save_started_keeps_the_text_dirty() {
+        let mut content = Content::with_text("");
+        let mut history = EditHistory::new();
+        type_text(&mut history, &mut content, "ab");
+        let saving_id = history.state_id();
+        type_text(&mut history, &mut content, "cd");
+        history.mark_saved(saving_id);
+        assert!(!history.is_saved());
+        history.undo(&mut content);
+        assert_eq!(lines(&content), [""]);
+        assert!(!history.is_saved());
+    }
+
+    #[test]
+    fn edits_after_a_save_are_not_merged_into_the_saved_step() {
+        let mut content = Content::with_text("");
+        let mut history = EditHistory::new();
+        type_text(&mut history, &mut content, "ab");
+        history.mark_saved(history.state_id());
+        type_text(&mut history, &mut content, "cd");
+        history.undo(&mut content);
+        assert_eq!(lines(&content), ["ab"]);
+        assert!(history.is_saved());
+    }
+
+    #[test]
+    fn unsaved_history_is_never_saved() {
+        let mut history = EditHistory::new();
+        history.mark_unsaved();
+        assert!(!history.is_saved());
+    }
+
+    #[test]
+    fn trimmed_drops_unchanged_lines() {
+        let edit = LineEdit::trimmed(
+            3,
+            vec!["a".into(), "b".into(), "c".into()],
+            vec!["a".into(), "x".into(), "c".into()],
+        )
+        .unwrap();
+        assert_eq!(edit.line, 4);
+        assert_eq!(edit.removed, ["b"]);
+        assert_eq!(edit.inserted, ["x"]);
+        assert!(LineEdit::trimmed(0, vec!["a".into()], vec!["a".into()]).is_none());
+    }
+
+    #[test]
+    fn merge_combines_touching_edits() {
+        let mut first = LineEdit {
+            line: 1,
+            removed: vec!["b".into()],
+            inserted: vec!["b1".into(), "b2".into()],
+        };
+        let next = LineEdit {
+            line: 2,
+            removed: vec!["b2".into(), "c".into()],
+            inserted: vec!["b2c".into()],
+        };
+        assert!(first.merge(&next));
+        assert_eq!(first.line, 1);
+        assert_eq!(first.removed, ["b", "c"]);
+        assert_eq!(first.inserted, ["b1", "b2c"]);
+        let far = LineEdit {
+            line: 9,
+            removed: vec!["z".into()],
+            inserted: vec!["y".into()],
+        };
+        assert!(!first.merge(&far));
+    }
+}
@@ -0,0 +1,175 @@
+//!
+//! Notespace-Editor
+//!
+//! Line based diff of two texts, for showing them side by side.
+//!
+//! Lines at the start and end that are the same in both texts are skipped, and the
+//! rest is compared with a longest common subsequence table. When that table would
+//! be too large, the remaining lines are shown as changed.
+//!
+
+///
+/// The maximum number of cells in the longest common subsequence table.
+///
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+///
+/// A row of a side-by-side diff: the index of the line in the old and in the new
+/// text. A changed row with only one side is a removed or an added line.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffRow {
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+    pub changed: bool,
+}
+
+///
+/// Compare the `old` and `new` lines. Removed and added lines between two equal
+/// lines are paired up in the same rows.
+///
+pub fn diff_lines<S: AsRef<str>>(old: &[S], new: &[S]) -> Vec<DiffRow> {
+    let is_same =
+        |old_index: usize, new_index: usize| old[old_index].as_ref() == new[new_index].as_ref();
+    let mut prefix = 0;
+    while prefix < old.len() && prefix < new.len() && is_same(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while prefix + suffix < old.len()
+        && prefix + suffix < new.len()
+        && is_same(old.len() - 1 - suffix, new.len() - 1 - suffix)
+    {
+        suffix += 1;
+    }
+
+    let mut rows: Vec<DiffRow> = (0..prefix)
+        .map(|index| DiffRow {
+            old: Some(index),
+            new: Some(index),
+            changed: false,
+        })
+        .collect();
+
+    //
+    // The changed middle part: `common[i][j]` is the length of the longest common
+    // subsequence of the old lines from `i` and the new lines from `j`.
+    //
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    let old_count = old_end - prefix;
+    let new_count = new_end - prefix;
+    let (mut old_index, mut new_index) = (prefix, prefix);
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    if old_count.saturating_mul(new_count) <= MAX_DIFF_CELLS {
+        let width = new_count + 1;
+        let mut common = vec![0u32; (old_count + 1) * width];
+        for i in (0..old_count).rev() {
+            for j in (0..new_count).rev() {
+                common[i * width + j] = if is_same(prefix + i, prefix + j) {
+                    common[(i + 1) * width + j + 1] + 1
+                } else {
+                    common[(i + 1) * width + j].max(common[i * width + j + 1])
+                };
+            }
+        }
+        while old_index < old_end && new_index < new_end {
+            let (i, j) = (old_index - prefix, new_index - prefix);
+            if is_same(old_index, new_index) {
+                pair_changes(&mut rows, &mut removed, &mut added);
+                rows.push(DiffRow {
+                    old: Some(old_index),
+                    new: Some(new_index),
+                    changed: false,
+                });
+                old_index += 1;
+                new_index += 1;
+            } else if common[(i + 1) * width + j] >= common[i * width + j + 1] {
+                removed.push(old_index);
+                old_index += 1;
+            } else {
+                added.push(new_index);
+                new_index += 1;
+            }
+        }
+    }
+    removed.extend(old_index..old_end);
+    added.extend(new_index..new_end);
+    pair_changes(&mut rows, &mut removed, &mut added);
+
+    rows.extend((0..suffix).map(|index| DiffRow {
+        old: Some(old_end + index),
+        new: Some(new_end + index),
+        changed: false,
+    }));
+    rows
+}
+
+///
+/// Add rows for the `removed` and `added` lines, side by side, and clear them.
+///
+fn pair_changes(rows: &mut Vec<DiffRow>, removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+    for index in 0..removed.len().max(added.len()) {
+        rows.push(DiffRow {
+            old: removed.get(index).copied(),
+            new: added.get(index).copied(),
+            changed: true,
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(old: Option<usize>, new: Option<usize>, changed: bool) -> DiffRow {
+        DiffRow { old, new, changed }
+    }
+
+    #[test]
+    fn equal_texts_have_no_changes() {
+        let rows = diff_lines(&["a", "b"], &["a", "b"]);
+        assert_eq!(
+            rows,
+            [row(Some(0), Some(0), false), row(Some(1), Some(1), false)]
+        );
+    }
+
+    #[test]
+    fn changed_line_is_paired() {
+        let rows = diff_lines(&["a", "b", "c"], &["a", "x", "c"]);
+        assert_eq!(
+            rows,
+            [
+                row(Some(0), Some(0), false),
+                row(Some(1), Some(1), true),
+                row(Some(2), Some(2), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn added_and_removed_lines() {
+        let rows = diff_lines(&["a", "b", "c", "d"], &["b", "c", "x", "d"]);
+        assert_eq!(
+            rows,
+            [
+                row(Some(0), None, true),
+                row(Some(1), Some(0), false),
+                row(Some(2), Some(1), false),
+                row(None, Some(2), true),
+                row(Some(3), Some(3), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_texts() {
+        assert!(diff_lines::<&str>(&[], &[]).is_empty());
+        assert_eq!(diff_lines(&[], &["a"]), [row(None, Some(0), true)]);
+        assert_eq!(diff_lines(&["a"], &[]), [row(Some(0), None, true)]);
+    }
+}
@@ -0,0 +1,134 @@
+//!
+//! Notespace-Editor
+//!
+//! Line endings of files.
+//!
+//! The text of a document always uses `\n` internally. The line ending found in
+//! the file is remembered in the document and written again when it is saved.
+//!
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineEnding {
+    #[default]
+    #[serde(rename = "LF")]
+    Lf,
+    #[serde(rename = "CRLF")]
+    CrLf,
+    #[serde(rename = "CR")]
+    Cr,
+}
+
+impl LineEnding {
+    pub const ALL: [LineEnding; 3] = [LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr];
+
+    ///
+    /// The name shown in the statusbar and the command palette.
+    ///
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    ///
+    /// The line ending that clicking the statusbar converts to: LF and CRLF are
+    /// swapped, classic Mac CR becomes LF.
+    ///
+    pub fn toggled(&self) -> LineEnding {
+        match self {
+            LineEnding::Lf => LineEnding::CrLf,
+            LineEnding::CrLf | LineEnding::Cr => LineEnding::Lf,
+        }
+    }
+
+    ///
+    /// Find the most common line ending in `text` and convert all line endings to
+    /// `\n`. The line ending is `None` if the text is a single line.
+    ///
+    pub fn normalize(text: &str) -> (Cow<'_, str>, Option<LineEnding>) {
+        let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+        let mut bytes = text.bytes().peekable();
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'\n' => lf += 1,
+                b'\r' if bytes.peek() == Some(&b'\n') => {
+                    bytes.next();
+                    crlf += 1;
+                }
+                b'\r' => cr += 1,
+                _ => {}
+            }
+        }
+        let line_ending = if lf + crlf + cr == 0 {
+            None
+        } else if crlf >= lf && crlf >= cr {
+            Some(LineEnding::CrLf)
+        } else if cr > lf {
+            Some(LineEnding::Cr)
+        } else {
+            Some(LineEnding::Lf)
+        };
+        let text = match crlf + cr {
+            0 => Cow::Borrowed(text),
+            _ => Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n")),
+        };
+        (text, line_ending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_line_has_no_line_ending() {
+        let (text, line_ending) = LineEnding::normalize("one line");
+        assert!(matches!(text, Cow::Borrowed("one line")));
+        assert_eq!(line_ending, None);
+    }
+
+    #[test]
+    fn line_endings_are_converted_to_lf() {
+        assert_eq!(
+            LineEnding::normalize("a\r\nb\r\nc"),
+            (Cow::Borrowed("a\nb\nc"), Some(LineEnding::CrLf))
+        );
+        assert_eq!(
+            LineEnding::normalize("a\rb\rc"),
+            (Cow::Borrowed("a\nb\nc"), Some(LineEnding::Cr))
+        );
+        assert_eq!(
+            LineEnding::normalize("a\nb\n"),
+            (Cow::Borrowed("a\nb\n"), Some(LineEnding::Lf))
+        );
+    }
+
+    #[test]
+    fn most_common_line_ending_wins() {
+        let (text, line_ending) = LineEnding::normalize("a\r\nb\nc\nd");
+        assert_eq!(text, "a\nb\nc\nd");
+        assert_eq!(line_ending, Some(LineEnding::Lf));
+        let (_, line_ending) = LineEnding::normalize("a\r\nb\r\nc\n");
+        assert_eq!(line_ending, Some(LineEnding::CrLf));
+    }
+
+    #[test]
+    fn toggled_swaps_lf_and_crlf() {
+        assert_eq!(LineEnding::Lf.toggled(), LineEnding::CrLf);
+        assert_eq!(LineEnding::CrLf.toggled(), LineEnding::Lf);
+        assert_eq!(LineEnding::Cr.toggled(), LineEnding::Lf);
+    }
+}
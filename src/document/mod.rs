@@ -0,0 +1,11 @@
+//!
+//! Document modules.
+//!
+pub(crate) mod document_manager;
+pub(crate) mod edit_history;
+pub(crate) mod line_diff;
+pub(crate) mod line_ending;
+pub(crate) mod recovery_journal;
+pub(crate) mod text_cursor;
+pub(crate) mod text_document;
+pub(crate) mod text_encoding;
@@ -0,0 +1,385 @@
+//!
+//! Notespace-Editor
+//!
+//! Crash recovery journal for documents with unsaved changes.
+//!
+//! Each running editor writes a snapshot of every modified document to its own
+//! session directory in `AppConfiguration::data_path() + RECOVERY_DIRECTORY`, and
+//! removes the snapshot again once the document is saved or closed. The session
+//! directory is removed when the editor exits normally, so any snapshots found on
+//! the next start were left behind by an editor that did not.
+//!
+
+use crate::app_configuration::AppConfiguration;
+use crate::app_const::RECOVERY_SESSION_TIMEOUT;
+use crate::app_io::{AppIOError, LoadedFile, write_file_atomic};
+use crate::document::edit_history::EditStateId;
+use crate::document::line_ending::LineEnding;
+use crate::document::text_document::{DocumentId, TextDocument};
+use crate::document::text_encoding::TextEncoding;
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RECOVERY_DIRECTORY: &str = "recovery";
+
+///
+/// Written on every journal update while a session has snapshots, so other running
+/// editors can tell the session is still alive.
+///
+const HEARTBEAT_FILE: &str = "heartbeat";
+
+const SNAPSHOT_EXTENSION: &str = "toml";
+
+///
+/// A copy of a modified document, as written to the recovery journal.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverySnapshot {
+    pub file_path: Option<PathBuf>,
+    ///
+    /// The modification time of the file when it was loaded or last saved, in
+    /// nanoseconds since the Unix epoch.
+    ///
+    pub file_modified: Option<u64>,
+    ///
+    /// When the snapshot was written, in milliseconds since the Unix epoch.
+    ///
+    pub saved_at: u64,
+    pub cursor: (usize, usize),
+    pub encoding: String,
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    ///
+    /// The text as it would be written to the file.
+    ///
+    pub text: String,
+}
+
+impl RecoverySnapshot {
+    pub fn of(document: &TextDocument) -> Self {
+        Self {
+            file_path: document.file_path.clone(),
+            file_modified: document.file_modified.map(nanos_since_epoch),
+            saved_at: nanos_since_epoch(SystemTime::now()) / 1_000_000,
+            cursor: document.file_content.cursor_position(),
+            encoding: document.encoding.encoding.name().to_string(),
+            bom: document.encoding.bom,
+            line_ending: document.line_ending,
+            text: document.text(),
+        }
+    }
+
+    pub fn encoding(&self) -> TextEncoding {
+        match Encoding::for_label(self.encoding.as_bytes()) {
+            Some(encoding) => TextEncoding::new(encoding, self.bom),
+            None => TextEncoding::default(),
+        }
+    }
+
+    ///
+    /// The snapshot as a file loaded from `path`, to open it in a document.
+    ///
+    pub fn loaded_file(&self, path: PathBuf) -> LoadedFile {
+        LoadedFile {
+            path,
+            text: Arc::new(self.text.clone()),
+            encoding: self.encoding(),
+            modified: self
+                .file_modified
+                .map(|nanos| UNIX_EPOCH + Duration::from_nanos(nanos)),
+        }
+    }
+
+    ///
+    /// The time the snapshot was written, e.g. `2025-01-31 12:00 UTC`.
+    ///
+    pub fn saved_at_text(&self) -> String {
+        match time::OffsetDateTime::from_unix_timestamp((self.saved_at / 1000) as i64) {
+            Ok(saved_at) => format!(
+                "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+                saved_at.year(),
+                saved_at.month() as u8,
+                saved_at.day(),
+                saved_at.hour(),
+                saved_at.minute()
+            ),
+            Err(_) => String::new(),
+        }
+    }
+}
+
+///
+/// A snapshot found in the journal of an editor that did not exit normally.
+///
+#[derive(Debug, Clone)]
+pub struct RecoveredSnapshot {
+    ///
+    /// The snapshot file in the journal.
+    ///
+    pub path: PathBuf,
+    pub snapshot: RecoverySnapshot,
+    ///
+    /// The file was modified or deleted after the snapshot's document loaded it.
+    ///
+    pub file_changed: bool,
+}
+
+impl RecoveredSnapshot {
+    ///
+    /// Remove the snapshot file, and the session directory once it has no more
+    /// snapshots.
+    ///
+    pub fn remove(&self) {
+        let _ = std::fs::remove_file(&self.path);
+        if let Some(session_path) = self.path.parent()
+            && !has_snapshots(session_path)
+        {
+            let _ = std::fs::remove_dir_all(session_path);
+        }
+    }
+}
+
+///
+/// The journal of the running editor.
+///
+pub struct RecoveryJournal {
+    session_path: PathBuf,
+    ///
+    /// The state of each document as it was last written to the journal.
+    ///
+    written: HashMap<DocumentId, (EditStateId, LineEnding, TextEncoding)>,
+}
+
+impl Default for RecoveryJournal {
+    fn default() -> Self {
+        let started_at = nanos_since_epoch(SystemTime::now()) / 1_000_000;
+        Self {
+            session_path: Self::path().join(format!("{}-{}", std::process::id(), started_at)),
+            written: HashMap::new(),
+        }
+    }
+}
+
+impl RecoveryJournal {
+    ///
+    /// Returns the directory of the recovery journals:
+    ///
+    /// `AppConfiguration::data_path() + RECOVERY_DIRECTORY`
+    ///
+    pub fn path() -> PathBuf {
+        AppConfiguration::data_path().join(RECOVERY_DIRECTORY)
+    }
+
+    pub fn session_path(&self) -> &Path {
+        &self.session_path
+    }
+
+    ///
+    /// Returns `true` if snapshots of this session are in the journal.
+    ///
+    pub fn has_snapshots(&self) -> bool {
+        !self.written.is_empty()
+    }
+
+    ///
+    /// Find the changes to the journal for the open `documents`: modified documents
+    /// are written again if they changed since the last update, and the snapshots
+    /// of saved or closed documents are removed.
+    ///
+    pub fn changes(&mut self, documents: &[TextDocument]) -> JournalChanges {
+        let mut changes = JournalChanges {
+            session_path: self.session_path.clone(),
+            ..JournalChanges::default()
+        };
+        let mut written = HashMap::new();
+        for document in documents.iter().filter(|document| document.file_dirty) {
+            let state = (
+                document.history.state_id(),
+                document.line_ending,
+                document.encoding,
+            );
+            if self.written.get(&document.id) != Some(&state) {
+                match toml::to_string(&RecoverySnapshot::of(document)) {
+                    Ok(snapshot) => changes
+                        .writes
+                        .push((self.snapshot_path(document.id), snapshot)),
+                    Err(_) => continue,
+                }
+            }
+            written.insert(document.id, state);
+        }
+        for document_id in self.written.keys() {
+            if !written.contains_key(document_id) {
+                changes.removals.push(self.snapshot_path(*document_id));
+            }
+        }
+        changes.heartbeat = !written.is_empty();
+        changes.remove_session = written.is_empty() && !self.written.is_empty();
+        self.written = written;
+        changes
+    }
+
+    ///
+    /// Write every modified document again on the next update, after writing the
+    /// journal failed.
+    ///
+    pub fn forget_written(&mut self) {
+        self.written.clear();
+    }
+
+    ///
+    /// Remove the journal of this session, when the editor exits normally.
+    ///
+    pub fn remove_session(&mut self) {
+        self.written.clear();
+        let _ = std::fs::remove_dir_all(&self.session_path);
+    }
+
+    fn snapshot_path(&self, document_id: DocumentId) -> PathBuf {
+        self.session_path
+            .join(format!("{}.{}", document_id, SNAPSHOT_EXTENSION))
+    }
+
+    ///
+    /// Find the snapshots left behind by editors that did not exit normally.
+    ///
+    /// Sessions other than `own_session_path` with a recent heartbeat belong to
+    /// editors that are still running and are skipped. Dead sessions without any
+    /// snapshots are removed.
+    ///
+    pub async fn find_snapshots(own_session_path: PathBuf) -> Vec<RecoveredSnapshot> {
+        let mut recovered = Vec::new();
+        let Ok(mut sessions) = tokio::fs::read_dir(Self::path()).await else {
+            return recovered;
+        };
+        while let Ok(Some(session)) = sessions.next_entry().await {
+            let session_path = session.path();
+            if session_path == own_session_path || is_session_alive(&session_path).await {
+                continue;
+            }
+            let Ok(mut entries) = tokio::fs::read_dir(&session_path).await else {
+                continue;
+            };
+            let mut found = false;
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if path
+                    .extension()
+                    .is_none_or(|extension| extension != SNAPSHOT_EXTENSION)
+                {
+                    continue;
+                }
+                let Ok(content) = tokio::fs::read_to_string(&path).await else {
+                    continue;
+                };
+                let Ok(snapshot) = toml::from_str::<RecoverySnapshot>(&content) else {
+                    continue;
+                };
+                found = true;
+                let file_changed = match &snapshot.file_path {
+                    Some(file_path) => {
+                        let modified = tokio::fs::metadata(file_path)
+                            .await
+                            .and_then(|metadata| metadata.modified())
+                            .ok()
+                            .map(nanos_since_epoch);
+                        modified != snapshot.file_modified
+                    }
+                    None => false,
+                };
+                recovered.push(RecoveredSnapshot {
+                    path,
+                    snapshot,
+                    file_changed,
+                });
+            }
+            if !found {
+                let _ = tokio::fs::remove_dir_all(&session_path).await;
+            }
+        }
+        recovered.sort_by_key(|recovered| recovered.snapshot.saved_at);
+        recovered
+    }
+}
+
+///
+/// The files to write to and remove from the journal, found by
+/// `RecoveryJournal::changes()`.
+///
+#[derive(Debug, Default)]
+pub struct JournalChanges {
+    session_path: PathBuf,
+    writes: Vec<(PathBuf, String)>,
+    removals: Vec<PathBuf>,
+    heartbeat: bool,
+    remove_session: bool,
+}
+
+impl JournalChanges {
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+            && self.removals.is_empty()
+            && !self.heartbeat
+            && !self.remove_session
+    }
+
+    pub async fn apply(self) -> Result<(), AppIOError> {
+        let io_error = |error: std::io::Error| AppIOError::IOFailedError(error.kind());
+        if self.remove_session {
+            return tokio::fs::remove_dir_all(&self.session_path)
+                .await
+                .map_err(io_error);
+        }
+        tokio::fs::create_dir_all(&self.session_path)
+            .await
+            .map_err(io_error)?;
+        for (path, snapshot) in &self.writes {
+            write_file_atomic(path, snapshot.as_bytes())
+                .await
+                .map_err(io_error)?;
+        }
+        for path in &self.removals {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        if self.heartbeat {
+            tokio::fs::write(self.session_path.join(HEARTBEAT_FILE), [])
+                .await
+                .map_err(io_error)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Returns `true` if the heartbeat of the session was written recently.
+///
+async fn is_session_alive(session_path: &Path) -> bool {
+    tokio::fs::metadata(session_path.join(HEARTBEAT_FILE))
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < RECOVERY_SESSION_TIMEOUT)
+}
+
+fn has_snapshots(session_path: &Path) -> bool {
+    std::fs::read_dir(session_path).is_ok_and(|mut entries| {
+        entries.any(|entry| {
+            entry.is_ok_and(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|extension| extension == SNAPSHOT_EXTENSION)
+            })
+        })
+    })
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as u64)
+}
@@ -0,0 +1,104 @@
+//!
+//! Notespace-Editor
+//!
+//! Cursor and selection helpers for `text_editor::Content`.
+//!
+//! `Content` only exposes relative cursor motions, so placing the cursor at an
+//! absolute position is done by replaying motions from a known point.
+//!
+
+use iced::widget::text_editor::{Action, Content, Motion};
+
+///
+/// A position in a `Content`: `(line, column)`, where `column` is a byte index into
+/// the line. This is the same unit as `Content::cursor_position()`.
+///
+pub type TextPosition = (usize, usize);
+
+///
+/// Move the cursor to `position`, clearing any selection.
+///
+pub fn move_cursor_to(content: &mut Content, position: TextPosition) {
+    content.perform(Action::Move(Motion::DocumentStart));
+    advance_to(content, position, Action::Move);
+}
+
+///
+/// The position of the 1-based `line` and `column`, counting the column in
+/// characters, as used on the command line and in compiler messages.
+///
+pub fn position_from_line_column(content: &Content, line: usize, column: usize) -> TextPosition {
+    let line = line.saturating_sub(1);
+    let column = content
+        .line(line)
+        .and_then(|text| {
+            text.char_indices()
+                .map(|(index, _)| index)
+                .chain(std::iter::once(text.len()))
+                .nth(column.saturating_sub(1))
+        })
+        .unwrap_or(0);
+    (line, column)
+}
+
+///
+/// Select the text between `start` and `end`. The cursor is left at `end`.
+///
+pub fn select_range(content: &mut Content, start: TextPosition, end: TextPosition) {
+    let (start, end) = if start <= end {
+        (start, end)
+    } else {
+        (end, start)
+    };
+    move_cursor_to(content, start);
+    if start != end {
+        advance_to(content, end, Action::Select);
+    }
+}
+
+///
+/// Move forward from the current cursor to `position`, using `action` to turn each
+/// motion into an `Action` (either `Action::Move` or `Action::Select`).
+///
+/// `Motion::Down` moves by visual (wrapped) lines, so it is repeated until the
+/// cursor reaches the target line. The column is then reached one character at a
+/// time.
+///
+fn advance_to(content: &mut Content, position: TextPosition, action: fn(Motion) -> Action) {
+    let (line, column) = position;
+    let line = line.min(content.line_count().saturating_sub(1));
+    let column = content
+        .line(line)
+        .map(|text| floor_char_boundary(&text, column))
+        .unwrap_or(0);
+
+    let start_line = content.cursor_position().0;
+    while content.cursor_position().0 < line {
+        let before = content.cursor_position();
+        content.perform(action(Motion::Down));
+        if content.cursor_position() == before {
+            break;
+        }
+    }
+    if content.cursor_position().0 != start_line {
+        content.perform(action(Motion::Home));
+    }
+    while content.cursor_position().0 == line && content.cursor_position().1 < column {
+        let before = content.cursor_position();
+        content.perform(action(Motion::Right));
+        if content.cursor_position() == before {
+            break;
+        }
+    }
+}
+
+///
+/// The largest char boundary in `text` that is not greater than `index`.
+///
+pub fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
@@ -0,0 +1,265 @@
+//!
+//! Notespace-Editor
+//!
+//! A single open document (buffer) shown in a tab.
+//!
+
+use crate::app_io::LoadedFile;
+use crate::document::edit_history::EditHistory;
+use crate::document::line_ending::LineEnding;
+use crate::document::text_cursor::move_cursor_to;
+use crate::document::text_encoding::TextEncoding;
+use iced::widget::scrollable::AbsoluteOffset;
+use iced::widget::text_editor::{Action, Content, Edit};
+use rust_i18n::t;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+///
+/// Unique identifier for an open document. Identifiers are never reused while the
+/// application is running, so async results can safely refer to a document that
+/// may have been closed in the meantime.
+///
+pub type DocumentId = u64;
+
+///
+/// An open document: the text buffer plus the file state associated with it.
+///
+/// The cursor and selection are owned by the `Content`.
+///
+pub struct TextDocument {
+    pub(crate) id: DocumentId,
+    pub(crate) file_path: Option<PathBuf>,
+    pub(crate) file_dirty: bool,
+    pub(crate) file_content: Content,
+    pub(crate) scroll_offset: AbsoluteOffset,
+    pub(crate) history: EditHistory,
+    ///
+    /// The encoding the file is saved in.
+    ///
+    pub(crate) encoding: TextEncoding,
+    ///
+    /// The line ending the file is saved with. The `Content` always uses `\n`.
+    ///
+    pub(crate) line_ending: LineEnding,
+    ///
+    /// The line ending of the file when it was last loaded or saved. Converting
+    /// the line ending is not an edit, so it is compared with this instead.
+    ///
+    pub(crate) saved_line_ending: LineEnding,
+    ///
+    /// The file ends with a line ending. `Content` drops the line ending after the
+    /// last line when the text is loaded, so it is remembered here.
+    ///
+    pub(crate) final_line_ending: bool,
+    ///
+    /// The modification time of the file when it was last loaded or saved.
+    ///
+    pub(crate) file_modified: Option<SystemTime>,
+    ///
+    /// The file was deleted or renamed by another program.
+    ///
+    pub(crate) file_missing: bool,
+    ///
+    /// The syntax chosen by the user, as a file extension. `None` uses the
+    /// extension of the file.
+    ///
+    pub(crate) syntax_mode: Option<String>,
+}
+
+impl TextDocument {
+    ///
+    /// Create an empty, untitled document that will be saved with `line_ending`.
+    ///
+    pub fn new(id: DocumentId, line_ending: LineEnding) -> Self {
+        Self {
+            id,
+            file_path: None,
+            file_dirty: false,
+            file_content: Content::new(),
+            scroll_offset: AbsoluteOffset::default(),
+            history: EditHistory::new(),
+            encoding: TextEncoding::default(),
+            line_ending,
+            saved_line_ending: line_ending,
+            final_line_ending: false,
+            file_modified: None,
+            file_missing: false,
+            syntax_mode: None,
+        }
+    }
+
+    ///
+    /// Create a document for the `loaded_file`. The line ending is detected from
+    /// the text; `default_line_ending` is used for a file with a single line.
+    ///
+    pub fn with_file(
+        id: DocumentId,
+        loaded_file: &LoadedFile,
+        default_line_ending: LineEnding,
+    ) -> Self {
+        let (text, line_ending) = LineEnding::normalize(&loaded_file.text);
+        let line_ending = line_ending.unwrap_or(default_line_ending);
+        let file_content = Content::with_text(&text);
+        Self {
+            id,
+            file_path: Some(loaded_file.path.clone()),
+            file_dirty: false,
+            history: EditHistory::new(),
+            file_content,
+            scroll_offset: AbsoluteOffset::default(),
+            encoding: loaded_file.encoding,
+            line_ending,
+            saved_line_ending: line_ending,
+            final_line_ending: text.ends_with('\n'),
+            file_modified: loaded_file.modified,
+            file_missing: false,
+            syntax_mode: None,
+        }
+    }
+
+    ///
+    /// Create an untitled document with `text` that has not been saved yet, e.g.
+    /// text read from standard input.
+    ///
+    pub fn untitled_with_text(id: DocumentId, text: &str, default_line_ending: LineEnding) -> Self {
+        let (text, line_ending) = LineEnding::normalize(text);
+        let line_ending = line_ending.unwrap_or(default_line_ending);
+        let file_content = Content::with_text(&text);
+        let mut history = EditHistory::new();
+        history.mark_unsaved();
+        Self {
+            id,
+            file_path: None,
+            file_dirty: true,
+            history,
+            file_content,
+            scroll_offset: AbsoluteOffset::default(),
+            encoding: TextEncoding::default(),
+            line_ending,
+            saved_line_ending: line_ending,
+            final_line_ending: text.ends_with('\n'),
+            file_modified: None,
+            file_missing: false,
+            syntax_mode: None,
+        }
+    }
+
+    ///
+    /// Returns `true` if saving would write the same file as was last loaded or
+    /// saved: the text is in its saved state and the line ending is unchanged.
+    ///
+    pub fn is_saved(&self) -> bool {
+        self.history.is_saved() && self.line_ending == self.saved_line_ending
+    }
+
+    ///
+    /// The text as it is written to the file, with the line ending of the document.
+    ///
+    /// Unlike `Content::text()`, a line ending is only added after the last line if
+    /// the file had one, so files are saved with the same final line as they were
+    /// loaded with.
+    ///
+    pub fn text(&self) -> String {
+        let line_ending = self.line_ending.as_str();
+        let mut text = String::new();
+        for (index, line) in self.file_content.lines().enumerate() {
+            if index > 0 {
+                text.push_str(line_ending);
+            }
+            text.push_str(&line);
+        }
+        if self.final_line_ending {
+            text.push_str(line_ending);
+        }
+        text
+    }
+
+    ///
+    /// Replace the whole text with `text`, e.g. the file decoded again with another
+    /// encoding. The replacement is recorded in the history, so it can be undone.
+    /// The cursor stays on the same line and column where possible.
+    ///
+    pub fn replace_text(&mut self, text: &str) {
+        let (text, line_ending) = LineEnding::normalize(text);
+        if let Some(line_ending) = line_ending {
+            self.line_ending = line_ending;
+        }
+        //
+        // Pasting adds an empty line for a final line ending, loading does not.
+        //
+        let (text, final_line_ending) = match text.strip_suffix('\n') {
+            Some(text) => (text, true),
+            None => (text.as_ref(), false),
+        };
+        self.final_line_ending = final_line_ending;
+        let cursor = self.file_content.cursor_position();
+        self.file_content.perform(Action::SelectAll);
+        let edit = match text.is_empty() {
+            true => Edit::Backspace,
+            false => Edit::Paste(Arc::new(text.to_string())),
+        };
+        self.history.perform(&mut self.file_content, edit);
+        move_cursor_to(&mut self.file_content, cursor);
+    }
+
+    ///
+    /// The name shown in the tab strip: the file name, or a localized
+    /// placeholder for untitled documents.
+    ///
+    pub fn title(&self) -> String {
+        self.file_path
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| t!("untitled").to_string())
+    }
+
+    ///
+    /// The file extension used to select the syntax highlighter.
+    ///
+    pub fn syntax_extension(&self) -> String {
+        if let Some(syntax_mode) = &self.syntax_mode {
+            return syntax_mode.clone();
+        }
+        self.file_path
+            .as_ref()
+            .and_then(|path| path.extension()?.to_str())
+            .unwrap_or("md") // TODO: App config setting for default file extension.
+            .to_string()
+    }
+
+    ///
+    /// Returns `true` for an untitled, unmodified and empty document. Such a
+    /// document is replaced when a file is opened.
+    ///
+    pub fn is_pristine(&self) -> bool {
+        self.file_path.is_none()
+            && !self.file_dirty
+            && self.file_content.line_count() <= 1
+            && self.file_content.line(0).is_none_or(|line| line.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converting_the_line_ending_back_is_saved() {
+        let loaded_file = LoadedFile {
+            path: PathBuf::from("notes.md"),
+            text: Arc::new("one\r\ntwo\r\n".to_string()),
+            encoding: TextEncoding::default(),
+            modified: None,
+        };
+        let mut document = TextDocument::with_file(1, &loaded_file, LineEnding::Lf);
+        assert_eq!(document.line_ending, LineEnding::CrLf);
+        assert!(document.is_saved());
+        document.line_ending = LineEnding::Lf;
+        assert!(!document.is_saved());
+        document.line_ending = LineEnding::CrLf;
+        assert!(document.is_saved());
+    }
+}
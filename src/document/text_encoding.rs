@@ -0,0 +1,282 @@
+//!
+//! Notespace-Editor
+//!
+//! Text encodings of files: detection when a file is opened, and conversion back
+//! to the same encoding when it is saved.
+//!
+//! A byte order mark (BOM) decides the encoding when there is one. Otherwise the
+//! text is UTF-8 if it is valid UTF-8, UTF-16 if the NUL bytes look like UTF-16
+//! with mostly ASCII characters, and else the most likely legacy encoding
+//! guessed by `chardetng`. Data with many control characters is binary.
+//!
+
+use crate::app_const::{BINARY_CHECK_LENGTH, BINARY_CONTROL_PERCENT};
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+
+///
+/// The encoding of a file, and whether it starts with a byte order mark.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEncoding {
+    pub(crate) encoding: &'static Encoding,
+    pub(crate) bom: bool,
+}
+
+impl Default for TextEncoding {
+    ///
+    /// UTF-8 without BOM, used for new files.
+    ///
+    fn default() -> Self {
+        Self::new(UTF_8, false)
+    }
+}
+
+impl TextEncoding {
+    pub fn new(encoding: &'static Encoding, bom: bool) -> Self {
+        Self { encoding, bom }
+    }
+
+    ///
+    /// The encodings that can be chosen by the user, in display order.
+    ///
+    pub fn all() -> Vec<TextEncoding> {
+        let mut encodings = vec![
+            TextEncoding::new(UTF_8, false),
+            TextEncoding::new(UTF_8, true),
+            TextEncoding::new(UTF_16LE, true),
+            TextEncoding::new(UTF_16LE, false),
+            TextEncoding::new(UTF_16BE, true),
+            TextEncoding::new(UTF_16BE, false),
+        ];
+        encodings.extend(
+            [
+                encoding_rs::WINDOWS_1252,
+                encoding_rs::ISO_8859_15,
+                encoding_rs::WINDOWS_1250,
+                encoding_rs::ISO_8859_2,
+                encoding_rs::WINDOWS_1251,
+                encoding_rs::KOI8_R,
+                encoding_rs::IBM866,
+                encoding_rs::WINDOWS_1253,
+                encoding_rs::WINDOWS_1254,
+                encoding_rs::WINDOWS_1255,
+                encoding_rs::WINDOWS_1256,
+                encoding_rs::WINDOWS_874,
+                encoding_rs::SHIFT_JIS,
+                encoding_rs::EUC_JP,
+                encoding_rs::EUC_KR,
+                encoding_rs::GB18030,
+                encoding_rs::BIG5,
+            ]
+            .into_iter()
+            .map(|encoding| TextEncoding::new(encoding, false)),
+        );
+        encodings
+    }
+
+    ///
+    /// The name shown in the statusbar and the command palette, e.g. "UTF-8 BOM".
+    ///
+    pub fn label(&self) -> String {
+        match self.bom {
+            true => format!("{} BOM", self.encoding.name()),
+            false => self.encoding.name().to_string(),
+        }
+    }
+
+    ///
+    /// The byte order mark written at the start of the file, if any.
+    ///
+    fn bom_bytes(&self) -> &'static [u8] {
+        match self.bom {
+            true if self.encoding == UTF_8 => b"\xEF\xBB\xBF",
+            true if self.encoding == UTF_16LE => b"\xFF\xFE",
+            true if self.encoding == UTF_16BE => b"\xFE\xFF",
+            _ => b"",
+        }
+    }
+
+    ///
+    /// Detect the encoding of `bytes` and decode them. Returns `None` for binary
+    /// data.
+    ///
+    pub fn detect_and_decode(bytes: &[u8]) -> Option<(String, TextEncoding)> {
+        if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+            let text_encoding = TextEncoding::new(encoding, true);
+            return Some((text_encoding.decode(bytes), text_encoding));
+        }
+        let sample = &bytes[..bytes.len().min(BINARY_CHECK_LENGTH)];
+        let encoding = if sample.contains(&0) {
+            detect_utf16(bytes, sample)?
+        } else if has_many_control_bytes(sample) {
+            return None;
+        } else if std::str::from_utf8(bytes).is_ok() {
+            UTF_8
+        } else {
+            let mut detector = chardetng::EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, true)
+        };
+        let text_encoding = TextEncoding::new(encoding, false);
+        Some((text_encoding.decode(bytes), text_encoding))
+    }
+
+    ///
+    /// Decode `bytes` with this encoding, skipping a matching byte order mark.
+    /// Invalid sequences are replaced with U+FFFD.
+    ///
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let bytes = bytes
+            .strip_prefix(TextEncoding::new(self.encoding, true).bom_bytes())
+            .unwrap_or(bytes);
+        let (text, _had_errors) = self.encoding.decode_without_bom_handling(bytes);
+        text.into_owned()
+    }
+
+    ///
+    /// Returns `true` if the start of `bytes` is the byte order mark of this encoding.
+    ///
+    pub fn has_bom(&self, bytes: &[u8]) -> bool {
+        let bom = TextEncoding::new(self.encoding, true).bom_bytes();
+        !bom.is_empty() && bytes.starts_with(bom)
+    }
+
+    ///
+    /// Encode `text`, with the byte order mark if there is one. Returns `None` if
+    /// the text contains characters that the encoding cannot represent.
+    ///
+    pub fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        let mut bytes = self.bom_bytes().to_vec();
+        //
+        // `encoding_rs` follows the WHATWG Encoding Standard, which writes UTF-8
+        // for UTF-16, so UTF-16 is encoded here.
+        //
+        if self.encoding == UTF_16LE {
+            bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        } else if self.encoding == UTF_16BE {
+            bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        } else {
+            let (encoded, _, had_errors) = self.encoding.encode(text);
+            if had_errors {
+                return None;
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+        Some(bytes)
+    }
+}
+
+///
+/// Recognize UTF-16 without a BOM: text that is mostly ASCII has a NUL in every
+/// other byte. Any other NUL byte means the data is binary, so `None` is returned.
+///
+fn detect_utf16(bytes: &[u8], sample: &[u8]) -> Option<&'static Encoding> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let (mut even, mut odd) = (0, 0);
+    for (index, byte) in sample.iter().enumerate() {
+        if *byte == 0 {
+            match index % 2 {
+                0 => even += 1,
+                _ => odd += 1,
+            }
+        }
+    }
+    let units = sample.len() / 2;
+    match (even, odd) {
+        (0, odd) if odd * 2 >= units => Some(UTF_16LE),
+        (even, 0) if even * 2 >= units => Some(UTF_16BE),
+        _ => None,
+    }
+}
+
+///
+/// Returns `true` if more than `BINARY_CONTROL_PERCENT` of the `sample` are C0
+/// control characters other than whitespace and escape, which text rarely has.
+///
+fn has_many_control_bytes(sample: &[u8]) -> bool {
+    let control_bytes = sample
+        .iter()
+        .filter(|byte| {
+            **byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\x0B' | b'\x0C' | b'\r' | b'\x1B')
+        })
+        .count();
+    control_bytes * 100 > sample.len() * BINARY_CONTROL_PERCENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_without_bom() {
+        let (text, encoding) = TextEncoding::detect_and_decode("grüße".as_bytes()).unwrap();
+        assert_eq!(text, "grüße");
+        assert_eq!(encoding, TextEncoding::new(UTF_8, false));
+    }
+
+    #[test]
+    fn bom_decides_the_encoding() {
+        let (text, encoding) = TextEncoding::detect_and_decode(b"\xEF\xBB\xBFabc").unwrap();
+        assert_eq!(text, "abc");
+        assert_eq!(encoding, TextEncoding::new(UTF_8, true));
+        let (text, encoding) = TextEncoding::detect_and_decode(b"\xFF\xFEa\0b\0").unwrap();
+        assert_eq!(text, "ab");
+        assert_eq!(encoding, TextEncoding::new(UTF_16LE, true));
+    }
+
+    #[test]
+    fn utf16_without_bom() {
+        let (text, encoding) = TextEncoding::detect_and_decode(b"\0h\0i").unwrap();
+        assert_eq!(text, "hi");
+        assert_eq!(encoding, TextEncoding::new(UTF_16BE, false));
+        let (text, encoding) = TextEncoding::detect_and_decode(b"h\0i\0").unwrap();
+        assert_eq!(text, "hi");
+        assert_eq!(encoding, TextEncoding::new(UTF_16LE, false));
+    }
+
+    #[test]
+    fn binary_data_is_not_text() {
+        assert!(TextEncoding::detect_and_decode(b"\0\0ab\0").is_none());
+        assert!(TextEncoding::detect_and_decode(b"a\0\0b").is_none());
+    }
+
+    #[test]
+    fn control_characters_without_nul_are_binary() {
+        assert!(TextEncoding::detect_and_decode(b"\x01\x02header\x03\x7F\x04\xFFdata").is_none());
+        let (text, _) =
+            TextEncoding::detect_and_decode(b"\x1B[1mbold\x1B[0m\tcolumn\r\n\x0Cpage").unwrap();
+        assert_eq!(text, "\x1B[1mbold\x1B[0m\tcolumn\r\n\x0Cpage");
+    }
+
+    #[test]
+    fn legacy_encoding_is_guessed() {
+        let bytes = encoding_rs::WINDOWS_1252
+            .encode("Café au lait, crème brûlée et pâté.")
+            .0;
+        let (text, _) = TextEncoding::detect_and_decode(&bytes).unwrap();
+        assert_eq!(text, "Café au lait, crème brûlée et pâté.");
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        for encoding in TextEncoding::all() {
+            let bytes = encoding.encode("plain text").unwrap();
+            assert_eq!(encoding.has_bom(&bytes), encoding.bom);
+            assert_eq!(encoding.decode(&bytes), "plain text");
+        }
+    }
+
+    #[test]
+    fn encode_fails_for_unmappable_characters() {
+        let encoding = TextEncoding::new(encoding_rs::WINDOWS_1252, false);
+        assert!(encoding.encode("日本").is_none());
+    }
+
+    #[test]
+    fn label_mentions_the_bom() {
+        assert_eq!(TextEncoding::new(UTF_8, true).label(), "UTF-8 BOM");
+        assert_eq!(TextEncoding::default().label(), "UTF-8");
+    }
+}
@@ -0,0 +1,123 @@
+//!
+//! Notespace-Editor
+//!
+//! Syntax highlighter that also highlights find matches.
+//!
+//! Wraps `iced::highlighter::Highlighter` and appends a highlight for each match of
+//! the find pattern. The editor applies highlights in order, so the match colors
+//! take precedence over the syntax colors.
+//!
+
+use crate::find::find_state::FindMatch;
+use iced::advanced::text::highlighter::{self, Format};
+use iced::{Font, Theme};
+use regex::Regex;
+use std::ops::Range;
+
+///
+/// Settings for `FindHighlighter`. `pattern` is the find pattern from
+/// `FindState::pattern()` and `current_match` is shown in a different color.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindHighlighterSettings {
+    pub(crate) syntax: iced::highlighter::Settings,
+    pub(crate) pattern: Option<String>,
+    pub(crate) current_match: Option<FindMatch>,
+}
+
+pub enum FindHighlight {
+    Syntax(iced::highlighter::Highlight),
+    Match,
+    CurrentMatch,
+}
+
+pub struct FindHighlighter {
+    syntax: iced::highlighter::Highlighter,
+    regex: Option<Regex>,
+    current_match: Option<FindMatch>,
+}
+
+impl FindHighlighter {
+    ///
+    /// Convert a `FindHighlight` to the text format used by the editor.
+    ///
+    pub fn to_format(highlight: &FindHighlight, theme: &Theme) -> Format<Font> {
+        match highlight {
+            FindHighlight::Syntax(highlight) => highlight.to_format(),
+            FindHighlight::Match => Format {
+                color: Some(theme.extended_palette().primary.strong.color),
+                font: None,
+            },
+            FindHighlight::CurrentMatch => Format {
+                color: Some(theme.extended_palette().success.strong.color),
+                font: None,
+            },
+        }
+    }
+}
+
+impl highlighter::Highlighter for FindHighlighter {
+    type Settings = FindHighlighterSettings;
+    type Highlight = FindHighlight;
+    type Iterator<'a> = Box<dyn Iterator<Item = (Range<usize>, FindHighlight)> + 'a>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            syntax: iced::highlighter::Highlighter::new(&settings.syntax),
+            regex: settings
+                .pattern
+                .as_deref()
+                .and_then(|pattern| Regex::new(pattern).ok()),
+            current_match: settings.current_match,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.syntax.update(&new_settings.syntax);
+        self.regex = new_settings
+            .pattern
+            .as_deref()
+            .and_then(|pattern| Regex::new(pattern).ok());
+        self.current_match = new_settings.current_match;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.syntax.change_line(line);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let line_index = self.syntax.current_line();
+        let current_match = self.current_match;
+        let matches: Vec<(Range<usize>, FindHighlight)> = self
+            .regex
+            .as_ref()
+            .map(|regex| {
+                regex
+                    .find_iter(line)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| {
+                        let is_current = current_match.is_some_and(|current| {
+                            current.line == line_index && current.start == m.start()
+                        });
+                        let highlight = if is_current {
+                            FindHighlight::CurrentMatch
+                        } else {
+                            FindHighlight::Match
+                        };
+                        (m.range(), highlight)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Box::new(
+            self.syntax
+                .highlight_line(line)
+                .map(|(range, highlight)| (range, FindHighlight::Syntax(highlight)))
+                .chain(matches),
+        )
+    }
+
+    fn current_line(&self) -> usize {
+        self.syntax.current_line()
+    }
+}
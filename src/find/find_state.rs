@@ -0,0 +1,293 @@
+//!
+//! Notespace-Editor
+//!
+//! Find and replace state for the active document.
+//!
+//! Matching is done line by line, so a pattern never matches across a line break.
+//!
+
+use crate::document::text_cursor::TextPosition;
+use iced::widget::text_editor::Content;
+use regex::{Captures, Regex};
+
+///
+/// Options that control how the find query is matched.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    pub(crate) case_sensitive: bool,
+    pub(crate) whole_word: bool,
+    pub(crate) regex: bool,
+}
+
+///
+/// A match within a single line. `start` and `end` are byte indexes into the line.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FindMatch {
+    pub(crate) line: usize,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+impl FindMatch {
+    pub fn start_position(&self) -> TextPosition {
+        (self.line, self.start)
+    }
+
+    pub fn end_position(&self) -> TextPosition {
+        (self.line, self.end)
+    }
+}
+
+///
+/// State of the find/replace panel.
+///
+#[derive(Debug, Default)]
+pub struct FindState {
+    pub(crate) visible: bool,
+    pub(crate) show_replace: bool,
+    pub(crate) query: String,
+    pub(crate) replacement: String,
+    pub(crate) options: FindOptions,
+    pub(crate) matches: Vec<FindMatch>,
+    pub(crate) current_match: Option<usize>,
+    pub(crate) error: Option<String>,
+}
+
+impl FindState {
+    ///
+    /// The regular expression source for the current query and options, or `None`
+    /// if there is nothing to search for or the query is not a valid expression.
+    ///
+    /// The case option is embedded in the pattern, so the pattern alone is enough
+    /// to rebuild the `Regex` (see `FindHighlighter`).
+    ///
+    pub fn pattern(&self) -> Option<String> {
+        if !self.visible || self.query.is_empty() {
+            return None;
+        }
+        let pattern = build_pattern(&self.query, self.options);
+        Regex::new(&pattern).is_ok().then_some(pattern)
+    }
+
+    ///
+    /// Search `content` again, e.g. after the query, options or text changed.
+    ///
+    /// The current match becomes the first match at or after the cursor. When the
+    /// current match is selected, the search starts at its start instead, so that
+    /// typing more of the query keeps the match that still fits.
+    ///
+    pub fn update_matches(&mut self, content: &Content) {
+        let cursor = content.cursor_position();
+        let anchor = match self.current() {
+            Some(current) if current.end_position() == cursor => current.start_position(),
+            _ => cursor,
+        };
+        self.matches.clear();
+        self.current_match = None;
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+        let regex = match Regex::new(&build_pattern(&self.query, self.options)) {
+            Ok(regex) => regex,
+            Err(error) => {
+                self.error = Some(error.to_string());
+                return;
+            }
+        };
+        for (line_index, line) in content.lines().enumerate() {
+            self.matches
+                .extend(
+                    regex
+                        .find_iter(&line)
+                        .filter(|m| !m.is_empty())
+                        .map(|m| FindMatch {
+                            line: line_index,
+                            start: m.start(),
+                            end: m.end(),
+                        }),
+                );
+        }
+        if !self.matches.is_empty() {
+            self.current_match = Some(
+                self.matches
+                    .iter()
+                    .position(|m| m.start_position() >= anchor)
+                    .unwrap_or(0),
+            );
+        }
+    }
+
+    ///
+    /// The match that is currently selected, if any.
+    ///
+    pub fn current(&self) -> Option<FindMatch> {
+        self.current_match
+            .and_then(|index| self.matches.get(index))
+            .copied()
+    }
+
+    ///
+    /// Select the first match after `cursor`, wrapping around to the first match.
+    ///
+    pub fn select_next(&mut self, cursor: TextPosition) -> Option<FindMatch> {
+        let index = self
+            .matches
+            .iter()
+            .position(|m| m.start_position() >= cursor && Some(*m) != self.current())
+            .or_else(|| (!self.matches.is_empty()).then_some(0))?;
+        self.current_match = Some(index);
+        self.current()
+    }
+
+    ///
+    /// Select the last match before `cursor`, wrapping around to the last match.
+    ///
+    pub fn select_previous(&mut self, cursor: TextPosition) -> Option<FindMatch> {
+        let index = self
+            .matches
+            .iter()
+            .rposition(|m| m.start_position() < cursor && Some(*m) != self.current())
+            .or_else(|| self.matches.len().checked_sub(1))?;
+        self.current_match = Some(index);
+        self.current()
+    }
+
+    ///
+    /// The replacement text for `matched`, a match in `line`. In regex mode
+    /// `$1`-style capture group references are expanded, otherwise the replacement
+    /// is used literally.
+    ///
+    /// The expression is run on the whole line, so anchors, boundaries and
+    /// captures see the same text as when the match was found.
+    ///
+    pub fn replacement_for(&self, line: &str, matched: FindMatch) -> String {
+        if !self.options.regex {
+            return self.replacement.clone();
+        }
+        let captures = Regex::new(&build_pattern(&self.query, self.options))
+            .ok()
+            .and_then(|regex| regex.captures_at(line, matched.start));
+        match captures {
+            Some(captures)
+                if captures
+                    .get(0)
+                    .is_some_and(|m| m.start() == matched.start && m.end() == matched.end) =>
+            {
+                let mut replacement = String::new();
+                captures.expand(&self.replacement, &mut replacement);
+                replacement
+            }
+            _ => self.replacement.clone(),
+        }
+    }
+
+    ///
+    /// Returns the text of `content` with every match replaced, and the number of
+    /// replacements, or `None` if nothing matched.
+    ///
+    pub fn replace_all(&self, content: &Content) -> Option<(String, usize)> {
+        let regex = Regex::new(&build_pattern(&self.query, self.options)).ok()?;
+        let mut count = 0;
+        let lines: Vec<String> = content
+            .lines()
+            .map(|line| {
+                count += regex.find_iter(&line).filter(|m| !m.is_empty()).count();
+                replace_matches(&regex, &line, &self.replacement, self.options.regex)
+            })
+            .collect();
+        (count > 0).then(|| (lines.join("\n"), count))
+    }
+}
+
+///
+/// Replace every match of `regex` in `line`. With `expand`, `$1`-style capture
+/// group references in `replacement` are expanded, otherwise it is used literally.
+///
+/// Empty matches are left alone, they are not found or counted either.
+///
+pub fn replace_matches(regex: &Regex, line: &str, replacement: &str, expand: bool) -> String {
+    regex
+        .replace_all(line, |captures: &Captures| {
+            let mut text = String::new();
+            if captures.get(0).is_some_and(|m| !m.is_empty()) {
+                match expand {
+                    true => captures.expand(replacement, &mut text),
+                    false => text.push_str(replacement),
+                }
+            }
+            text
+        })
+        .to_string()
+}
+
+///
+/// Build the regular expression source for `query`.
+///
+pub fn build_pattern(query: &str, options: FindOptions) -> String {
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    //
+    // Half boundaries only require a non-word character (or the line edge) on
+    // the outside, so queries that begin or end with punctuation still match.
+    //
+    let pattern = if options.whole_word {
+        format!(r"\b{{start-half}}(?:{})\b{{end-half}}", pattern)
+    } else {
+        pattern
+    };
+    let case_flag = if options.case_sensitive { "" } else { "(?i)" };
+    format!("{}{}", case_flag, pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::text_cursor::select_range;
+
+    #[test]
+    fn longer_query_keeps_the_selected_match() {
+        let mut content = Content::with_text("fo foo foo");
+        let mut find_state = FindState {
+            query: "fo".to_string(),
+            ..FindState::default()
+        };
+        select_range(&mut content, (0, 3), (0, 3));
+        find_state.update_matches(&content);
+        let current = find_state.current().unwrap();
+        assert_eq!(current.start_position(), (0, 3));
+        select_range(
+            &mut content,
+            current.start_position(),
+            current.end_position(),
+        );
+        find_state.query = "foo".to_string();
+        find_state.update_matches(&content);
+        assert_eq!(find_state.current().unwrap().start_position(), (0, 3));
+    }
+
+    #[test]
+    fn search_starts_at_the_cursor() {
+        let mut content = Content::with_text("foo foo");
+        let mut find_state = FindState {
+            query: "foo".to_string(),
+            ..FindState::default()
+        };
+        select_range(&mut content, (0, 1), (0, 1));
+        find_state.update_matches(&content);
+        assert_eq!(find_state.current().unwrap().start_position(), (0, 4));
+    }
+
+    #[test]
+    fn replace_matches_skips_empty_matches() {
+        let regex = Regex::new("a*").unwrap();
+        assert_eq!(replace_matches(&regex, "baac", "X", false), "bXc");
+        let regex = Regex::new(r"(\w+)|\b").unwrap();
+        assert_eq!(replace_matches(&regex, "ab cd", "<$1>", true), "<ab> <cd>");
+    }
+}
@@ -0,0 +1,5 @@
+//!
+//! Find and replace modules.
+//!
+pub(crate) mod find_highlighter;
+pub(crate) mod find_state;
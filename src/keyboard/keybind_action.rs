@@ -7,10 +7,142 @@ pub enum KeybindAction {
     CutText,
     CopyText,
     PasteText,
+    Undo,
+    Redo,
+    SelectAll,
     NewFile,
     OpenFile,
+    ReopenClosedFile,
+    OpenFolder,
+    CloseFolder,
+    QuickOpen,
     SaveFile,
     CloseFile,
+    NextTab,
+    PreviousTab,
+    Find,
+    FindNext,
+    FindPrevious,
+    Replace,
+    SearchInFolder,
+    ToggleMarkdownPreview,
+    ExportHtml,
+    ExportPdf,
     ShowSettings,
+    CommandPalette,
     QuitApplication,
 }
+
+impl KeybindAction {
+    ///
+    /// Every `KeybindAction`, in display order.
+    ///
+    pub const ALL: [KeybindAction; 27] = [
+        KeybindAction::CutText,
+        KeybindAction::CopyText,
+        KeybindAction::PasteText,
+        KeybindAction::Undo,
+        KeybindAction::Redo,
+        KeybindAction::SelectAll,
+        KeybindAction::NewFile,
+        KeybindAction::OpenFile,
+        KeybindAction::ReopenClosedFile,
+        KeybindAction::OpenFolder,
+        KeybindAction::CloseFolder,
+        KeybindAction::QuickOpen,
+        KeybindAction::SaveFile,
+        KeybindAction::CloseFile,
+        KeybindAction::NextTab,
+        KeybindAction::PreviousTab,
+        KeybindAction::Find,
+        KeybindAction::FindNext,
+        KeybindAction::FindPrevious,
+        KeybindAction::Replace,
+        KeybindAction::SearchInFolder,
+        KeybindAction::ToggleMarkdownPreview,
+        KeybindAction::ExportHtml,
+        KeybindAction::ExportPdf,
+        KeybindAction::ShowSettings,
+        KeybindAction::CommandPalette,
+        KeybindAction::QuitApplication,
+    ];
+
+    ///
+    /// The name used for the action in the keybindings file.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeybindAction::CutText => "CutText",
+            KeybindAction::CopyText => "CopyText",
+            KeybindAction::PasteText => "PasteText",
+            KeybindAction::Undo => "Undo",
+            KeybindAction::Redo => "Redo",
+            KeybindAction::SelectAll => "SelectAll",
+            KeybindAction::NewFile => "NewFile",
+            KeybindAction::OpenFile => "OpenFile",
+            KeybindAction::ReopenClosedFile => "ReopenClosedFile",
+            KeybindAction::OpenFolder => "OpenFolder",
+            KeybindAction::CloseFolder => "CloseFolder",
+            KeybindAction::QuickOpen => "QuickOpen",
+            KeybindAction::SaveFile => "SaveFile",
+            KeybindAction::CloseFile => "CloseFile",
+            KeybindAction::NextTab => "NextTab",
+            KeybindAction::PreviousTab => "PreviousTab",
+            KeybindAction::Find => "Find",
+            KeybindAction::FindNext => "FindNext",
+            KeybindAction::FindPrevious => "FindPrevious",
+            KeybindAction::Replace => "Replace",
+            KeybindAction::SearchInFolder => "SearchInFolder",
+            KeybindAction::ToggleMarkdownPreview => "ToggleMarkdownPreview",
+            KeybindAction::ExportHtml => "ExportHtml",
+            KeybindAction::ExportPdf => "ExportPdf",
+            KeybindAction::ShowSettings => "ShowSettings",
+            KeybindAction::CommandPalette => "CommandPalette",
+            KeybindAction::QuitApplication => "QuitApplication",
+        }
+    }
+
+    ///
+    /// The i18n key of the user-displayable action name.
+    ///
+    pub fn i18n_key(&self) -> &'static str {
+        match self {
+            KeybindAction::CutText => "action_cut_text",
+            KeybindAction::CopyText => "action_copy_text",
+            KeybindAction::PasteText => "action_paste_text",
+            KeybindAction::Undo => "action_undo",
+            KeybindAction::Redo => "action_redo",
+            KeybindAction::SelectAll => "action_select_all",
+            KeybindAction::NewFile => "action_new_file",
+            KeybindAction::OpenFile => "action_open_file",
+            KeybindAction::ReopenClosedFile => "action_reopen_closed_file",
+            KeybindAction::OpenFolder => "action_open_folder",
+            KeybindAction::CloseFolder => "action_close_folder",
+            KeybindAction::QuickOpen => "action_quick_open",
+            KeybindAction::SaveFile => "action_save_file",
+            KeybindAction::CloseFile => "action_close_file",
+            KeybindAction::NextTab => "action_next_tab",
+            KeybindAction::PreviousTab => "action_previous_tab",
+            KeybindAction::Find => "action_find",
+            KeybindAction::FindNext => "action_find_next",
+            KeybindAction::FindPrevious => "action_find_previous",
+            KeybindAction::Replace => "action_replace",
+            KeybindAction::SearchInFolder => "action_search_in_folder",
+            KeybindAction::ToggleMarkdownPreview => "action_toggle_markdown_preview",
+            KeybindAction::ExportHtml => "action_export_html",
+            KeybindAction::ExportPdf => "action_export_pdf",
+            KeybindAction::ShowSettings => "action_show_settings",
+            KeybindAction::CommandPalette => "action_command_palette",
+            KeybindAction::QuitApplication => "action_quit_application",
+        }
+    }
+
+    ///
+    /// Look up an action by the name returned from `name()`.
+    ///
+    pub fn from_name(name: &str) -> Option<KeybindAction> {
+        KeybindAction::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+}
@@ -1,25 +1,65 @@
 //!
-//! Smartcard-Iced-App
+//! Notespace-Editor
 //!
 //! Keybind Manager. Map `KeybindAction` to `(Modifiers, Code)` combinations.
 //!
 use crate::keyboard::keybind_action::KeybindAction;
+use crate::keyboard::keymap_file;
 use iced::keyboard::Modifiers;
 use iced::keyboard::key::Code;
 use iced::keyboard::key::Key;
 use iced::keyboard::key::Named;
 use lazy_static::lazy_static;
 use libutil::dbg_println;
+use rust_i18n::t;
 use std::collections::HashMap;
+use std::fmt;
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct KeybindManager {
     pub bindings: HashMap<(iced::keyboard::Modifiers, iced::keyboard::key::Code), KeybindAction>,
+    pub problems: Vec<KeymapProblem>,
+    ///
+    /// The keybinds as they were when the keybindings file was last read or
+    /// written, used to find the actions that `save` has to write.
+    ///
+    saved_bindings: HashMap<(Modifiers, Code), KeybindAction>,
 }
 
 impl Default for KeybindManager {
     fn default() -> Self {
         let mut bindings = HashMap::new();
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyX),
+            KeybindAction::CutText,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyC),
+            KeybindAction::CopyText,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyV),
+            KeybindAction::PasteText,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyZ),
+            KeybindAction::Undo,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyY),
+            KeybindAction::Redo,
+        );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::KeyZ,
+            ),
+            KeybindAction::Redo,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyA),
+            KeybindAction::SelectAll,
+        );
         bindings.insert(
             (Modifiers::CTRL, iced::keyboard::key::Code::KeyQ),
             KeybindAction::QuitApplication,
@@ -36,14 +76,96 @@ impl Default for KeybindManager {
             (Modifiers::CTRL, iced::keyboard::key::Code::KeyO),
             KeybindAction::OpenFile,
         );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::KeyT,
+            ),
+            KeybindAction::ReopenClosedFile,
+        );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::KeyO,
+            ),
+            KeybindAction::OpenFolder,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyP),
+            KeybindAction::QuickOpen,
+        );
         bindings.insert(
             (Modifiers::CTRL, iced::keyboard::key::Code::KeyS),
             KeybindAction::SaveFile,
         );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyW),
+            KeybindAction::CloseFile,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::Tab),
+            KeybindAction::NextTab,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::PageDown),
+            KeybindAction::NextTab,
+        );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::Tab,
+            ),
+            KeybindAction::PreviousTab,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::PageUp),
+            KeybindAction::PreviousTab,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyF),
+            KeybindAction::Find,
+        );
+        bindings.insert(
+            (Modifiers::empty(), iced::keyboard::key::Code::F3),
+            KeybindAction::FindNext,
+        );
+        bindings.insert(
+            (Modifiers::SHIFT, iced::keyboard::key::Code::F3),
+            KeybindAction::FindPrevious,
+        );
+        bindings.insert(
+            (Modifiers::CTRL, iced::keyboard::key::Code::KeyH),
+            KeybindAction::Replace,
+        );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::KeyF,
+            ),
+            KeybindAction::SearchInFolder,
+        );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::KeyV,
+            ),
+            KeybindAction::ToggleMarkdownPreview,
+        );
+        bindings.insert(
+            (
+                Modifiers::CTRL | Modifiers::SHIFT,
+                iced::keyboard::key::Code::KeyP,
+            ),
+            KeybindAction::CommandPalette,
+        );
         //
         // TODO: remaining keybinds
         //
-        Self { bindings }
+        Self {
+            saved_bindings: bindings.clone(),
+            bindings,
+            problems: Vec::new(),
+        }
     }
 }
 
@@ -71,13 +193,31 @@ impl KeybindManager {
     /// no associated keybinds are found.
     ///
     pub fn get_key_for_action(&self, keybind_action: KeybindAction) -> Option<(Modifiers, Code)> {
-        self.bindings.iter().find_map(|(key, val)| {
-            if val == &keybind_action {
-                Some(*key)
-            } else {
-                None
-            }
-        })
+        self.get_keys_for_action(keybind_action).into_iter().next()
+    }
+
+    ///
+    /// Get every keybind associated with the specified `keybind_action`, sorted by
+    /// display text so the order does not depend on the `HashMap`.
+    ///
+    pub fn get_keys_for_action(&self, keybind_action: KeybindAction) -> Vec<(Modifiers, Code)> {
+        Self::keys_for_action(&self.bindings, keybind_action)
+    }
+
+    fn keys_for_action(
+        bindings: &HashMap<(Modifiers, Code), KeybindAction>,
+        keybind_action: KeybindAction,
+    ) -> Vec<(Modifiers, Code)> {
+        let mut keys: Vec<(Modifiers, Code)> = bindings
+            .iter()
+            .filter(|(_, action)| **action == keybind_action)
+            .map(|(key, _)| *key)
+            .collect();
+        keys.sort_by_cached_key(|(mods, code)| {
+            let text = KeybindManager::get_display_text_for_keybind(*mods, *code);
+            (text.len(), text)
+        });
+        keys
     }
 
     ///
@@ -93,9 +233,10 @@ impl KeybindManager {
     }
 
     ///
-    /// Internal function called by `get_display_text_for_action`.
+    /// Get a user-displayable (but not localized) String representation of a keybind,
+    /// e.g. `Ctrl+Shift+S`. `parse_keybind` accepts the same format.
     ///
-    fn get_display_text_for_keybind(mods: Modifiers, code: Code) -> String {
+    pub fn get_display_text_for_keybind(mods: Modifiers, code: Code) -> String {
         let mut parts = Vec::new();
 
         if mods.contains(Modifiers::CTRL) {
@@ -111,63 +252,63 @@ impl KeybindManager {
             parts.push("Super");
         }
 
-        // Convert Code to a string
-        let code_str = match code {
-            Code::KeyA => "A",
-            Code::KeyB => "B",
-            Code::KeyC => "C",
-            Code::KeyD => "D",
-            Code::KeyE => "E",
-            Code::KeyF => "F",
-            Code::KeyG => "G",
-            Code::KeyH => "H",
-            Code::KeyI => "I",
-            Code::KeyJ => "J",
-            Code::KeyK => "K",
-            Code::KeyL => "L",
-            Code::KeyM => "M",
-            Code::KeyN => "N",
-            Code::KeyO => "O",
-            Code::KeyP => "P",
-            Code::KeyQ => "Q",
-            Code::KeyR => "R",
-            Code::KeyS => "S",
-            Code::KeyT => "T",
-            Code::KeyU => "U",
-            Code::KeyV => "V",
-            Code::KeyW => "W",
-            Code::KeyX => "X",
-            Code::KeyY => "Y",
-            Code::KeyZ => "Z",
-            Code::F1 => "F1",
-            Code::F2 => "F2",
-            Code::F3 => "F3",
-            Code::F4 => "F4",
-            Code::F5 => "F5",
-            Code::F6 => "F6",
-            Code::F7 => "F7",
-            Code::F8 => "F8",
-            Code::F9 => "F9",
-            Code::F10 => "F10",
-            Code::F11 => "F11",
-            Code::F12 => "F12",
-            Code::Enter => "Enter",
-            Code::Space => "Space",
-            Code::Escape => "Esc",
-            Code::Tab => "Tab",
-            Code::Backspace => "Backspace",
-            Code::Delete => "Del",
-            Code::ArrowUp => "↑",
-            Code::ArrowDown => "↓",
-            Code::ArrowLeft => "←",
-            Code::ArrowRight => "→",
-            other => return format!("{:?}", other), // Fallback for unsupported keys
+        // Convert Code to a string, falling back to the `Debug` name for unsupported keys
+        let code_str = match KEYBIND_CODE_NAMES.iter().find(|(c, _)| *c == code) {
+            Some((_, name)) => name.to_string(),
+            None => format!("{:?}", code),
         };
 
-        parts.push(code_str);
+        parts.push(&code_str);
         parts.join("+")
     }
 
+    ///
+    /// Parse a keybind in the format produced by `get_display_text_for_keybind`,
+    /// e.g. `Ctrl+Shift+S`. Modifier and key names are not case sensitive, and
+    /// common aliases such as `Control`, `Cmd`, `Escape` or `PageUp` are accepted.
+    ///
+    /// Returns a description of the problem if the keybind cannot be parsed.
+    ///
+    pub fn parse_keybind(keybind: &str) -> Result<(Modifiers, Code), String> {
+        let tokens: Vec<&str> = keybind.split('+').map(str::trim).collect();
+        let Some((key_name, modifier_names)) = tokens.split_last() else {
+            return Err(keybind.to_string());
+        };
+
+        let mut mods = Modifiers::empty();
+        for name in modifier_names {
+            let modifier = match name.to_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CTRL,
+                "shift" => Modifiers::SHIFT,
+                "alt" | "option" => Modifiers::ALT,
+                "super" | "logo" | "meta" | "cmd" | "command" | "win" => Modifiers::LOGO,
+                _ => return Err(format!("{} ({})", keybind, name)),
+            };
+            if mods.contains(modifier) {
+                return Err(format!("{} ({})", keybind, name));
+            }
+            mods |= modifier;
+        }
+
+        if key_name.is_empty() {
+            return Err(keybind.to_string());
+        }
+        let code = KEYBIND_CODE_NAMES
+            .iter()
+            .find(|(code, name)| {
+                name.eq_ignore_ascii_case(key_name)
+                    || format!("{:?}", code).eq_ignore_ascii_case(key_name)
+            })
+            .or_else(|| {
+                KEYBIND_CODE_ALIASES
+                    .iter()
+                    .find(|(_, name)| name.eq_ignore_ascii_case(key_name))
+            })
+            .map(|(code, _)| *code)
+            .ok_or_else(|| format!("{} ({})", keybind, key_name))?;
+        Ok((mods, code))
+    }
+
     ///
     /// Map `iced::keyboard::key::Code` to `iced::keyboard::key::Key`.
     ///
@@ -289,11 +430,357 @@ impl KeybindManager {
         }
     }
 
-    //
-    // TODO: load and save the key bindings...
-    //
+    ///
+    /// Load the default keybinds and merge the user keybinds from the keybindings
+    /// file over them. Problems found in the file are kept in `problems` so they
+    /// can be shown to the user.
+    ///
+    pub fn load() -> Self {
+        let mut keybind_manager = Self::default();
+        match keymap_file::load() {
+            Ok(Some(table)) => keybind_manager.merge(&table),
+            Ok(None) => {}
+            Err(problem) => keybind_manager.problems.push(problem),
+        }
+        keybind_manager.saved_bindings = keybind_manager.bindings.clone();
+        keybind_manager
+    }
+
+    ///
+    /// Write the actions whose keybinds changed since the keybindings file was
+    /// last read or written. Actions back at their defaults are removed from the
+    /// file, every other entry is kept as it is, including unknown actions and
+    /// keybinds that could not be parsed.
+    ///
+    /// A file that cannot be read is not overwritten, the problem is reported
+    /// instead.
+    ///
+    pub fn save(&mut self) {
+        let changed: Vec<KeybindAction> = KeybindAction::ALL
+            .into_iter()
+            .filter(|action| {
+                self.get_keys_for_action(*action)
+                    != Self::keys_for_action(&self.saved_bindings, *action)
+            })
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        let mut table = match keymap_file::load() {
+            Ok(table) => table.unwrap_or_default(),
+            Err(problem) => {
+                self.problems.retain(|known| *known != problem);
+                self.problems.push(problem);
+                return;
+            }
+        };
+        let defaults = Self::default();
+        for action in &changed {
+            let keys = self.get_keys_for_action(*action);
+            if keys == defaults.get_keys_for_action(*action) {
+                table.remove(action.name());
+            } else {
+                let keybinds = keys
+                    .iter()
+                    .map(|(mods, code)| {
+                        toml::Value::String(KeybindManager::get_display_text_for_keybind(
+                            *mods, *code,
+                        ))
+                    })
+                    .collect();
+                table.insert(action.name().to_string(), toml::Value::Array(keybinds));
+            }
+        }
+        if let Err(problem) = keymap_file::save(&table) {
+            self.problems.retain(|known| *known != problem);
+            self.problems.push(problem);
+            return;
+        }
+        self.saved_bindings = self.bindings.clone();
+        //
+        // The entries of the changed actions were rewritten, so their problems
+        // no longer apply.
+        //
+        self.problems.retain(|problem| match problem {
+            KeymapProblem::FileError { .. } => false,
+            KeymapProblem::UnknownAction { .. } => true,
+            KeymapProblem::InvalidKeybind { action, .. } => !changed.contains(action),
+            KeymapProblem::Conflict { first, second, .. } => {
+                !changed.contains(first) && !changed.contains(second)
+            }
+        });
+    }
+
+    ///
+    /// Get the keybind for a key press, if the key can be bound.
+    ///
+    pub fn get_keybind_for_key(key: &Key, modifiers: Modifiers) -> Option<(Modifiers, Code)> {
+        KEY_MAP.get(key).map(|code| (modifiers, *code))
+    }
+
+    ///
+    /// Replace the keybinds of `keybind_action` with `keybind`.
+    ///
+    /// Returns the action that `keybind` was taken from, if it was bound to a
+    /// different action.
+    ///
+    pub fn set_keybind(
+        &mut self,
+        keybind_action: KeybindAction,
+        keybind: (Modifiers, Code),
+    ) -> Option<KeybindAction> {
+        self.bindings.retain(|_, action| *action != keybind_action);
+        self.bindings
+            .insert(keybind, keybind_action)
+            .filter(|previous| *previous != keybind_action)
+    }
+
+    ///
+    /// Restore the default keybinds of `keybind_action`.
+    ///
+    /// Returns the keybinds that were taken from other actions.
+    ///
+    pub fn reset_keybind(
+        &mut self,
+        keybind_action: KeybindAction,
+    ) -> Vec<((Modifiers, Code), KeybindAction)> {
+        self.bindings.retain(|_, action| *action != keybind_action);
+        Self::default()
+            .get_keys_for_action(keybind_action)
+            .into_iter()
+            .filter_map(|key| {
+                self.bindings
+                    .insert(key, keybind_action)
+                    .map(|previous| (key, previous))
+            })
+            .collect()
+    }
+
+    ///
+    /// Restore all default keybinds.
+    ///
+    pub fn reset_all_keybinds(&mut self) {
+        self.bindings = Self::default().bindings;
+    }
+
+    ///
+    /// Returns `true` if `keybind_action` has its default keybinds.
+    ///
+    pub fn is_default(&self, keybind_action: KeybindAction) -> bool {
+        self.get_keys_for_action(keybind_action)
+            == Self::default().get_keys_for_action(keybind_action)
+    }
+
+    ///
+    /// Merge user keybinds over the current keybinds.
+    ///
+    /// Each entry maps an action name to a keybind, or to an array of keybinds. An
+    /// entry replaces all keybinds of that action, and an empty array unbinds the
+    /// action. When a keybind is already used by another action the user keybind
+    /// wins and the conflict is reported.
+    ///
+    fn merge(&mut self, table: &toml::Table) {
+        let mut overrides = Vec::new();
+        for (name, value) in table {
+            let Some(action) = KeybindAction::from_name(name) else {
+                self.problems
+                    .push(KeymapProblem::UnknownAction { name: name.clone() });
+                continue;
+            };
+            let keybinds: Vec<&toml::Value> = match value {
+                toml::Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            let mut parsed = Vec::new();
+            for keybind in keybinds {
+                match keybind
+                    .as_str()
+                    .ok_or_else(|| keybind.to_string())
+                    .and_then(KeybindManager::parse_keybind)
+                {
+                    Ok(key) => parsed.push(key),
+                    Err(keybind) => self
+                        .problems
+                        .push(KeymapProblem::InvalidKeybind { action, keybind }),
+                }
+            }
+            overrides.push((action, parsed));
+        }
+
+        for (action, _) in &overrides {
+            self.bindings.retain(|_, bound| bound != action);
+        }
+        for (action, keys) in overrides {
+            for key in keys {
+                if let Some(existing) = self.bindings.insert(key, action)
+                    && existing != action
+                {
+                    self.problems.push(KeymapProblem::Conflict {
+                        keybind: KeybindManager::get_display_text_for_keybind(key.0, key.1),
+                        first: existing,
+                        second: action,
+                    });
+                }
+            }
+        }
+    }
+}
+
+///
+/// A problem found while loading the keybindings file.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeymapProblem {
+    FileError {
+        message: String,
+    },
+    UnknownAction {
+        name: String,
+    },
+    InvalidKeybind {
+        action: KeybindAction,
+        keybind: String,
+    },
+    Conflict {
+        keybind: String,
+        first: KeybindAction,
+        second: KeybindAction,
+    },
+}
+
+impl fmt::Display for KeymapProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapProblem::FileError { message } => {
+                write!(f, "{}", t!("keymap_file_error", message = message))
+            }
+            KeymapProblem::UnknownAction { name } => {
+                write!(f, "{}", t!("keymap_unknown_action", action = name))
+            }
+            KeymapProblem::InvalidKeybind { action, keybind } => write!(
+                f,
+                "{}",
+                t!(
+                    "keymap_invalid_keybind",
+                    action = action.name(),
+                    keybind = keybind
+                )
+            ),
+            KeymapProblem::Conflict {
+                keybind,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}",
+                t!(
+                    "keymap_conflict",
+                    keybind = keybind,
+                    first = first.name(),
+                    second = second.name()
+                )
+            ),
+        }
+    }
 }
 
+///
+/// Display names of the supported keys, used by `get_display_text_for_keybind`
+/// and `parse_keybind`.
+///
+const KEYBIND_CODE_NAMES: &[(Code, &str)] = &[
+    (Code::KeyA, "A"),
+    (Code::KeyB, "B"),
+    (Code::KeyC, "C"),
+    (Code::KeyD, "D"),
+    (Code::KeyE, "E"),
+    (Code::KeyF, "F"),
+    (Code::KeyG, "G"),
+    (Code::KeyH, "H"),
+    (Code::KeyI, "I"),
+    (Code::KeyJ, "J"),
+    (Code::KeyK, "K"),
+    (Code::KeyL, "L"),
+    (Code::KeyM, "M"),
+    (Code::KeyN, "N"),
+    (Code::KeyO, "O"),
+    (Code::KeyP, "P"),
+    (Code::KeyQ, "Q"),
+    (Code::KeyR, "R"),
+    (Code::KeyS, "S"),
+    (Code::KeyT, "T"),
+    (Code::KeyU, "U"),
+    (Code::KeyV, "V"),
+    (Code::KeyW, "W"),
+    (Code::KeyX, "X"),
+    (Code::KeyY, "Y"),
+    (Code::KeyZ, "Z"),
+    (Code::Digit0, "0"),
+    (Code::Digit1, "1"),
+    (Code::Digit2, "2"),
+    (Code::Digit3, "3"),
+    (Code::Digit4, "4"),
+    (Code::Digit5, "5"),
+    (Code::Digit6, "6"),
+    (Code::Digit7, "7"),
+    (Code::Digit8, "8"),
+    (Code::Digit9, "9"),
+    (Code::Minus, "-"),
+    (Code::Equal, "="),
+    (Code::BracketLeft, "["),
+    (Code::BracketRight, "]"),
+    (Code::Backslash, "\\"),
+    (Code::Semicolon, ";"),
+    (Code::Quote, "'"),
+    (Code::Comma, ","),
+    (Code::Period, "."),
+    (Code::Slash, "/"),
+    (Code::Backquote, "`"),
+    (Code::F1, "F1"),
+    (Code::F2, "F2"),
+    (Code::F3, "F3"),
+    (Code::F4, "F4"),
+    (Code::F5, "F5"),
+    (Code::F6, "F6"),
+    (Code::F7, "F7"),
+    (Code::F8, "F8"),
+    (Code::F9, "F9"),
+    (Code::F10, "F10"),
+    (Code::F11, "F11"),
+    (Code::F12, "F12"),
+    (Code::Enter, "Enter"),
+    (Code::Space, "Space"),
+    (Code::Escape, "Esc"),
+    (Code::Tab, "Tab"),
+    (Code::Backspace, "Backspace"),
+    (Code::Delete, "Del"),
+    (Code::Insert, "Ins"),
+    (Code::Home, "Home"),
+    (Code::End, "End"),
+    (Code::PageUp, "PgUp"),
+    (Code::PageDown, "PgDn"),
+    (Code::ArrowUp, "↑"),
+    (Code::ArrowDown, "↓"),
+    (Code::ArrowLeft, "←"),
+    (Code::ArrowRight, "→"),
+];
+
+///
+/// Additional key names accepted by `parse_keybind`.
+///
+const KEYBIND_CODE_ALIASES: &[(Code, &str)] = &[
+    (Code::Escape, "Escape"),
+    (Code::Delete, "Delete"),
+    (Code::Insert, "Insert"),
+    (Code::PageUp, "PageUp"),
+    (Code::PageDown, "PageDown"),
+    (Code::ArrowUp, "Up"),
+    (Code::ArrowDown, "Down"),
+    (Code::ArrowLeft, "Left"),
+    (Code::ArrowRight, "Right"),
+    (Code::Enter, "Return"),
+];
+
 lazy_static! {
     ///
     /// Static singleton pattern.
@@ -326,6 +813,55 @@ lazy_static! {
         m.insert(Key::Character("x".into()), Code::KeyX);
         m.insert(Key::Character("y".into()), Code::KeyY);
         m.insert(Key::Character("z".into()), Code::KeyZ);
+        m.insert(Key::Character("0".into()), Code::Digit0);
+        m.insert(Key::Character("1".into()), Code::Digit1);
+        m.insert(Key::Character("2".into()), Code::Digit2);
+        m.insert(Key::Character("3".into()), Code::Digit3);
+        m.insert(Key::Character("4".into()), Code::Digit4);
+        m.insert(Key::Character("5".into()), Code::Digit5);
+        m.insert(Key::Character("6".into()), Code::Digit6);
+        m.insert(Key::Character("7".into()), Code::Digit7);
+        m.insert(Key::Character("8".into()), Code::Digit8);
+        m.insert(Key::Character("9".into()), Code::Digit9);
+        m.insert(Key::Character("-".into()), Code::Minus);
+        m.insert(Key::Character("=".into()), Code::Equal);
+        m.insert(Key::Character("[".into()), Code::BracketLeft);
+        m.insert(Key::Character("]".into()), Code::BracketRight);
+        m.insert(Key::Character("\\".into()), Code::Backslash);
+        m.insert(Key::Character(";".into()), Code::Semicolon);
+        m.insert(Key::Character("'".into()), Code::Quote);
+        m.insert(Key::Character(",".into()), Code::Comma);
+        m.insert(Key::Character(".".into()), Code::Period);
+        m.insert(Key::Character("/".into()), Code::Slash);
+        m.insert(Key::Character("`".into()), Code::Backquote);
+        m.insert(Key::Character(" ".into()), Code::Space);
+        m.insert(Key::Named(Named::Space), Code::Space);
+        m.insert(Key::Named(Named::Enter), Code::Enter);
+        m.insert(Key::Named(Named::Escape), Code::Escape);
+        m.insert(Key::Named(Named::Backspace), Code::Backspace);
+        m.insert(Key::Named(Named::Delete), Code::Delete);
+        m.insert(Key::Named(Named::Insert), Code::Insert);
+        m.insert(Key::Named(Named::Home), Code::Home);
+        m.insert(Key::Named(Named::End), Code::End);
+        m.insert(Key::Named(Named::ArrowUp), Code::ArrowUp);
+        m.insert(Key::Named(Named::ArrowDown), Code::ArrowDown);
+        m.insert(Key::Named(Named::ArrowLeft), Code::ArrowLeft);
+        m.insert(Key::Named(Named::ArrowRight), Code::ArrowRight);
+        m.insert(Key::Named(Named::Tab), Code::Tab);
+        m.insert(Key::Named(Named::PageUp), Code::PageUp);
+        m.insert(Key::Named(Named::PageDown), Code::PageDown);
+        m.insert(Key::Named(Named::F1), Code::F1);
+        m.insert(Key::Named(Named::F2), Code::F2);
+        m.insert(Key::Named(Named::F3), Code::F3);
+        m.insert(Key::Named(Named::F4), Code::F4);
+        m.insert(Key::Named(Named::F5), Code::F5);
+        m.insert(Key::Named(Named::F6), Code::F6);
+        m.insert(Key::Named(Named::F7), Code::F7);
+        m.insert(Key::Named(Named::F8), Code::F8);
+        m.insert(Key::Named(Named::F9), Code::F9);
+        m.insert(Key::Named(Named::F10), Code::F10);
+        m.insert(Key::Named(Named::F11), Code::F11);
+        m.insert(Key::Named(Named::F12), Code::F12);
         m
     };
 
@@ -335,3 +871,106 @@ lazy_static! {
 // Example declaration:
 //
 //static KEY_A: Key = Key::Character(SmolStr::new_inline("a"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> toml::Table {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_keybind_accepts_aliases_and_any_case() {
+        assert_eq!(
+            KeybindManager::parse_keybind("Ctrl+Shift+S"),
+            Ok((Modifiers::CTRL | Modifiers::SHIFT, Code::KeyS))
+        );
+        assert_eq!(
+            KeybindManager::parse_keybind("control + s"),
+            Ok((Modifiers::CTRL, Code::KeyS))
+        );
+        assert_eq!(
+            KeybindManager::parse_keybind("Cmd+KeyS"),
+            Ok((Modifiers::LOGO, Code::KeyS))
+        );
+    }
+
+    #[test]
+    fn parse_keybind_rejects_unknown_names() {
+        assert!(KeybindManager::parse_keybind("Hyper+S").is_err());
+        assert!(KeybindManager::parse_keybind("Ctrl+Ctrl+S").is_err());
+        assert!(KeybindManager::parse_keybind("Ctrl+").is_err());
+        assert!(KeybindManager::parse_keybind("Ctrl+NoSuchKey").is_err());
+    }
+
+    #[test]
+    fn display_text_parses_back() {
+        for (code, _) in KEYBIND_CODE_NAMES.iter() {
+            let mods = Modifiers::CTRL | Modifiers::ALT;
+            let text = KeybindManager::get_display_text_for_keybind(mods, *code);
+            assert_eq!(KeybindManager::parse_keybind(&text), Ok((mods, *code)));
+        }
+    }
+
+    #[test]
+    fn merge_replaces_the_keybinds_of_an_action() {
+        let mut keybind_manager = KeybindManager::default();
+        keybind_manager.merge(&table("SaveFile = \"Ctrl+Shift+S\"\nQuitApplication = []"));
+        assert_eq!(
+            keybind_manager.get_keys_for_action(KeybindAction::SaveFile),
+            [(Modifiers::CTRL | Modifiers::SHIFT, Code::KeyS)]
+        );
+        assert!(
+            keybind_manager
+                .get_keys_for_action(KeybindAction::QuitApplication)
+                .is_empty()
+        );
+        assert!(keybind_manager.problems.is_empty());
+    }
+
+    #[test]
+    fn merge_reports_problems() {
+        let mut keybind_manager = KeybindManager::default();
+        keybind_manager.merge(&table(
+            "NoSuchAction = \"Ctrl+K\"\nSaveFile = [\"Ctrl+Bogus\", \"Ctrl+X\"]",
+        ));
+        assert!(
+            keybind_manager
+                .problems
+                .contains(&KeymapProblem::UnknownAction {
+                    name: "NoSuchAction".to_string()
+                })
+        );
+        assert!(keybind_manager.problems.iter().any(|problem| matches!(
+            problem,
+            KeymapProblem::InvalidKeybind {
+                action: KeybindAction::SaveFile,
+                ..
+            }
+        )));
+        //
+        // The user keybind wins over the default of another action.
+        //
+        assert!(keybind_manager.problems.contains(&KeymapProblem::Conflict {
+            keybind: "Ctrl+X".to_string(),
+            first: KeybindAction::CutText,
+            second: KeybindAction::SaveFile,
+        }));
+        assert_eq!(
+            keybind_manager.get_keys_for_action(KeybindAction::SaveFile),
+            [(Modifiers::CTRL, Code::KeyX)]
+        );
+    }
+
+    #[test]
+    fn reset_keybind_restores_the_default() {
+        let mut keybind_manager = KeybindManager::default();
+        let taken =
+            keybind_manager.set_keybind(KeybindAction::SaveFile, (Modifiers::CTRL, Code::KeyX));
+        assert_eq!(taken, Some(KeybindAction::CutText));
+        assert!(!keybind_manager.is_default(KeybindAction::SaveFile));
+        keybind_manager.reset_keybind(KeybindAction::SaveFile);
+        assert!(keybind_manager.is_default(KeybindAction::SaveFile));
+    }
+}
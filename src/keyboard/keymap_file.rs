@@ -0,0 +1,67 @@
+//!
+//! Notespace-Editor
+//!
+//! User keybindings file.
+//!
+//! The file is stored next to the settings file and maps action names to keybinds:
+//!
+//! ```toml
+//! SaveFile = "Ctrl+Shift+S"
+//! NextTab = ["Ctrl+Tab", "Ctrl+PgDn"]
+//! QuitApplication = []
+//! ```
+//!
+
+use crate::app_configuration::AppConfiguration;
+use crate::keyboard::keybind_manager::KeymapProblem;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+const KEYBINDINGS_FILE: &str = "keybindings.toml";
+
+///
+/// Returns the keybindings file path, in the same directory as the settings file.
+///
+pub fn path() -> PathBuf {
+    AppConfiguration::path().with_file_name(KEYBINDINGS_FILE)
+}
+
+///
+/// Attempts to load the keybindings file.
+///
+/// Returns `Ok(None)` if there is no keybindings file.
+///
+pub fn load() -> Result<Option<toml::Table>, KeymapProblem> {
+    let content = match fs::read_to_string(path()) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(KeymapProblem::FileError {
+                message: error.to_string(),
+            });
+        }
+    };
+    content
+        .parse::<toml::Table>()
+        .map(Some)
+        .map_err(|error| KeymapProblem::FileError {
+            message: error.message().to_string(),
+        })
+}
+
+///
+/// Write `table` to the keybindings file.
+///
+pub fn save(table: &toml::Table) -> Result<(), KeymapProblem> {
+    let path = path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let content = toml::to_string_pretty(table).map_err(|error| KeymapProblem::FileError {
+        message: error.to_string(),
+    })?;
+    fs::write(path, content).map_err(|error| KeymapProblem::FileError {
+        message: error.to_string(),
+    })
+}
@@ -3,3 +3,4 @@
 //!
 pub(crate) mod keybind_action;
 pub(crate) mod keybind_manager;
+pub(crate) mod keymap_file;
@@ -4,18 +4,27 @@
 //! The `main()` function.
 //!
 
+mod app_args;
 mod app_configuration;
 mod app_const;
+mod app_file_watcher;
 mod app_io;
 mod app_message;
+mod app_session;
 mod app_state;
 mod controls;
+mod document;
+mod find;
 mod keyboard;
 mod main_window;
+mod markdown;
+mod palette;
 mod ui_const;
 mod ui_style;
 mod ui_util;
+mod workspace;
 
+use crate::app_args::AppArguments;
 use crate::app_configuration::AppConfiguration;
 use fa_iced::load_font_fontawesome_ttf;
 use main_window::AppMain;
@@ -28,6 +37,27 @@ extern crate rust_i18n;
 rust_i18n::i18n!("locales", fallback = "en");
 
 fn main() -> iced::Result {
+    //
+    // Parse the command line before anything else, so `--help` and invalid
+    // arguments do not open a window.
+    //
+    let app_arguments = match AppArguments::parse(std::env::args().skip(1)) {
+        Ok(app_arguments) => app_arguments,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, AppArguments::usage());
+            std::process::exit(2);
+        }
+    };
+    if app_arguments.help {
+        println!("{}", AppArguments::usage());
+        return Ok(());
+    }
+    //
+    // Standard input is read before the window is opened, the editor cannot be
+    // used while the text is still being piped in.
+    //
+    let stdin_text = app_arguments.read_stdin.then(app_args::read_stdin);
+
     //
     // Load the Font Awesome fonts.
     //
@@ -42,6 +72,10 @@ fn main() -> iced::Result {
     let mut window_settings = iced::window::Settings::default();
     window_settings.size = app_configuration.get_window_size();
     window_settings.position = app_configuration.get_window_position();
+    //
+    // Closing the window is handled by `AppMain`, so unsaved changes can be saved first.
+    //
+    window_settings.exit_on_close_request = false;
 
     //
     // Initialize and run the app.
@@ -51,5 +85,5 @@ fn main() -> iced::Result {
         .window(window_settings)
         .scale_factor(AppMain::scale_factor)
         .theme(AppMain::theme)
-        .run()
+        .run_with(move || AppMain::new(app_arguments, stdin_text))
 }
@@ -4,19 +4,95 @@
 //! The Iced `Application` implementation.
 //!
 
-use super::app_configuration::AppConfiguration;
-use super::app_io::{async_open_file_from_dialog, async_save_file_to_path};
+use super::app_args::AppArguments;
+use super::app_configuration::{AppConfiguration, AutosaveMode};
+use super::app_const::{
+    AUTOSAVE_TICK, AUTOSAVED_NOTICE_DURATION, MAX_CLOSED_FILES, RECOVERY_INTERVAL,
+};
+use super::app_file_watcher::{watch_files, watch_folder};
+use super::app_io::{AppIOError, LoadedFile, SavedFile};
+use super::app_io::{
+    async_create_file, async_create_folder, async_delete_path, async_open_file_from_dialog,
+    async_open_file_from_path, async_open_file_with_encoding, async_open_folder_from_dialog,
+    async_rename_path, async_save_file_to_path,
+};
 use super::app_message::AppMessage;
+use super::app_session::{AppSession, SessionDocument};
 use super::app_state::AppState;
+use crate::controls::command_palette::AppCommandPalette;
+use crate::controls::diff_view::DiffView;
+use crate::controls::file_changed_dialog::FileChangedPrompt;
+use crate::controls::findbar::AppFindbar;
+use crate::controls::keybinding_editor::AppKeybindingEditor;
+use crate::controls::markdown_preview::AppMarkdownPreview;
+use crate::controls::quick_open::AppQuickOpen;
+use crate::controls::recovery_dialog::RecoveryPrompt;
+use crate::controls::search_panel::AppSearchPanel;
+use crate::controls::sidebar::AppSidebar;
 use crate::controls::statusbar::AppStatusbar;
+use crate::controls::tabbar::AppTabbar;
 use crate::controls::toolbar::AppToolbar;
+use crate::controls::unsaved_changes_dialog::{CloseIntent, UnsavedChangesPrompt};
+use crate::document::edit_history::EditStateId;
+use crate::document::line_ending::LineEnding;
+use crate::document::recovery_journal::RecoveryJournal;
+use crate::document::text_cursor::{move_cursor_to, position_from_line_column, select_range};
+use crate::document::text_document::DocumentId;
+use crate::document::text_encoding::TextEncoding;
+use crate::find::find_highlighter::{FindHighlighter, FindHighlighterSettings};
 use crate::keyboard::keybind_action::KeybindAction;
-use iced::widget::{center, horizontal_space, mouse_area, opaque, stack};
+use crate::keyboard::keybind_manager::KeybindManager;
+use crate::markdown::html_export::{HtmlExportOptions, async_export_html};
+use crate::markdown::pdf_export::{PdfExportOptions, async_export_pdf};
+use crate::ui_const::{
+    UI_DROP_OVERLAY_TEXT_SIZE, UI_EDITOR_LINE_HEIGHT, UI_EDITOR_SCROLLABLE_ID,
+    UI_FILE_TREE_INPUT_ID, UI_FIND_INPUT_ID, UI_FOLDER_SEARCH_INPUT_ID,
+    UI_MARKDOWN_PREVIEW_SCROLLABLE_ID, UI_PALETTE_INPUT_ID, UI_PALETTE_SCROLLABLE_ID,
+    UI_QUICK_OPEN_INPUT_ID, UI_QUICK_OPEN_PREVIEW_LINES, UI_QUICK_OPEN_SCROLLABLE_ID,
+    UI_SIDEBAR_DIVIDER_WIDTH, UI_SIDEBAR_MAX_WIDTH, UI_SIDEBAR_MIN_WIDTH, UR_LEFT_SIDEBAR_PADDING,
+};
+use crate::ui_style::AppStyle;
+use crate::workspace::file_index::{FileIndex, find_changes, list_files};
+use crate::workspace::file_tree::{FileOperation, FileTree, FileTreeEdit, read_directory};
+use crate::workspace::folder_search::{replace_in_files, search_folder, write_replacement};
+use crate::workspace::folder_search_state::ReplacePreview;
+use crate::workspace::quick_open_state::QuickOpenPreview;
+use crate::workspace::search_index::{
+    IndexUpdate, load_search_index, read_changed_files, save_search_index, scan_folder,
+};
+use crate::workspace::search_query::SearchQuery;
+use iced::keyboard::key::Named;
+use iced::widget::scrollable::{AbsoluteOffset, RelativeOffset};
+use iced::widget::text_editor::{Action, Binding, Edit, KeyPress};
+use iced::widget::{
+    center, horizontal_space, mouse_area, opaque, scrollable, stack, text, text_input,
+    vertical_rule,
+};
 use iced::widget::{column, container, row, text_editor};
 use iced::window::Event;
-use iced::{Element, Length, Task, Theme};
+use iced::{Element, Length, Task, Theme, task};
 use iced::{Font, Subscription};
 use rust_i18n::t;
+use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+///
+/// The message that reports the result of saving a document.
+///
+type SavedMessage = fn(DocumentId, EditStateId, Result<SavedFile, AppIOError>) -> AppMessage;
+
+///
+/// A save of a document that is running, and the save that was requested while
+/// it runs. A save without an encoding uses the encoding of the document.
+///
+struct RunningSave {
+    encoding: TextEncoding,
+    line_ending: LineEnding,
+    queued: Option<(SavedMessage, Option<TextEncoding>)>,
+}
 
 ///
 /// The top-level Iced Application component.
@@ -24,9 +100,64 @@ use rust_i18n::t;
 pub struct AppMain {
     app_state: AppState,
     toolbar: AppToolbar,
+    tabbar: AppTabbar,
+    findbar: AppFindbar,
     statusbar: AppStatusbar,
+    command_palette: AppCommandPalette,
+    keybinding_editor: AppKeybindingEditor,
     app_configuration: AppConfiguration,
     show_app_configuration_modal: bool,
+    unsaved_changes_prompt: Option<UnsavedChangesPrompt>,
+    editor_viewport_height: f32,
+    ///
+    /// The documents opened from the command line with `--wait`. The application
+    /// exits when all of them are closed.
+    ///
+    wait_documents: Option<Vec<DocumentId>>,
+    ///
+    /// Files are dragged over the window.
+    ///
+    files_hovered: bool,
+    ///
+    /// The time of the last edit that has not been autosaved yet.
+    ///
+    last_edit_at: Option<Instant>,
+    recovery_journal: RecoveryJournal,
+    ///
+    /// Snapshots of unsaved documents found in the recovery journal on start.
+    ///
+    recovery_prompt: Option<RecoveryPrompt>,
+    ///
+    /// Documents with unsaved changes whose file was changed by another program,
+    /// asked about one at a time.
+    ///
+    file_changed_prompts: Vec<FileChangedPrompt>,
+    ///
+    /// Documents that are being saved. Changes to their files are not reported
+    /// until the save has finished.
+    ///
+    saving_documents: HashMap<DocumentId, RunningSave>,
+    ///
+    /// The files of closed documents, the most recently closed last.
+    ///
+    closed_files: Vec<SessionDocument>,
+    ///
+    /// The divider between the sidebar and the editor is being dragged.
+    ///
+    sidebar_resizing: bool,
+    sidebar: AppSidebar,
+    search_panel: AppSearchPanel,
+    ///
+    /// Aborts the folder search that is running.
+    ///
+    folder_search_handle: Option<task::Handle>,
+    quick_open: AppQuickOpen,
+    markdown_preview: AppMarkdownPreview,
+    ///
+    /// The search index is being saved, and it changed again since the save started.
+    ///
+    search_index_saving: bool,
+    search_index_unsaved: bool,
 }
 
 ///
@@ -40,231 +171,2542 @@ impl Default for AppMain {
         //
         let mut app_configuration = AppConfiguration::load();
         app_state.scale_factor = app_configuration.scale_factor;
+        app_state
+            .document_manager
+            .set_default_line_ending(app_configuration.default_line_ending);
         Self {
             app_state,
             toolbar: AppToolbar::new(),
+            tabbar: AppTabbar::new(),
+            findbar: AppFindbar::new(),
             statusbar: AppStatusbar::new(),
+            command_palette: AppCommandPalette::new(),
+            keybinding_editor: AppKeybindingEditor::new(),
             app_configuration: AppConfiguration::load(),
             show_app_configuration_modal: false,
+            unsaved_changes_prompt: None,
+            editor_viewport_height: 0.0,
+            wait_documents: None,
+            files_hovered: false,
+            last_edit_at: None,
+            recovery_journal: RecoveryJournal::default(),
+            recovery_prompt: None,
+            file_changed_prompts: Vec::new(),
+            saving_documents: HashMap::new(),
+            closed_files: Vec::new(),
+            sidebar_resizing: false,
+            sidebar: AppSidebar::new(),
+            search_panel: AppSearchPanel::new(),
+            folder_search_handle: None,
+            quick_open: AppQuickOpen::new(),
+            markdown_preview: AppMarkdownPreview::new(),
+            search_index_saving: false,
+            search_index_unsaved: false,
+        }
+    }
+}
+
+impl AppMain {
+    // ///
+    // /// Iced Application Traits:
+    // ///
+    // type Executor = iced::executor::Default;
+    // type Message = AppMessage;
+    // type Theme = Theme;
+    // type Flags = ();
+    // //type State = AppState;
+
+    ///
+    /// Constructor. Opens the files from the command line, and `stdin_text` in an
+    /// untitled document.
+    ///
+    pub fn new(
+        app_arguments: AppArguments,
+        stdin_text: Option<String>,
+    ) -> (Self, Task<AppMessage>) {
+        let mut app = AppMain::default();
+        app.app_configuration.prune_recent_files();
+        let restore_session = app_arguments.files.is_empty() && stdin_text.is_none();
+        if app_arguments.wait {
+            app.wait_documents = Some(Vec::new());
+        }
+        //
+        // The text from standard input is the first tab. Files are opened one
+        // after the other, so their tabs are in the same order as the arguments.
+        //
+        if let Some(text) = stdin_text {
+            let document_id = app.app_state.document_manager.open_untitled(&text);
+            app.add_wait_document(document_id);
+        }
+        let mut tasks = Vec::new();
+        for file_argument in app_arguments.files {
+            let position = file_argument.position;
+            if file_argument.path.exists() {
+                tasks.push(Task::perform(
+                    async_open_file_from_path(file_argument.path),
+                    move |result| AppMessage::CommandLineFileOpened(result, position),
+                ));
+            } else {
+                //
+                // A file that does not exist yet is created when it is saved.
+                //
+                tasks.push(Task::done(AppMessage::CommandLineFileOpened(
+                    Ok(LoadedFile {
+                        path: file_argument.path,
+                        text: Arc::default(),
+                        encoding: TextEncoding::default(),
+                        modified: None,
+                    }),
+                    position,
+                )));
+            }
+        }
+        tasks.push(Task::done(AppMessage::CommandLineFilesOpened));
+        let task = tasks
+            .into_iter()
+            .reduce(|first, second| first.chain(second))
+            .unwrap_or_else(Task::none);
+        let task = match restore_session {
+            true => app.restore_session(),
+            false => task,
+        };
+        let find_snapshots = Task::perform(
+            RecoveryJournal::find_snapshots(app.recovery_journal.session_path().to_path_buf()),
+            AppMessage::RecoverySnapshotsFound,
+        );
+        (app, Task::batch([task, find_snapshots]))
+    }
+
+    // pub(crate) fn get_window_settings(&mut self) -> iced::window::Settings {
+    //     self.app_configuration = AppConfiguration::load();
+    //     self.app_state.scale_factor = self.app_configuration.scale_factor;
+    //     let mut window_settings = iced::window::Settings::default();
+    //     //window_settings.size = iced::Size::new(1200.0, 1024.0);
+    //     window_settings.size = self.app_configuration.get_window_size();
+    //     window_settings.position = self.app_configuration.get_window_position();
+    // }
+
+    ///
+    /// Iced function to handle messages.
+    ///
+    pub(crate) fn update(&mut self, message: AppMessage) -> Task<AppMessage> {
+        match message {
+            AppMessage::TextEdited(action) => {
+                self.perform_text_action(action);
+                self.sync_markdown_preview()
+            }
+            AppMessage::OpenFileFromDialog => self.open_file(),
+            AppMessage::FileOpened(Ok(loaded_file)) => {
+                self.app_configuration.add_recent_file(&loaded_file.path);
+                self.app_state.document_manager.open_document(&loaded_file);
+                self.active_document_changed()
+            }
+            AppMessage::FileOpened(Err(error)) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::CommandLineFileOpened(Ok(loaded_file), position) => {
+                self.app_configuration.add_recent_file(&loaded_file.path);
+                let document_id = self.app_state.document_manager.open_document(&loaded_file);
+                self.add_wait_document(document_id);
+                let task = self.active_document_changed();
+                match position {
+                    Some((line, column)) => {
+                        let document = self.app_state.document_manager.active_mut();
+                        let position =
+                            position_from_line_column(&document.file_content, line, column);
+                        move_cursor_to(&mut document.file_content, position);
+                        task.chain(self.scroll_to_line(position.0))
+                    }
+                    None => task,
+                }
+            }
+            AppMessage::CommandLineFileOpened(Err(error), _) => {
+                if self.wait_documents.is_some() {
+                    eprintln!("Could not open file: {:?}", error);
+                }
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::CommandLineFilesOpened => {
+                //
+                // With `--wait`, the caller would wait forever when none of the
+                // documents could be opened.
+                //
+                if self
+                    .wait_documents
+                    .as_ref()
+                    .is_some_and(|wait_documents| wait_documents.is_empty())
+                {
+                    eprintln!("No document was opened, nothing to wait for.");
+                    return self.quit();
+                }
+                Task::none()
+            }
+            AppMessage::SessionFileOpened(Ok(loaded_file), session_document) => {
+                self.app_state.document_manager.open_document(&loaded_file);
+                self.restore_session_document(session_document);
+                Task::none()
+            }
+            AppMessage::SessionFileOpened(Err(error), _) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::OpenRecentFile(file_path) => {
+                self.app_state.error = None;
+                Task::perform(
+                    async_open_file_from_path(file_path.clone()),
+                    move |result| AppMessage::RecentFileOpened(file_path.clone(), result),
+                )
+            }
+            AppMessage::RecentFileOpened(_, Ok(loaded_file)) => {
+                self.update(AppMessage::FileOpened(Ok(loaded_file)))
+            }
+            AppMessage::RecentFileOpened(
+                file_path,
+                Err(AppIOError::IOFailedError(ErrorKind::NotFound)),
+            ) => {
+                self.app_configuration.remove_recent_file(&file_path);
+                self.app_state.error = Some(AppIOError::IOFailedError(ErrorKind::NotFound));
+                Task::none()
+            }
+            AppMessage::RecentFileOpened(_, Err(error)) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::RecentFilesShown => {
+                self.app_configuration.prune_recent_files();
+                Task::none()
+            }
+            AppMessage::ClosedFileReopened(Ok(loaded_file), session_document) => {
+                self.app_configuration.add_recent_file(&loaded_file.path);
+                self.app_state.document_manager.open_document(&loaded_file);
+                self.restore_session_document(session_document);
+                self.active_document_changed()
+            }
+            AppMessage::ClosedFileReopened(Err(error), _) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::OpenFolderFromDialog => {
+                Task::perform(async_open_folder_from_dialog(), AppMessage::FolderOpened)
+            }
+            AppMessage::FolderOpened(Ok(root)) => self.open_folder(root),
+            AppMessage::FolderOpened(Err(error)) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::CloseFolder => {
+                self.app_state.file_tree = None;
+                self.app_state.file_index = None;
+                self.app_state.search_index = None;
+                self.app_state.quick_open_state.close();
+                self.app_state.folder_search_state.visible = false;
+                self.app_state.folder_search_state.clear();
+                if let Some(handle) = self.folder_search_handle.take() {
+                    handle.abort();
+                }
+                self.sidebar_resizing = false;
+                Task::none()
+            }
+            AppMessage::FolderDirectoryRead(directory, result) => {
+                let Some(file_tree) = &mut self.app_state.file_tree else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(entries) => file_tree.set_children(directory, entries),
+                    Err(error) if directory == file_tree.root => {
+                        self.app_state.file_tree = None;
+                        self.app_state.error = Some(error);
+                    }
+                    Err(_) => file_tree.remove_directory(&directory),
+                }
+                Task::none()
+            }
+            AppMessage::FolderChanged(paths) => {
+                let directories: HashSet<PathBuf> = paths
+                    .iter()
+                    .filter_map(|path| path.parent())
+                    .map(|directory| directory.to_path_buf())
+                    .collect();
+                let index_task = match &self.app_state.file_index {
+                    Some(file_index) => {
+                        let root = file_index.root.clone();
+                        Task::perform(find_changes(root.clone(), paths), move |changes| {
+                            AppMessage::FileIndexChanged(root.clone(), changes)
+                        })
+                    }
+                    None => Task::none(),
+                };
+                Task::batch([self.read_folder_directories(directories), index_task])
+            }
+            AppMessage::FileIndexBuilt(root, files) => {
+                match &mut self.app_state.file_index {
+                    Some(file_index) if file_index.root == root => file_index.set_files(files),
+                    _ => return Task::none(),
+                }
+                self.refresh_quick_open_results()
+            }
+            AppMessage::FileIndexChanged(root, changes) => {
+                match &mut self.app_state.file_index {
+                    Some(file_index) if file_index.root == root => {
+                        file_index.apply(changes.clone())
+                    }
+                    _ => return Task::none(),
+                }
+                let update_search_index =
+                    Task::perform(read_changed_files(changes), move |update| {
+                        AppMessage::SearchIndexUpdated(root.clone(), update)
+                    });
+                Task::batch([self.refresh_quick_open_results(), update_search_index])
+            }
+            AppMessage::SearchIndexLoaded(search_index) => {
+                let Some(file_index) = &mut self.app_state.file_index else {
+                    return Task::none();
+                };
+                if file_index.root != search_index.root {
+                    return Task::none();
+                }
+                //
+                // Until the folder has been listed, quick open finds the files that
+                // were indexed.
+                //
+                if file_index.building && search_index.paths().next().is_some() {
+                    file_index.set_files(search_index.paths().cloned().collect());
+                }
+                let root = search_index.root.clone();
+                let scan = Task::perform(
+                    scan_folder(root.clone(), search_index.modified_times()),
+                    move |update| AppMessage::SearchIndexScanned(root.clone(), update),
+                );
+                self.app_state.search_index = Some(search_index);
+                Task::batch([self.refresh_quick_open_results(), scan])
+            }
+            AppMessage::SearchIndexScanned(root, update) => {
+                if !self.update_search_index(&root, update) {
+                    return Task::none();
+                }
+                if let Some(search_index) = &mut self.app_state.search_index {
+                    Arc::make_mut(search_index).building = false;
+                }
+                Task::batch([self.refresh_quick_open_results(), self.save_search_index()])
+            }
+            AppMessage::SearchIndexUpdated(root, update) => {
+                if !self.update_search_index(&root, update) {
+                    return Task::none();
+                }
+                Task::batch([self.refresh_quick_open_results(), self.save_search_index()])
+            }
+            AppMessage::SearchIndexSaved => {
+                self.search_index_saving = false;
+                match self.search_index_unsaved {
+                    true => self.save_search_index(),
+                    false => Task::none(),
+                }
+            }
+            AppMessage::CloseQuickOpen => {
+                self.app_state.quick_open_state.close();
+                Task::none()
+            }
+            AppMessage::QuickOpenQueryChanged(query) => {
+                self.app_state.quick_open_state.query = query;
+                let search_index = self
+                    .app_state
+                    .search_index
+                    .as_deref()
+                    .filter(|search_index| !search_index.building);
+                if let Some(file_index) = &self.app_state.file_index {
+                    self.app_state.quick_open_state.update_results(
+                        file_index,
+                        &self.app_configuration.recent_files,
+                        search_index,
+                    );
+                }
+                self.quick_open_selection_changed()
+            }
+            AppMessage::QuickOpenSelectNext => {
+                self.app_state.quick_open_state.select_next();
+                self.quick_open_selection_changed()
+            }
+            AppMessage::QuickOpenSelectPrevious => {
+                self.app_state.quick_open_state.select_previous();
+                self.quick_open_selection_changed()
+            }
+            AppMessage::QuickOpenSubmit => {
+                let index = self.app_state.quick_open_state.selected;
+                self.update(AppMessage::QuickOpenOpen(index))
+            }
+            AppMessage::QuickOpenOpen(index) => {
+                let file_path = self
+                    .app_state
+                    .quick_open_state
+                    .results
+                    .get(index)
+                    .map(|(file_path, _)| file_path.clone());
+                self.app_state.quick_open_state.close();
+                match file_path {
+                    Some(file_path) => {
+                        self.app_state.error = None;
+                        Task::perform(async_open_file_from_path(file_path), AppMessage::FileOpened)
+                    }
+                    None => Task::none(),
+                }
+            }
+            AppMessage::QuickOpenPreviewLoaded(file_path, result) => {
+                let quick_open_state = &mut self.app_state.quick_open_state;
+                if quick_open_state.selected_path() == Some(file_path.as_path()) {
+                    let text = match result {
+                        Ok(loaded_file) => loaded_file
+                            .text
+                            .lines()
+                            .take(UI_QUICK_OPEN_PREVIEW_LINES)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        Err(error) => error.to_string(),
+                    };
+                    quick_open_state.preview = Some(QuickOpenPreview {
+                        path: file_path,
+                        text,
+                    });
+                }
+                Task::none()
+            }
+            AppMessage::OpenFolderSearch => self.open_folder_search(),
+            AppMessage::CloseFolderSearch => {
+                self.app_state.folder_search_state.visible = false;
+                Task::none()
+            }
+            AppMessage::FolderSearchQueryChanged(query) => {
+                self.app_state.folder_search_state.query = query;
+                Task::none()
+            }
+            AppMessage::FolderSearchReplacementChanged(replacement) => {
+                self.app_state.folder_search_state.replacement = replacement;
+                Task::none()
+            }
+            AppMessage::FolderSearchIncludeChanged(include) => {
+                self.app_state.folder_search_state.include = include;
+                Task::none()
+            }
+            AppMessage::FolderSearchExcludeChanged(exclude) => {
+                self.app_state.folder_search_state.exclude = exclude;
+                Task::none()
+            }
+            AppMessage::ToggleFolderSearchCaseSensitive => {
+                let options = &mut self.app_state.folder_search_state.options;
+                options.case_sensitive = !options.case_sensitive;
+                self.search_folder()
+            }
+            AppMessage::ToggleFolderSearchWholeWord => {
+                let options = &mut self.app_state.folder_search_state.options;
+                options.whole_word = !options.whole_word;
+                self.search_folder()
+            }
+            AppMessage::ToggleFolderSearchRegex => {
+                let options = &mut self.app_state.folder_search_state.options;
+                options.regex = !options.regex;
+                self.search_folder()
+            }
+            AppMessage::FolderSearchSubmit => self.search_folder(),
+            AppMessage::FolderSearchEvent(search_id, event) => {
+                let folder_search_state = &mut self.app_state.folder_search_state;
+                if search_id == folder_search_state.search_id {
+                    folder_search_state.apply(event);
+                }
+                Task::none()
+            }
+            AppMessage::FolderSearchOpenMatch(file_path, find_match) => {
+                self.app_state.error = None;
+                Task::perform(async_open_file_from_path(file_path), move |result| {
+                    AppMessage::FolderSearchFileOpened(result, find_match)
+                })
+            }
+            AppMessage::FolderSearchFileOpened(Ok(loaded_file), find_match) => {
+                self.app_configuration.add_recent_file(&loaded_file.path);
+                self.app_state.document_manager.open_document(&loaded_file);
+                let task = self.active_document_changed();
+                let document = self.app_state.document_manager.active_mut();
+                select_range(
+                    &mut document.file_content,
+                    find_match.start_position(),
+                    find_match.end_position(),
+                );
+                task.chain(self.scroll_to_line(find_match.line))
+            }
+            AppMessage::FolderSearchFileOpened(Err(error), _) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::FolderReplacePreview => self.preview_folder_replace(),
+            AppMessage::FolderReplacePreviewed(search_id, replacements) => {
+                if search_id != self.app_state.folder_search_state.search_id {
+                    return Task::none();
+                }
+                let mut replace_preview = ReplacePreview::default();
+                for (file_path, result) in replacements {
+                    let unsaved = self
+                        .app_state
+                        .document_manager
+                        .find_by_path(&file_path)
+                        .is_some_and(|document| document.file_dirty);
+                    match result {
+                        Ok(_) if unsaved => replace_preview
+                            .skipped
+                            .push((file_path, t!("folder_replace_unsaved").to_string())),
+                        Ok(file_replacement) => replace_preview.files.push(file_replacement),
+                        Err(error) => replace_preview.skipped.push((file_path, error.to_string())),
+                    }
+                }
+                self.app_state.folder_search_state.replace_preview = Some(replace_preview);
+                Task::none()
+            }
+            AppMessage::FolderReplaceConfirm => {
+                let folder_search_state = &mut self.app_state.folder_search_state;
+                let Some(replace_preview) = folder_search_state.replace_preview.take() else {
+                    return Task::none();
+                };
+                folder_search_state.pending_writes = replace_preview.files.len();
+                folder_search_state.written_files = 0;
+                let file_backup = self.app_configuration.get_file_backup();
+                Task::batch(replace_preview.files.into_iter().map(|file_replacement| {
+                    Task::perform(
+                        write_replacement(file_replacement, file_backup.clone()),
+                        AppMessage::FolderReplaceWritten,
+                    )
+                }))
+            }
+            AppMessage::FolderReplaceCancel => {
+                self.app_state.folder_search_state.replace_preview = None;
+                Task::none()
+            }
+            AppMessage::FolderReplaceWritten(result) => {
+                let folder_search_state = &mut self.app_state.folder_search_state;
+                folder_search_state.pending_writes =
+                    folder_search_state.pending_writes.saturating_sub(1);
+                match result {
+                    Ok(_) => folder_search_state.written_files += 1,
+                    Err(error) => self.app_state.error = Some(error),
+                }
+                if folder_search_state.pending_writes > 0 {
+                    return Task::none();
+                }
+                //
+                // Open documents of the written files are reloaded by the file
+                // watcher, the results are searched again.
+                //
+                self.app_state.notice = Some(
+                    t!(
+                        "folder_replace_done",
+                        files = folder_search_state.written_files
+                    )
+                    .to_string(),
+                );
+                self.search_folder()
+            }
+            AppMessage::FileTreeToggle(directory) => {
+                let Some(file_tree) = &mut self.app_state.file_tree else {
+                    return Task::none();
+                };
+                file_tree.selected = Some(directory.clone());
+                file_tree.context_menu = None;
+                match file_tree.toggle(&directory) {
+                    true => self.read_folder_directories([directory]),
+                    false => Task::none(),
+                }
+            }
+            AppMessage::FileTreeOpenFile(file_path) => {
+                if let Some(file_tree) = &mut self.app_state.file_tree {
+                    file_tree.selected = Some(file_path.clone());
+                    file_tree.context_menu = None;
+                }
+                self.app_state.error = None;
+                Task::perform(async_open_file_from_path(file_path), AppMessage::FileOpened)
+            }
+            AppMessage::FileTreeContextMenu(path) => {
+                if let Some(file_tree) = &mut self.app_state.file_tree {
+                    file_tree.context_menu = match file_tree.context_menu == Some(path.clone()) {
+                        true => None,
+                        false => Some(path.clone()),
+                    };
+                    file_tree.selected = Some(path);
+                }
+                Task::none()
+            }
+            AppMessage::FileTreeStartEdit(edit) => {
+                let Some(file_tree) = &mut self.app_state.file_tree else {
+                    return Task::none();
+                };
+                file_tree.context_menu = None;
+                let text = file_tree.initial_edit_text(&edit);
+                file_tree.edit = Some((edit, text));
+                text_input::focus(text_input::Id::new(UI_FILE_TREE_INPUT_ID))
+            }
+            AppMessage::FileTreeEditChanged(text) => {
+                if let Some(file_tree) = &mut self.app_state.file_tree
+                    && let Some((_, value)) = &mut file_tree.edit
+                {
+                    *value = text;
+                }
+                Task::none()
+            }
+            AppMessage::FileTreeEditSubmit => {
+                let Some(file_tree) = &mut self.app_state.file_tree else {
+                    return Task::none();
+                };
+                let Some(target) = file_tree
+                    .edit
+                    .as_ref()
+                    .and_then(|(edit, value)| file_tree.edit_target(edit, value))
+                else {
+                    return Task::none();
+                };
+                let task = match file_tree.edit.take() {
+                    Some((FileTreeEdit::NewFile(_), _)) => {
+                        Task::perform(async_create_file(target), AppMessage::FileOperationFinished)
+                    }
+                    Some((FileTreeEdit::NewFolder(_), _)) => Task::perform(
+                        async_create_folder(target),
+                        AppMessage::FileOperationFinished,
+                    ),
+                    Some((FileTreeEdit::Rename(path), _)) | Some((FileTreeEdit::Move(path), _)) => {
+                        Task::perform(
+                            async_rename_path(path, target),
+                            AppMessage::FileOperationFinished,
+                        )
+                    }
+                    None => Task::none(),
+                };
+                self.app_state.error = None;
+                task
+            }
+            AppMessage::FileTreeEditCancel => {
+                if let Some(file_tree) = &mut self.app_state.file_tree {
+                    file_tree.edit = None;
+                }
+                Task::none()
+            }
+            AppMessage::FileTreeDelete(path) => {
+                if let Some(file_tree) = &mut self.app_state.file_tree {
+                    file_tree.context_menu = None;
+                    file_tree.delete_prompt = Some(path);
+                }
+                Task::none()
+            }
+            AppMessage::FileTreeDeleteConfirm => {
+                match self
+                    .app_state
+                    .file_tree
+                    .as_mut()
+                    .and_then(|file_tree| file_tree.delete_prompt.take())
+                {
+                    Some(path) => {
+                        self.app_state.error = None;
+                        Task::perform(async_delete_path(path), AppMessage::FileOperationFinished)
+                    }
+                    None => Task::none(),
+                }
+            }
+            AppMessage::FileTreeDeleteCancel => {
+                if let Some(file_tree) = &mut self.app_state.file_tree {
+                    file_tree.delete_prompt = None;
+                }
+                Task::none()
+            }
+            AppMessage::FileOperationFinished(Ok(operation)) => {
+                self.file_operation_finished(operation)
+            }
+            AppMessage::FileOperationFinished(Err(error)) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::SidebarResizeStarted => {
+                self.sidebar_resizing = true;
+                Task::none()
+            }
+            AppMessage::SidebarResized(width) => {
+                if self.sidebar_resizing {
+                    self.app_configuration.sidebar_width =
+                        width.clamp(UI_SIDEBAR_MIN_WIDTH, UI_SIDEBAR_MAX_WIDTH);
+                }
+                Task::none()
+            }
+            AppMessage::SidebarResizeFinished => {
+                self.sidebar_resizing = false;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::SessionRestored(active_file) => {
+                let document_manager = &mut self.app_state.document_manager;
+                if let Some(document_id) = active_file
+                    .and_then(|active_file| document_manager.find_by_path(&active_file))
+                    .map(|document| document.id)
+                {
+                    document_manager.select_document(document_id);
+                }
+                self.active_document_changed()
+            }
+            AppMessage::UpdateSyntaxMode(syntax_mode) => {
+                self.app_state.document_manager.active_mut().syntax_mode = syntax_mode;
+                Task::none()
+            }
+            AppMessage::ReopenWithEncoding(encoding) => self.reopen_with_encoding(encoding),
+            AppMessage::FileReopened(document_id, Ok(loaded_file)) => {
+                if let Some(document) = self.app_state.document_manager.get_mut(document_id) {
+                    //
+                    // The text decoded with the new encoding is what the file
+                    // contains, so it stays unmodified if it was before.
+                    //
+                    let was_saved = document.history.is_saved();
+                    document.replace_text(&loaded_file.text);
+                    document.encoding = loaded_file.encoding;
+                    document.file_modified = loaded_file.modified;
+                    document.file_missing = false;
+                    if was_saved {
+                        document.history.mark_saved(document.history.state_id());
+                    }
+                    document.file_dirty = !document.is_saved();
+                }
+                self.refresh_find_matches();
+                self.refresh_markdown_preview();
+                Task::none()
+            }
+            AppMessage::FileReopened(_, Err(error)) => {
+                self.app_state.error = Some(error);
+                Task::none()
+            }
+            AppMessage::ConvertLineEnding(line_ending) => {
+                let document = self.app_state.document_manager.active_mut();
+                //
+                // The text is unchanged, but the file is different unless the
+                // line ending is converted back.
+                //
+                document.line_ending = line_ending;
+                document.file_dirty = !document.is_saved();
+                Task::none()
+            }
+            AppMessage::UpdateDefaultLineEnding(line_ending) => {
+                self.app_state
+                    .document_manager
+                    .set_default_line_ending(line_ending);
+                self.app_configuration.default_line_ending = line_ending;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::UpdateBackupMode(backup_mode) => {
+                self.app_configuration.backup_mode = backup_mode;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::SaveWithEncoding(encoding) => {
+                //
+                // The document keeps its encoding until the text was written in
+                // the new one, it may not be able to represent every character.
+                //
+                let document_id = self.app_state.document_manager.active().id;
+                self.save_document_with(document_id, AppMessage::FileSaved, Some(encoding))
+            }
+            AppMessage::NewFile => self.new_file(),
+            AppMessage::SaveFile => self.save_file(),
+            AppMessage::FileSaved(document_id, state_id, Ok(saved_file)) => {
+                let running_save = self.saving_documents.remove(&document_id);
+                self.app_configuration.add_recent_file(&saved_file.path);
+                if let Some(document) = self.app_state.document_manager.get_mut(document_id) {
+                    if let Some(running_save) = &running_save {
+                        document.encoding = running_save.encoding;
+                        document.saved_line_ending = running_save.line_ending;
+                    }
+                    document.file_path = Some(saved_file.path);
+                    document.file_modified = saved_file.modified;
+                    document.file_missing = false;
+                    document.history.mark_saved(state_id);
+                    document.file_dirty = !document.is_saved();
+                }
+                let queued_save = self.start_queued_save(document_id, running_save);
+                //
+                // The save started by the prompt is the last one, an autosave that
+                // was already running is followed by it.
+                //
+                let close_task = match self.unsaved_changes_prompt {
+                    Some(prompt)
+                        if prompt.saving
+                            && prompt.document_id == document_id
+                            && !self.saving_documents.contains_key(&document_id) =>
+                    {
+                        let is_saved = self
+                            .app_state
+                            .document_manager
+                            .get(document_id)
+                            .is_some_and(|document| document.is_saved());
+                        if is_saved {
+                            self.unsaved_changes_prompt = None;
+                            self.resume_close_intent(prompt)
+                        } else {
+                            self.unsaved_changes_prompt = Some(UnsavedChangesPrompt {
+                                saving: false,
+                                ..prompt
+                            });
+                            Task::none()
+                        }
+                    }
+                    _ => Task::none(),
+                };
+                Task::batch([queued_save, close_task])
+            }
+            AppMessage::FileAutosaved(document_id, state_id, result) => {
+                if result.is_ok() {
+                    self.app_state.autosaved_at = Some(Instant::now());
+                }
+                self.update(AppMessage::FileSaved(document_id, state_id, result))
+            }
+            AppMessage::AutosaveTick(now) => {
+                if self.app_state.autosaved_at.is_some_and(|autosaved_at| {
+                    now.duration_since(autosaved_at) >= AUTOSAVED_NOTICE_DURATION
+                }) {
+                    self.app_state.autosaved_at = None;
+                }
+                let delay = Duration::from_secs(self.app_configuration.autosave_delay_seconds);
+                match self.last_edit_at {
+                    Some(last_edit_at)
+                        if self.app_configuration.autosave_mode == AutosaveMode::AfterDelay
+                            && now.duration_since(last_edit_at) >= delay =>
+                    {
+                        self.autosave()
+                    }
+                    _ => Task::none(),
+                }
+            }
+            AppMessage::WatchedFileChanged(file_path) => {
+                match self.app_state.document_manager.find_by_path(&file_path) {
+                    Some(document) if !self.saving_documents.contains_key(&document.id) => {
+                        let document_id = document.id;
+                        Task::perform(
+                            async_open_file_with_encoding(file_path, document.encoding),
+                            move |result| AppMessage::WatchedFileLoaded(document_id, result),
+                        )
+                    }
+                    _ => Task::none(),
+                }
+            }
+            AppMessage::WatchedFileLoaded(document_id, Ok(loaded_file)) => {
+                self.watched_file_loaded(document_id, loaded_file);
+                Task::none()
+            }
+            AppMessage::WatchedFileLoaded(
+                document_id,
+                Err(AppIOError::IOFailedError(ErrorKind::NotFound)),
+            ) => {
+                if let Some(document) = self.app_state.document_manager.get_mut(document_id) {
+                    document.file_missing = true;
+                }
+                Task::none()
+            }
+            AppMessage::WatchedFileLoaded(_, Err(_)) => Task::none(),
+            AppMessage::FileChangedReload => {
+                if !self.file_changed_prompts.is_empty() {
+                    let prompt = self.file_changed_prompts.remove(0);
+                    self.reload_document(prompt.document_id, &prompt.loaded_file);
+                }
+                Task::none()
+            }
+            AppMessage::FileChangedKeepMine => {
+                if !self.file_changed_prompts.is_empty() {
+                    //
+                    // Only ask again when the file changes once more.
+                    //
+                    let prompt = self.file_changed_prompts.remove(0);
+                    if let Some(document) =
+                        self.app_state.document_manager.get_mut(prompt.document_id)
+                    {
+                        document.file_modified = prompt.loaded_file.modified;
+                        //
+                        // Autosave skipped the document while the prompt was open.
+                        //
+                        if document.file_dirty {
+                            self.last_edit_at = Some(Instant::now());
+                        }
+                    }
+                }
+                Task::none()
+            }
+            AppMessage::FileChangedShowDiff => {
+                let document_manager = &self.app_state.document_manager;
+                if let Some(prompt) = self.file_changed_prompts.first_mut()
+                    && let Some(document) = document_manager.get(prompt.document_id)
+                {
+                    prompt.diff_view = Some(DiffView::new(
+                        t!("file_changed_on_disk").to_string(),
+                        &prompt.loaded_file.text,
+                        t!("file_changed_yours").to_string(),
+                        &document.text(),
+                    ));
+                }
+                Task::none()
+            }
+            AppMessage::FileChangedHideDiff => {
+                if let Some(prompt) = self.file_changed_prompts.first_mut() {
+                    prompt.diff_view = None;
+                }
+                Task::none()
+            }
+            AppMessage::RecoveryTick => self.write_recovery_journal(),
+            AppMessage::RecoveryJournalWritten(result) => {
+                if result.is_err() {
+                    //
+                    // Write all snapshots again on the next tick.
+                    //
+                    self.recovery_journal.forget_written();
+                }
+                Task::none()
+            }
+            AppMessage::RecoverySnapshotsFound(snapshots) => {
+                if !snapshots.is_empty() {
+                    self.recovery_prompt = Some(RecoveryPrompt::new(snapshots));
+                }
+                Task::none()
+            }
+            AppMessage::RecoveryRestore(index) => {
+                self.restore_snapshot(index);
+                self.active_document_changed()
+                    .chain(self.write_recovery_journal())
+            }
+            AppMessage::RecoveryDiscard(index) => {
+                if let Some(prompt) = &mut self.recovery_prompt
+                    && index < prompt.snapshots.len()
+                {
+                    prompt.compare = None;
+                    prompt.snapshots.remove(index).remove();
+                }
+                self.close_finished_recovery_prompt();
+                Task::none()
+            }
+            AppMessage::RecoveryCompare(index) => {
+                let file_path = self
+                    .recovery_prompt
+                    .as_ref()
+                    .and_then(|prompt| prompt.snapshots.get(index))
+                    .and_then(|recovered| recovered.snapshot.file_path.clone());
+                match file_path {
+                    Some(file_path) => {
+                        Task::perform(async_open_file_from_path(file_path), move |result| {
+                            AppMessage::RecoveryCompareLoaded(index, result)
+                        })
+                    }
+                    None => Task::none(),
+                }
+            }
+            AppMessage::RecoveryCompareLoaded(index, result) => {
+                if let Some(prompt) = &mut self.recovery_prompt
+                    && let Some(recovered) = prompt.snapshots.get(index)
+                {
+                    //
+                    // A file that cannot be read, e.g. because it was deleted, is
+                    // compared as an empty file.
+                    //
+                    let (file_title, file_text) = match result {
+                        Ok(loaded_file) => {
+                            (t!("recovery_file_on_disk").to_string(), loaded_file.text)
+                        }
+                        Err(error) => (error.to_string(), Arc::default()),
+                    };
+                    let diff_view = DiffView::new(
+                        file_title,
+                        &file_text,
+                        t!(
+                            "recovery_snapshot",
+                            saved_at = recovered.snapshot.saved_at_text()
+                        )
+                        .to_string(),
+                        &recovered.snapshot.text,
+                    );
+                    prompt.compare = Some((index, diff_view));
+                }
+                Task::none()
+            }
+            AppMessage::RecoveryCompareClosed => {
+                if let Some(prompt) = &mut self.recovery_prompt {
+                    prompt.compare = None;
+                }
+                Task::none()
+            }
+            AppMessage::RecoveryRestoreAll => {
+                while self
+                    .recovery_prompt
+                    .as_ref()
+                    .is_some_and(|prompt| !prompt.snapshots.is_empty())
+                {
+                    self.restore_snapshot(0);
+                }
+                self.active_document_changed()
+                    .chain(self.write_recovery_journal())
+            }
+            AppMessage::RecoveryDiscardAll => {
+                if let Some(prompt) = self.recovery_prompt.take() {
+                    for recovered in prompt.snapshots {
+                        recovered.remove();
+                    }
+                }
+                Task::none()
+            }
+            AppMessage::RecoveryLater => {
+                //
+                // The snapshots stay in the journal and are offered again on the
+                // next start.
+                //
+                self.recovery_prompt = None;
+                Task::none()
+            }
+            AppMessage::UpdateAutosaveMode(autosave_mode) => {
+                self.app_configuration.autosave_mode = autosave_mode;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::UpdateExportTableOfContents(enabled) => {
+                self.app_configuration.export_table_of_contents = enabled;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::UpdatePdfPageSize(page_size) => {
+                self.app_configuration.pdf_page_size = page_size;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::UpdatePdfMargin(margin) => {
+                self.app_configuration.pdf_margin = margin;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::FileExported(result) => {
+                match result {
+                    Ok(saved_file) => {
+                        self.app_state.notice =
+                            Some(t!("export_done", file = saved_file.path.display()).to_string());
+                    }
+                    //
+                    // Closing the file dialog cancels the export.
+                    //
+                    Err(AppIOError::FileDialogClosedError) => {}
+                    Err(error) => self.app_state.error = Some(error),
+                }
+                Task::none()
+            }
+            AppMessage::FileSaved(document_id, _, Err(error)) => {
+                let running_save = self.saving_documents.remove(&document_id);
+                let queued_save = self.start_queued_save(document_id, running_save);
+                self.app_state.error = Some(error);
+                //
+                // The save was cancelled or failed, so the document is still dirty.
+                //
+                if !self.saving_documents.contains_key(&document_id)
+                    && self
+                        .unsaved_changes_prompt
+                        .is_some_and(|prompt| prompt.saving && prompt.document_id == document_id)
+                {
+                    self.unsaved_changes_prompt = None;
+                }
+                queued_save
+            }
+            AppMessage::SelectDocument(document_id) => {
+                self.app_state.document_manager.select_document(document_id);
+                self.active_document_changed()
+            }
+            AppMessage::CloseDocument(document_id) => self.close_document(document_id),
+            AppMessage::UnsavedChangesSave => match self.unsaved_changes_prompt.as_mut() {
+                Some(prompt) => {
+                    prompt.saving = true;
+                    let document_id = prompt.document_id;
+                    self.save_document(document_id)
+                }
+                None => Task::none(),
+            },
+            AppMessage::UnsavedChangesDiscard => match self.unsaved_changes_prompt.take() {
+                Some(prompt) => {
+                    self.remember_closed_file(prompt.document_id);
+                    self.app_state
+                        .document_manager
+                        .close_document(prompt.document_id);
+                    self.file_changed_prompts
+                        .retain(|file_changed| file_changed.document_id != prompt.document_id);
+                    let task = self.active_document_changed();
+                    match prompt.intent {
+                        CloseIntent::CloseDocument => task.chain(self.exit_if_wait_finished()),
+                        CloseIntent::QuitApplication => self.quit(),
+                    }
+                }
+                None => Task::none(),
+            },
+            AppMessage::UnsavedChangesCancel => {
+                self.unsaved_changes_prompt = None;
+                Task::none()
+            }
+            AppMessage::EditorScrolled(viewport) => {
+                self.app_state.document_manager.active_mut().scroll_offset =
+                    viewport.absolute_offset();
+                self.editor_viewport_height = viewport.bounds().height;
+                Task::none()
+            }
+            AppMessage::OpenFindPanel => self.open_find_panel(false),
+            AppMessage::CloseFindPanel => {
+                self.app_state.find_state.visible = false;
+                Task::none()
+            }
+            AppMessage::FindQueryChanged(query) => {
+                self.app_state.find_state.query = query;
+                self.refresh_find_matches();
+                self.select_current_match()
+            }
+            AppMessage::ReplacementChanged(replacement) => {
+                self.app_state.find_state.replacement = replacement;
+                Task::none()
+            }
+            AppMessage::ToggleFindCaseSensitive => {
+                let options = &mut self.app_state.find_state.options;
+                options.case_sensitive = !options.case_sensitive;
+                self.refresh_find_matches();
+                Task::none()
+            }
+            AppMessage::ToggleFindWholeWord => {
+                let options = &mut self.app_state.find_state.options;
+                options.whole_word = !options.whole_word;
+                self.refresh_find_matches();
+                Task::none()
+            }
+            AppMessage::ToggleFindRegex => {
+                let options = &mut self.app_state.find_state.options;
+                options.regex = !options.regex;
+                self.refresh_find_matches();
+                Task::none()
+            }
+            AppMessage::FindNext => self.find_next(),
+            AppMessage::FindPrevious => self.find_previous(),
+            AppMessage::ReplaceOne => self.replace_one(),
+            AppMessage::ReplaceAll => {
+                self.replace_all();
+                Task::none()
+            }
+            AppMessage::RecordKeybind(keybind_action) => {
+                self.keybinding_editor.warning = None;
+                self.keybinding_editor.recording =
+                    match self.keybinding_editor.recording == Some(keybind_action) {
+                        true => None,
+                        false => Some(keybind_action),
+                    };
+                Task::none()
+            }
+            AppMessage::ResetKeybind(keybind_action) => {
+                self.reset_keybind(keybind_action);
+                Task::none()
+            }
+            AppMessage::ResetAllKeybinds => {
+                self.keybinding_editor.recording = None;
+                self.keybinding_editor.warning = None;
+                self.app_state.keybind_manager.reset_all_keybinds();
+                self.app_state.keybind_manager.save();
+                Task::none()
+            }
+            AppMessage::ClipboardPasted(text) => {
+                if let Some(text) = text.filter(|text| !text.is_empty()) {
+                    self.perform_text_action(Action::Edit(Edit::Paste(Arc::new(text))));
+                }
+                Task::none()
+            }
+            AppMessage::RunKeybindAction(keybind_action) => {
+                self.perform_keybind_action(keybind_action)
+            }
+            AppMessage::CloseCommandPalette => {
+                self.app_state.palette_state.close();
+                Task::none()
+            }
+            AppMessage::CommandPaletteQueryChanged(query) => {
+                self.app_state.palette_state.query = query;
+                self.app_state.palette_state.update_results();
+                self.scroll_to_selected_command()
+            }
+            AppMessage::CommandPaletteSelectNext => {
+                self.app_state.palette_state.select_next();
+                self.scroll_to_selected_command()
+            }
+            AppMessage::CommandPaletteSelectPrevious => {
+                self.app_state.palette_state.select_previous();
+                self.scroll_to_selected_command()
+            }
+            AppMessage::CommandPaletteSubmit => {
+                let index = self.app_state.palette_state.selected;
+                self.run_command(index)
+            }
+            AppMessage::RunCommand(index) => self.run_command(index),
+            AppMessage::OpenCommandPaletteWithQuery(query) => {
+                self.open_command_palette_with_query(query)
+            }
+            AppMessage::UpdateLanguage(str) => {
+                rust_i18n::set_locale(str.as_ref());
+                Task::none()
+            }
+            AppMessage::UpdateWindowTheme(theme) => {
+                self.app_state.window_theme = theme;
+                Task::none()
+            }
+            AppMessage::UpdateSyntaxTheme(theme) => {
+                self.app_state.syntax_theme = theme;
+                self.refresh_markdown_preview();
+                Task::none()
+            }
+            AppMessage::UpdateScale(value) => {
+                self.app_state.scale_factor = value;
+                self.app_configuration.scale_factor = value;
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::EventOccurred(iced::Event::Keyboard(
+                iced::keyboard::Event::KeyPressed { key, modifiers, .. },
+            )) => {
+                if let Some(keybind_action) = self.keybinding_editor.recording {
+                    self.record_keybind(keybind_action, &key, modifiers);
+                    return Task::none();
+                }
+                match self
+                    .app_state
+                    .keybind_manager
+                    .get_app_action(&key, modifiers)
+                {
+                    Some(keybind_action) => self.perform_keybind_action(keybind_action),
+                    None => Task::none(),
+                }
+            }
+            AppMessage::EventOccurred(iced::Event::Mouse(_)) => Task::none(),
+            AppMessage::EventOccurred(iced::Event::Window(event)) => {
+                println!("EVENT {:?}", event);
+                match event {
+                    Event::Moved(point) => {
+                        //
+                        // This does not currently work. The iced docs state "Note: Not available in Wayland."
+                        //
+                        // https://docs.rs/iced_core/0.13.2/iced_core/window/enum.Event.html
+                        //
+                        self.app_configuration.window_x = point.x;
+                        self.app_configuration.window_y = point.y;
+                        self.app_configuration.save();
+                    }
+                    Event::Opened { .. } => {}
+                    Event::Closed => {}
+                    Event::Resized(size) => {
+                        self.app_configuration.window_w =
+                            size.width * self.app_state.scale_factor as f32;
+                        self.app_configuration.window_h =
+                            size.height * self.app_state.scale_factor as f32;
+                        self.app_configuration.save();
+                    }
+                    Event::RedrawRequested(_) => {}
+                    Event::CloseRequested => {
+                        if self.app_configuration.autosave_mode == AutosaveMode::OnWindowClose {
+                            //
+                            // Quit after the saves are done, so only untitled documents
+                            // are left to ask about.
+                            //
+                            return self.autosave().chain(Task::done(
+                                AppMessage::RunKeybindAction(KeybindAction::QuitApplication),
+                            ));
+                        }
+                        return self.quit();
+                    }
+                    Event::Focused => {}
+                    Event::Unfocused => {
+                        if self.app_configuration.autosave_mode == AutosaveMode::OnFocusLost {
+                            return self.autosave();
+                        }
+                    }
+                    Event::FileHovered(_) => {
+                        self.files_hovered = true;
+                    }
+                    Event::FileDropped(file_path) => {
+                        //
+                        // Each dropped file is a separate event.
+                        //
+                        self.files_hovered = false;
+                        self.app_state.error = None;
+                        return Task::perform(
+                            async_open_file_from_path(file_path),
+                            AppMessage::FileOpened,
+                        );
+                    }
+                    Event::FilesHoveredLeft => {
+                        self.files_hovered = false;
+                    }
+                }
+                Task::none()
+            }
+            AppMessage::EventOccurred(iced::Event::Touch(_)) => Task::none(),
+            AppMessage::EventOccurred(iced::Event::Keyboard(
+                iced::keyboard::Event::ModifiersChanged(_),
+            )) => Task::none(),
+            AppMessage::EventOccurred(iced::Event::Keyboard(
+                iced::keyboard::Event::KeyReleased { .. },
+            )) => Task::none(),
+            // AppMessage::WindowResized(w, h) => {
+            //     self.app_configuration.window_w = w;
+            //     self.app_configuration.window_h = h;
+            //     self.app_configuration.save();
+            //     Task::none()
+            // }
+            AppMessage::SaveAppConfiguration => {
+                // if self.app_state.app_configuration_changed {
+                //     self.app_configuration.save();
+                //     self.app_state.app_configuration_changed = false;
+                // }
+                self.app_configuration.save();
+                Task::none()
+            }
+            AppMessage::TabPressed => Task::none(),
+            AppMessage::FocusChanged(_id) => Task::none(),
+            AppMessage::OpenAppConfigurationModal => {
+                self.show_app_configuration_modal = true;
+                Task::none()
+            }
+            AppMessage::CloseAppConfigurationModal => {
+                self.show_app_configuration_modal = false;
+                self.keybinding_editor.recording = None;
+                self.keybinding_editor.warning = None;
+                Task::none()
+            }
+        }
+    }
+
+    ///
+    /// Run the action bound to a keybind. Also used by the command palette.
+    ///
+    fn perform_keybind_action(&mut self, keybind_action: KeybindAction) -> Task<AppMessage> {
+        match keybind_action {
+            KeybindAction::ShowSettings => {
+                //self.app_state.current_view = MainWindowView::Settings;
+                self.show_app_configuration_modal = true;
+            }
+            KeybindAction::QuitApplication => {
+                return self.quit();
+            }
+            KeybindAction::CutText => {
+                return self.cut_text();
+            }
+            KeybindAction::CopyText => {
+                return self.copy_text();
+            }
+            KeybindAction::PasteText => {
+                return iced::clipboard::read().map(AppMessage::ClipboardPasted);
+            }
+            KeybindAction::Undo => {
+                self.undo();
+            }
+            KeybindAction::Redo => {
+                self.redo();
+            }
+            KeybindAction::SelectAll => {
+                self.perform_text_action(Action::SelectAll);
+            }
+            KeybindAction::NewFile => {
+                return self.new_file();
+            }
+            KeybindAction::OpenFile => {
+                return self.open_file();
+            }
+            KeybindAction::ReopenClosedFile => {
+                return self.reopen_closed_file();
+            }
+            KeybindAction::OpenFolder => {
+                return self.update(AppMessage::OpenFolderFromDialog);
+            }
+            KeybindAction::CloseFolder => {
+                return self.update(AppMessage::CloseFolder);
+            }
+            KeybindAction::QuickOpen => {
+                return self.open_quick_open();
+            }
+            KeybindAction::SaveFile => {
+                return self.save_file();
+            }
+            KeybindAction::CloseFile => {
+                let document_id = self.app_state.document_manager.active().id;
+                return self.close_document(document_id);
+            }
+            KeybindAction::NextTab => {
+                self.app_state.document_manager.select_next();
+                return self.active_document_changed();
+            }
+            KeybindAction::PreviousTab => {
+                self.app_state.document_manager.select_previous();
+                return self.active_document_changed();
+            }
+            KeybindAction::Find => {
+                return self.open_find_panel(false);
+            }
+            KeybindAction::FindNext => {
+                return self.find_next();
+            }
+            KeybindAction::FindPrevious => {
+                return self.find_previous();
+            }
+            KeybindAction::Replace => {
+                return self.open_find_panel(true);
+            }
+            KeybindAction::SearchInFolder => {
+                return self.open_folder_search();
+            }
+            KeybindAction::ToggleMarkdownPreview => {
+                return self.toggle_markdown_preview();
+            }
+            KeybindAction::ExportHtml => {
+                return self.export_html();
+            }
+            KeybindAction::ExportPdf => {
+                return self.export_pdf();
+            }
+            KeybindAction::CommandPalette => {
+                return self.open_command_palette();
+            }
+        }
+        Task::none()
+    }
+
+    ///
+    /// Show the command palette and focus its input.
+    ///
+    fn open_command_palette(&mut self) -> Task<AppMessage> {
+        self.open_command_palette_with_query(String::new())
+    }
+
+    fn open_command_palette_with_query(&mut self, query: String) -> Task<AppMessage> {
+        self.app_state
+            .palette_state
+            .open_with_query(query, self.app_configuration.recent_files.clone());
+        text_input::focus(text_input::Id::new(UI_PALETTE_INPUT_ID))
+    }
+
+    ///
+    /// Close the command palette and run the command at `index` in its results.
+    ///
+    fn run_command(&mut self, index: usize) -> Task<AppMessage> {
+        let message = self
+            .app_state
+            .palette_state
+            .results
+            .get(index)
+            .map(|command| command.message());
+        self.app_state.palette_state.close();
+        match message {
+            Some(message) => self.update(message),
+            None => Task::none(),
+        }
+    }
+
+    ///
+    /// Keep the selected command visible in the palette result list.
+    ///
+    fn scroll_to_selected_command(&self) -> Task<AppMessage> {
+        let palette_state = &self.app_state.palette_state;
+        let y = match palette_state.results.len() {
+            0 | 1 => 0.0,
+            len => palette_state.selected as f32 / (len - 1) as f32,
+        };
+        scrollable::snap_to(
+            scrollable::Id::new(UI_PALETTE_SCROLLABLE_ID),
+            RelativeOffset { x: 0.0, y },
+        )
+    }
+
+    ///
+    /// Assign the pressed key combination to the action being recorded in the
+    /// keybinding editor. Escape cancels the recording, and keys that cannot be
+    /// bound (e.g. a modifier on its own) are ignored.
+    ///
+    fn record_keybind(
+        &mut self,
+        keybind_action: KeybindAction,
+        key: &iced::keyboard::Key,
+        modifiers: iced::keyboard::Modifiers,
+    ) {
+        if *key == iced::keyboard::Key::Named(iced::keyboard::key::Named::Escape)
+            && modifiers.is_empty()
+        {
+            self.keybinding_editor.recording = None;
+            return;
+        }
+        let Some(keybind) = KeybindManager::get_keybind_for_key(key, modifiers) else {
+            return;
+        };
+        self.keybinding_editor.recording = None;
+        let keybind_manager = &mut self.app_state.keybind_manager;
+        let previous = keybind_manager.set_keybind(keybind_action, keybind);
+        self.keybinding_editor.warning =
+            previous.map(|previous| AppMain::keybind_conflict_warning(keybind, previous));
+        keybind_manager.save();
+    }
+
+    fn reset_keybind(&mut self, keybind_action: KeybindAction) {
+        self.keybinding_editor.recording = None;
+        let keybind_manager = &mut self.app_state.keybind_manager;
+        let taken = keybind_manager.reset_keybind(keybind_action);
+        self.keybinding_editor.warning = (!taken.is_empty()).then(|| {
+            taken
+                .into_iter()
+                .map(|(keybind, previous)| AppMain::keybind_conflict_warning(keybind, previous))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        keybind_manager.save();
+    }
+
+    fn keybind_conflict_warning(
+        keybind: (iced::keyboard::Modifiers, iced::keyboard::key::Code),
+        previous: KeybindAction,
+    ) -> String {
+        t!(
+            "keybind_conflict_warning",
+            keybind = KeybindManager::get_display_text_for_keybind(keybind.0, keybind.1),
+            action = t!(previous.i18n_key())
+        )
+        .to_string()
+    }
+
+    fn save_file(&mut self) -> Task<AppMessage> {
+        let document_id = self.app_state.document_manager.active().id;
+        self.save_document(document_id)
+    }
+
+    fn save_document(&mut self, document_id: DocumentId) -> Task<AppMessage> {
+        self.save_document_with(document_id, AppMessage::FileSaved, None)
+    }
+
+    ///
+    /// Save every modified document that has a file. Untitled documents are
+    /// skipped, autosave never asks for a file name. So are documents whose file
+    /// was changed or deleted by another program, until the user decides.
+    ///
+    fn autosave(&mut self) -> Task<AppMessage> {
+        self.last_edit_at = None;
+        let document_ids: Vec<DocumentId> = self
+            .app_state
+            .document_manager
+            .documents()
+            .iter()
+            .filter(|document| {
+                document.file_dirty
+                    && document.file_path.is_some()
+                    && !document.file_missing
+                    && !self.saving_documents.contains_key(&document.id)
+                    && !self
+                        .file_changed_prompts
+                        .iter()
+                        .any(|prompt| prompt.document_id == document.id)
+            })
+            .map(|document| document.id)
+            .collect();
+        Task::batch(document_ids.into_iter().map(|document_id| {
+            self.save_document_with(document_id, AppMessage::FileAutosaved, None)
+        }))
+    }
+
+    ///
+    /// Save the document in `encoding`, or in its own encoding, reporting the
+    /// result with the `saved` message. A document that is already being saved
+    /// is saved again once that save has finished, so that writes to the same
+    /// file never overlap.
+    ///
+    fn save_document_with(
+        &mut self,
+        document_id: DocumentId,
+        saved: SavedMessage,
+        encoding: Option<TextEncoding>,
+    ) -> Task<AppMessage> {
+        if let Some(running_save) = self.saving_documents.get_mut(&document_id) {
+            running_save.queued = Some((saved, encoding));
+            return Task::none();
+        }
+        match self.app_state.document_manager.get_mut(document_id) {
+            Some(document) => {
+                //
+                // Remember which state is being written, the text may be edited
+                // before the save has finished.
+                //
+                let state_id = document.history.state_id();
+                let encoding = encoding.unwrap_or(document.encoding);
+                self.saving_documents.insert(
+                    document_id,
+                    RunningSave {
+                        encoding,
+                        line_ending: document.line_ending,
+                        queued: None,
+                    },
+                );
+                Task::perform(
+                    async_save_file_to_path(
+                        document.file_path.clone(),
+                        document.text(),
+                        encoding,
+                        self.app_configuration.get_file_backup(),
+                    ),
+                    move |result| saved(document_id, state_id, result),
+                )
+            }
+            None => Task::none(),
+        }
+    }
+
+    ///
+    /// Start the save that was requested while the finished `running_save` of a
+    /// document was running, if any.
+    ///
+    fn start_queued_save(
+        &mut self,
+        document_id: DocumentId,
+        running_save: Option<RunningSave>,
+    ) -> Task<AppMessage> {
+        match running_save.and_then(|running_save| running_save.queued) {
+            Some((saved, encoding)) => self.save_document_with(document_id, saved, encoding),
+            None => Task::none(),
+        }
+    }
+
+    ///
+    /// Open the files of the last session, in the same order. Files that no longer
+    /// exist are skipped and listed in a notice.
+    ///
+    fn restore_session(&mut self) -> Task<AppMessage> {
+        let session = AppSession::load();
+        let (found, missing): (Vec<SessionDocument>, Vec<SessionDocument>) = session
+            .documents
+            .into_iter()
+            .partition(|session_document| session_document.file_path.exists());
+        if !missing.is_empty() {
+            let files = missing
+                .iter()
+                .map(|session_document| session_document.file_path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.app_state.notice = Some(t!("session_files_missing", files = files).to_string());
+        }
+        let task = found
+            .into_iter()
+            .map(|session_document| {
+                Task::perform(
+                    async_open_file_from_path(session_document.file_path.clone()),
+                    move |result| AppMessage::SessionFileOpened(result, session_document.clone()),
+                )
+            })
+            .fold(Task::none(), Task::chain)
+            .chain(Task::done(AppMessage::SessionRestored(session.active_file)));
+        match session.folder.filter(|folder| folder.is_dir()) {
+            Some(folder) => Task::batch([task, self.open_folder(folder)]),
+            None => task,
+        }
+    }
+
+    ///
+    /// Put the cursor, selection, scroll offset and syntax mode of the active
+    /// document back to where they were in the last session.
+    ///
+    fn restore_session_document(&mut self, session_document: SessionDocument) {
+        let document = self.app_state.document_manager.active_mut();
+        let (x, y) = session_document.scroll_offset;
+        document.scroll_offset = AbsoluteOffset { x, y };
+        document.syntax_mode = session_document.syntax_mode;
+        match session_document.selection_anchor {
+            Some(anchor) => {
+                select_range(&mut document.file_content, anchor, session_document.cursor)
+            }
+            None => move_cursor_to(&mut document.file_content, session_document.cursor),
+        }
+    }
+
+    ///
+    /// Handle the file of a document that was changed by another program. A
+    /// document without unsaved changes is reloaded, otherwise the user is asked.
+    ///
+    fn watched_file_loaded(&mut self, document_id: DocumentId, loaded_file: LoadedFile) {
+        if self.saving_documents.contains_key(&document_id) {
+            return;
+        }
+        let Some(document) = self.app_state.document_manager.get_mut(document_id) else {
+            return;
+        };
+        document.file_missing = false;
+        if loaded_file.modified == document.file_modified {
+            return;
+        }
+        if *loaded_file.text == document.text() {
+            //
+            // E.g. the file was touched, or written again with the same text.
+            //
+            document.file_modified = loaded_file.modified;
+            return;
+        }
+        if !document.file_dirty {
+            self.reload_document(document_id, &loaded_file);
+            return;
+        }
+        match self
+            .file_changed_prompts
+            .iter_mut()
+            .find(|prompt| prompt.document_id == document_id)
+        {
+            Some(prompt) => {
+                prompt.loaded_file = loaded_file;
+                prompt.diff_view = None;
+            }
+            None => self
+                .file_changed_prompts
+                .push(FileChangedPrompt::new(document_id, loaded_file)),
+        }
+    }
+
+    ///
+    /// Replace the text of the document with the `loaded_file`, discarding unsaved
+    /// changes. The reload is recorded in the history, so it can be undone.
+    ///
+    fn reload_document(&mut self, document_id: DocumentId, loaded_file: &LoadedFile) {
+        if let Some(document) = self.app_state.document_manager.get_mut(document_id) {
+            document.replace_text(&loaded_file.text);
+            document.encoding = loaded_file.encoding;
+            document.file_modified = loaded_file.modified;
+            document.file_missing = false;
+            document.history.mark_saved(document.history.state_id());
+            document.saved_line_ending = document.line_ending;
+            document.file_dirty = false;
+        }
+        self.refresh_find_matches();
+        self.refresh_markdown_preview();
+    }
+
+    ///
+    /// Write the snapshots of modified documents to the recovery journal, and
+    /// remove the snapshots of documents that were saved or closed.
+    ///
+    fn write_recovery_journal(&mut self) -> Task<AppMessage> {
+        let changes = self
+            .recovery_journal
+            .changes(self.app_state.document_manager.documents());
+        match changes.is_empty() {
+            true => Task::none(),
+            false => Task::perform(changes.apply(), AppMessage::RecoveryJournalWritten),
+        }
+    }
+
+    ///
+    /// Open the recovered snapshot at `index` in the recovery prompt as a modified
+    /// document. It is written to the journal of this session instead.
+    ///
+    fn restore_snapshot(&mut self, index: usize) {
+        if let Some(prompt) = &mut self.recovery_prompt
+            && index < prompt.snapshots.len()
+        {
+            prompt.compare = None;
+            let recovered = prompt.snapshots.remove(index);
+            self.app_state
+                .document_manager
+                .restore_snapshot(&recovered.snapshot);
+            recovered.remove();
+        }
+        self.close_finished_recovery_prompt();
+    }
+
+    fn close_finished_recovery_prompt(&mut self) {
+        if self
+            .recovery_prompt
+            .as_ref()
+            .is_some_and(|prompt| prompt.snapshots.is_empty())
+        {
+            self.recovery_prompt = None;
+        }
+    }
+
+    ///
+    /// Read the file of the active document again, decoding it with `encoding`.
+    /// An untitled document only remembers the encoding for saving.
+    ///
+    fn reopen_with_encoding(&mut self, encoding: TextEncoding) -> Task<AppMessage> {
+        self.app_state.error = None;
+        let document = self.app_state.document_manager.active_mut();
+        let document_id = document.id;
+        match document.file_path.clone() {
+            Some(file_path) => Task::perform(
+                async_open_file_with_encoding(file_path, encoding),
+                move |result| AppMessage::FileReopened(document_id, result),
+            ),
+            None => {
+                document.encoding = encoding;
+                Task::none()
+            }
+        }
+    }
+
+    fn open_file(&mut self) -> Task<AppMessage> {
+        Task::perform(async_open_file_from_dialog(), AppMessage::FileOpened)
+    }
+
+    fn new_file(&mut self) -> Task<AppMessage> {
+        self.app_state.document_manager.new_document();
+        self.active_document_changed()
+    }
+
+    ///
+    /// Close the document, asking the user first if it has unsaved changes.
+    ///
+    fn close_document(&mut self, document_id: DocumentId) -> Task<AppMessage> {
+        let Some(document) = self.app_state.document_manager.get_mut(document_id) else {
+            return Task::none();
+        };
+        if document.file_dirty {
+            self.app_state.document_manager.select_document(document_id);
+            self.unsaved_changes_prompt = Some(UnsavedChangesPrompt::new(
+                document_id,
+                CloseIntent::CloseDocument,
+            ));
+            return self.active_document_changed();
+        }
+        self.remember_closed_file(document_id);
+        self.app_state.document_manager.close_document(document_id);
+        self.file_changed_prompts
+            .retain(|prompt| prompt.document_id != document_id);
+        self.active_document_changed()
+            .chain(self.exit_if_wait_finished())
+    }
+
+    ///
+    /// Show `root` in the sidebar, and read its entries.
+    ///
+    fn open_folder(&mut self, root: PathBuf) -> Task<AppMessage> {
+        let root = std::path::absolute(&root).unwrap_or(root);
+        self.app_state.file_tree = Some(FileTree::new(root.clone()));
+        self.app_state.file_index = Some(FileIndex::new(root.clone()));
+        self.app_state.search_index = None;
+        let build_index = Task::perform(list_files(root.clone()), {
+            let root = root.clone();
+            move |files| AppMessage::FileIndexBuilt(root.clone(), files)
+        });
+        let load_search_index = Task::perform(
+            load_search_index(root.clone()),
+            AppMessage::SearchIndexLoaded,
+        );
+        Task::batch([
+            self.read_folder_directories([root]),
+            build_index,
+            load_search_index,
+        ])
+    }
+
+    ///
+    /// Apply the `update` to the search index of the folder at `root`. Returns
+    /// `false` if that folder is no longer open.
+    ///
+    fn update_search_index(&mut self, root: &Path, update: IndexUpdate) -> bool {
+        match &mut self.app_state.search_index {
+            Some(search_index) if search_index.root == root => {
+                Arc::make_mut(search_index).apply(update);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    ///
+    /// Save the search index in the background. If a save is running, the index is
+    /// saved again once it has finished.
+    ///
+    fn save_search_index(&mut self) -> Task<AppMessage> {
+        let Some(search_index) = &self.app_state.search_index else {
+            return Task::none();
+        };
+        if self.search_index_saving {
+            self.search_index_unsaved = true;
+            return Task::none();
+        }
+        self.search_index_saving = true;
+        self.search_index_unsaved = false;
+        Task::perform(save_search_index(Arc::clone(search_index)), |_| {
+            AppMessage::SearchIndexSaved
+        })
+    }
+
+    ///
+    /// Show quick open and focus its input. Quick open finds the files of the
+    /// folder open in the sidebar.
+    ///
+    fn open_quick_open(&mut self) -> Task<AppMessage> {
+        let Some(file_index) = &self.app_state.file_index else {
+            self.app_state.notice = Some(t!("quick_open_no_folder").to_string());
+            return Task::none();
+        };
+        let search_index = self
+            .app_state
+            .search_index
+            .as_deref()
+            .filter(|search_index| !search_index.building);
+        let quick_open_state = &mut self.app_state.quick_open_state;
+        quick_open_state.open();
+        quick_open_state.update_results(
+            file_index,
+            &self.app_configuration.recent_files,
+            search_index,
+        );
+        Task::batch([
+            text_input::focus(text_input::Id::new(UI_QUICK_OPEN_INPUT_ID)),
+            self.quick_open_selection_changed(),
+        ])
+    }
+
+    ///
+    /// Rank the files again after the index changed, keeping the selected file
+    /// selected.
+    ///
+    fn refresh_quick_open_results(&mut self) -> Task<AppMessage> {
+        let quick_open_state = &mut self.app_state.quick_open_state;
+        let Some(file_index) = &self.app_state.file_index else {
+            return Task::none();
+        };
+        if !quick_open_state.visible {
+            return Task::none();
+        }
+        let search_index = self
+            .app_state
+            .search_index
+            .as_deref()
+            .filter(|search_index| !search_index.building);
+        let selected = quick_open_state.selected_path().map(Path::to_path_buf);
+        quick_open_state.update_results(
+            file_index,
+            &self.app_configuration.recent_files,
+            search_index,
+        );
+        if let Some(index) = quick_open_state
+            .results
+            .iter()
+            .position(|(file_path, _)| Some(file_path) == selected.as_ref())
+        {
+            quick_open_state.selected = index;
+        }
+        self.quick_open_selection_changed()
+    }
+
+    ///
+    /// Keep the selected file visible in the quick open result list, and load its
+    /// preview.
+    ///
+    fn quick_open_selection_changed(&self) -> Task<AppMessage> {
+        let quick_open_state = &self.app_state.quick_open_state;
+        let y = match quick_open_state.results.len() {
+            0 | 1 => 0.0,
+            len => quick_open_state.selected as f32 / (len - 1) as f32,
+        };
+        let scroll = scrollable::snap_to(
+            scrollable::Id::new(UI_QUICK_OPEN_SCROLLABLE_ID),
+            RelativeOffset { x: 0.0, y },
+        );
+        let preview = match quick_open_state.selected_path() {
+            Some(file_path)
+                if quick_open_state
+                    .preview
+                    .as_ref()
+                    .is_none_or(|preview| preview.path != file_path) =>
+            {
+                let file_path = file_path.to_path_buf();
+                Task::perform(
+                    async_open_file_from_path(file_path.clone()),
+                    move |result| AppMessage::QuickOpenPreviewLoaded(file_path.clone(), result),
+                )
+            }
+            _ => Task::none(),
+        };
+        Task::batch([scroll, preview])
+    }
+
+    ///
+    /// Show the folder search in the sidebar and focus its input. The query starts
+    /// with the selected text, like the find bar.
+    ///
+    fn open_folder_search(&mut self) -> Task<AppMessage> {
+        if self.app_state.file_tree.is_none() {
+            self.app_state.notice = Some(t!("folder_search_no_folder").to_string());
+            return Task::none();
+        }
+        let selection = self
+            .app_state
+            .document_manager
+            .active()
+            .file_content
+            .selection()
+            .filter(|selection| !selection.is_empty() && !selection.contains('\n'));
+        let folder_search_state = &mut self.app_state.folder_search_state;
+        folder_search_state.visible = true;
+        if let Some(selection) = selection {
+            folder_search_state.query = selection;
+        }
+        let input_id = text_input::Id::new(UI_FOLDER_SEARCH_INPUT_ID);
+        Task::batch([
+            text_input::focus(input_id.clone()),
+            text_input::select_all(input_id),
+        ])
+    }
+
+    ///
+    /// Search the open folder for the query, dropping the results of the previous
+    /// search. Results are shown as each file has been searched.
+    ///
+    fn search_folder(&mut self) -> Task<AppMessage> {
+        if let Some(handle) = self.folder_search_handle.take() {
+            handle.abort();
+        }
+        let Some(file_tree) = &self.app_state.file_tree else {
+            return Task::none();
+        };
+        //
+        // Until the search index has been compared with the files of the folder,
+        // it may not know every file, so searches cannot rely on it.
+        //
+        let search_index = self
+            .app_state
+            .search_index
+            .as_deref()
+            .filter(|search_index| !search_index.building);
+        let folder_search_state = &mut self.app_state.folder_search_state;
+        if folder_search_state.query.is_empty() {
+            folder_search_state.clear();
+            return Task::none();
+        }
+        let request = match folder_search_state.request(&file_tree.root) {
+            //
+            // A search for text, not an expression, only reads the files that the
+            // search index finds.
+            //
+            Ok(mut request) => {
+                if !folder_search_state.options.regex
+                    && let Some(search_index) = search_index
+                {
+                    let query = SearchQuery::literal(
+                        &folder_search_state.query,
+                        folder_search_state.options.whole_word,
+                    );
+                    request.files = search_index
+                        .search(&query)
+                        .map(|ranked| ranked.into_iter().map(|(file_path, _)| file_path).collect());
+                }
+                request
+            }
+            Err(error) => {
+                folder_search_state.clear();
+                folder_search_state.error = Some(error);
+                return Task::none();
+            }
+        };
+        let search_id = folder_search_state.start(request.regex.clone());
+        let (task, handle) = Task::run(search_folder(request), move |event| {
+            AppMessage::FolderSearchEvent(search_id, event)
+        })
+        .abortable();
+        self.folder_search_handle = Some(handle);
+        task
+    }
+
+    ///
+    /// Make the replacement in the files found by the latest search, to show it to
+    /// the user before anything is written.
+    ///
+    fn preview_folder_replace(&self) -> Task<AppMessage> {
+        let folder_search_state = &self.app_state.folder_search_state;
+        let Some(regex) = folder_search_state.regex.clone() else {
+            return Task::none();
+        };
+        let search_id = folder_search_state.search_id;
+        Task::perform(
+            replace_in_files(
+                folder_search_state.matched_paths(),
+                regex,
+                folder_search_state.replacement.clone(),
+                folder_search_state.options.regex,
+            ),
+            move |replacements| AppMessage::FolderReplacePreviewed(search_id, replacements),
+        )
+    }
+
+    ///
+    /// Read the entries of the `directories` of the sidebar tree again, if they
+    /// were read before or are expanded.
+    ///
+    fn read_folder_directories(
+        &self,
+        directories: impl IntoIterator<Item = PathBuf>,
+    ) -> Task<AppMessage> {
+        let Some(file_tree) = &self.app_state.file_tree else {
+            return Task::none();
+        };
+        let tasks: Vec<Task<AppMessage>> = directories
+            .into_iter()
+            .filter(|directory| file_tree.is_loaded(directory) || file_tree.is_expanded(directory))
+            .map(|directory| {
+                Task::perform(read_directory(directory.clone()), move |result| {
+                    AppMessage::FolderDirectoryRead(directory.clone(), result)
+                })
+            })
+            .collect();
+        Task::batch(tasks)
+    }
+
+    ///
+    /// Show a file or directory that was created, renamed, moved or deleted from the
+    /// sidebar. A created file is opened, and open documents follow their files
+    /// when they are renamed or moved.
+    ///
+    fn file_operation_finished(&mut self, operation: FileOperation) -> Task<AppMessage> {
+        let mut directories = operation.changed_directories();
+        let mut task = Task::none();
+        match &operation {
+            FileOperation::CreatedFile(file_path) => {
+                task = Task::perform(
+                    async_open_file_from_path(file_path.clone()),
+                    AppMessage::FileOpened,
+                );
+            }
+            FileOperation::Renamed(from, to) => {
+                self.app_state.document_manager.path_renamed(from, to);
+                if let Some(file_tree) = &mut self.app_state.file_tree {
+                    file_tree.path_renamed(from, to);
+                    directories.extend(file_tree.unloaded_expanded_directories());
+                }
+            }
+            FileOperation::CreatedFolder(_) | FileOperation::Deleted(_) => {}
+        }
+        self.read_folder_directories(directories).chain(task)
+    }
+
+    ///
+    /// Keep the file of a document that is closed, so it can be reopened.
+    ///
+    fn remember_closed_file(&mut self, document_id: DocumentId) {
+        if let Some(session_document) = self
+            .app_state
+            .document_manager
+            .get(document_id)
+            .and_then(SessionDocument::of)
+        {
+            self.closed_files
+                .retain(|closed_file| closed_file.file_path != session_document.file_path);
+            self.closed_files.push(session_document);
+            if self.closed_files.len() > MAX_CLOSED_FILES {
+                self.closed_files.remove(0);
+            }
+        }
+    }
+
+    ///
+    /// Open the most recently closed file that is not open again already, with the
+    /// cursor and scroll offset it had when it was closed.
+    ///
+    fn reopen_closed_file(&mut self) -> Task<AppMessage> {
+        while let Some(session_document) = self.closed_files.pop() {
+            if self
+                .app_state
+                .document_manager
+                .find_by_path(&session_document.file_path)
+                .is_some()
+            {
+                continue;
+            }
+            self.app_state.error = None;
+            return Task::perform(
+                async_open_file_from_path(session_document.file_path.clone()),
+                move |result| AppMessage::ClosedFileReopened(result, session_document.clone()),
+            );
+        }
+        Task::none()
+    }
+
+    ///
+    /// Wait for the document to be closed before exiting, if `--wait` was given.
+    ///
+    fn add_wait_document(&mut self, document_id: DocumentId) {
+        if let Some(wait_documents) = &mut self.wait_documents
+            && !wait_documents.contains(&document_id)
+        {
+            wait_documents.push(document_id);
+        }
+    }
+
+    ///
+    /// Exit when all documents opened with `--wait` have been closed.
+    ///
+    fn exit_if_wait_finished(&mut self) -> Task<AppMessage> {
+        let document_manager = &self.app_state.document_manager;
+        match &self.wait_documents {
+            Some(wait_documents)
+                if !wait_documents.is_empty()
+                    && !wait_documents
+                        .iter()
+                        .any(|document_id| document_manager.contains(*document_id)) =>
+            {
+                self.quit()
+            }
+            _ => Task::none(),
+        }
+    }
+
+    ///
+    /// Continue what the user was doing after the prompted document was saved.
+    ///
+    fn resume_close_intent(&mut self, prompt: UnsavedChangesPrompt) -> Task<AppMessage> {
+        match prompt.intent {
+            CloseIntent::CloseDocument => self.close_document(prompt.document_id),
+            CloseIntent::QuitApplication => self.quit(),
+        }
+    }
+
+    ///
+    /// Refresh the state that belongs to the active document after switching tabs.
+    ///
+    /// The editor scrollable is shared by all tabs, so it is scrolled to the offset
+    /// saved for the active document.
+    ///
+    fn active_document_changed(&mut self) -> Task<AppMessage> {
+        self.refresh_find_matches();
+        self.refresh_markdown_preview();
+        self.app_state.preview_state.synced_line = None;
+        Task::batch([
+            scrollable::scroll_to(
+                scrollable::Id::new(UI_EDITOR_SCROLLABLE_ID),
+                self.app_state.document_manager.active().scroll_offset,
+            ),
+            self.sync_markdown_preview(),
+        ])
+    }
+
+    ///
+    /// Apply an editor `Action` to the active document. Edits are recorded in the
+    /// document history, which also tells whether the text differs from the file.
+    ///
+    fn perform_text_action(&mut self, action: Action) {
+        // reset error
+        self.app_state.error = None;
+        self.app_state.notice = None;
+        let document = self.app_state.document_manager.active_mut();
+        match action {
+            Action::Edit(edit) => {
+                document.history.perform(&mut document.file_content, edit);
+                document.file_dirty = !document.is_saved();
+                self.last_edit_at = Some(Instant::now());
+                self.refresh_find_matches();
+                self.refresh_markdown_preview();
+            }
+            action => document.file_content.perform(action),
+        }
+    }
+
+    ///
+    /// Copy the selection of the active document to the clipboard.
+    ///
+    fn copy_text(&self) -> Task<AppMessage> {
+        match self
+            .app_state
+            .document_manager
+            .active()
+            .file_content
+            .selection()
+        {
+            Some(selection) => iced::clipboard::write(selection),
+            None => Task::none(),
+        }
+    }
+
+    ///
+    /// Move the selection of the active document to the clipboard.
+    ///
+    fn cut_text(&mut self) -> Task<AppMessage> {
+        match self
+            .app_state
+            .document_manager
+            .active()
+            .file_content
+            .selection()
+        {
+            Some(selection) => {
+                self.perform_text_action(Action::Edit(Edit::Delete));
+                iced::clipboard::write(selection)
+            }
+            None => Task::none(),
+        }
+    }
+
+    fn undo(&mut self) {
+        let document = self.app_state.document_manager.active_mut();
+        if document.history.undo(&mut document.file_content) {
+            document.file_dirty = !document.is_saved();
+            self.last_edit_at = Some(Instant::now());
+            self.refresh_find_matches();
+            self.refresh_markdown_preview();
+        }
+    }
+
+    fn redo(&mut self) {
+        let document = self.app_state.document_manager.active_mut();
+        if document.history.redo(&mut document.file_content) {
+            document.file_dirty = !document.is_saved();
+            self.last_edit_at = Some(Instant::now());
+            self.refresh_find_matches();
+            self.refresh_markdown_preview();
+        }
+    }
+
+    ///
+    /// Show the find bar, optionally with the replace row, and focus the query input.
+    ///
+    /// A single-line selection becomes the new query.
+    ///
+    fn open_find_panel(&mut self, show_replace: bool) -> Task<AppMessage> {
+        let selection = self
+            .app_state
+            .document_manager
+            .active()
+            .file_content
+            .selection()
+            .filter(|selection| !selection.is_empty() && !selection.contains('\n'));
+        let find_state = &mut self.app_state.find_state;
+        find_state.visible = true;
+        find_state.show_replace = show_replace;
+        if let Some(selection) = selection {
+            find_state.query = selection;
+        }
+        self.refresh_find_matches();
+        let input_id = text_input::Id::new(UI_FIND_INPUT_ID);
+        Task::batch([
+            text_input::focus(input_id.clone()),
+            text_input::select_all(input_id),
+        ])
+    }
+
+    ///
+    /// Search the active document again if the find bar is open.
+    ///
+    fn refresh_find_matches(&mut self) {
+        if self.app_state.find_state.visible {
+            let content = &self.app_state.document_manager.active().file_content;
+            self.app_state.find_state.update_matches(content);
+        }
+    }
+
+    ///
+    /// Show or hide the Markdown preview beside the editor.
+    ///
+    fn toggle_markdown_preview(&mut self) -> Task<AppMessage> {
+        let preview_state = &mut self.app_state.preview_state;
+        preview_state.visible = !preview_state.visible;
+        preview_state.synced_line = None;
+        self.refresh_markdown_preview();
+        self.sync_markdown_preview()
+    }
+
+    ///
+    /// Render the active document again in the Markdown preview, if it is shown.
+    ///
+    fn refresh_markdown_preview(&mut self) {
+        let document = self.app_state.document_manager.active();
+        self.app_state.preview_state.update(
+            &document.file_content.text(),
+            document.file_path.clone(),
+            self.app_state.syntax_theme,
+        );
+    }
+
+    ///
+    /// Scroll the Markdown preview to the part of the document that the cursor is
+    /// in. The preview is scrolled by the same fraction of its height as the cursor
+    /// line is of the document.
+    ///
+    fn sync_markdown_preview(&mut self) -> Task<AppMessage> {
+        let preview_state = &mut self.app_state.preview_state;
+        if !preview_state.visible {
+            return Task::none();
         }
+        let content = &self.app_state.document_manager.active().file_content;
+        let (line, _) = content.cursor_position();
+        if preview_state.synced_line == Some(line) {
+            return Task::none();
+        }
+        preview_state.synced_line = Some(line);
+        let y = match content.line_count() {
+            0 | 1 => 0.0,
+            line_count => line as f32 / (line_count - 1) as f32,
+        };
+        scrollable::snap_to(
+            scrollable::Id::new(UI_MARKDOWN_PREVIEW_SCROLLABLE_ID),
+            RelativeOffset { x: 0.0, y },
+        )
     }
-}
 
-impl AppMain {
-    // ///
-    // /// Iced Application Traits:
-    // ///
-    // type Executor = iced::executor::Default;
-    // type Message = AppMessage;
-    // type Theme = Theme;
-    // type Flags = ();
-    // //type State = AppState;
+    ///
+    /// Export the active document as an HTML page, asking for the file name.
+    ///
+    fn export_html(&mut self) -> Task<AppMessage> {
+        let document = self.app_state.document_manager.active();
+        let options = HtmlExportOptions {
+            title: document
+                .file_path
+                .as_deref()
+                .and_then(Path::file_stem)
+                .map(|file_stem| file_stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| t!("untitled").to_string()),
+            theme: self.app_state.syntax_theme,
+            table_of_contents: self.app_configuration.export_table_of_contents,
+            base_directory: document
+                .file_path
+                .as_deref()
+                .and_then(Path::parent)
+                .map(Path::to_path_buf),
+        };
+        Task::perform(
+            async_export_html(
+                document.file_path.clone(),
+                document.text(),
+                options,
+                self.app_configuration.get_file_backup(),
+            ),
+            AppMessage::FileExported,
+        )
+    }
 
-    // ///
-    // /// Constructor.
-    // ///
-    // pub fn new() -> (Self, Task<AppMessage>) {
-    //     let app = AppMain::default();
-    //     (app, Task::none())
-    // }
+    ///
+    /// Export the active document as a PDF file, asking for the file name.
+    /// Markdown documents are laid out from their blocks, others as plain text.
+    ///
+    fn export_pdf(&mut self) -> Task<AppMessage> {
+        let document = self.app_state.document_manager.active();
+        let options = PdfExportOptions {
+            file_name: document.title(),
+            page_size: self.app_configuration.pdf_page_size,
+            margin: self.app_configuration.pdf_margin,
+            markdown: matches!(document.syntax_extension().as_str(), "md" | "markdown"),
+            theme: self.app_state.syntax_theme,
+        };
+        Task::perform(
+            async_export_pdf(
+                document.file_path.clone(),
+                document.text(),
+                options,
+                self.app_configuration.get_file_backup(),
+            ),
+            AppMessage::FileExported,
+        )
+    }
 
-    // pub(crate) fn get_window_settings(&mut self) -> iced::window::Settings {
-    //     self.app_configuration = AppConfiguration::load();
-    //     self.app_state.scale_factor = self.app_configuration.scale_factor;
-    //     let mut window_settings = iced::window::Settings::default();
-    //     //window_settings.size = iced::Size::new(1200.0, 1024.0);
-    //     window_settings.size = self.app_configuration.get_window_size();
-    //     window_settings.position = self.app_configuration.get_window_position();
-    // }
+    fn find_next(&mut self) -> Task<AppMessage> {
+        if !self.app_state.find_state.visible {
+            return self.open_find_panel(false);
+        }
+        let cursor = self
+            .app_state
+            .document_manager
+            .active()
+            .file_content
+            .cursor_position();
+        self.app_state.find_state.select_next(cursor);
+        self.select_current_match()
+    }
+
+    fn find_previous(&mut self) -> Task<AppMessage> {
+        if !self.app_state.find_state.visible {
+            return self.open_find_panel(false);
+        }
+        let content = &self.app_state.document_manager.active().file_content;
+        //
+        // The cursor is at the end of a selected match, so search back from its start.
+        //
+        let cursor = match self.app_state.find_state.current() {
+            Some(current) if content.selection().is_some() => current.start_position(),
+            _ => content.cursor_position(),
+        };
+        self.app_state.find_state.select_previous(cursor);
+        self.select_current_match()
+    }
 
     ///
-    /// Iced function to handle messages.
+    /// Select the current match in the editor and scroll it into view.
     ///
-    pub(crate) fn update(&mut self, message: AppMessage) -> Task<AppMessage> {
-        match message {
-            AppMessage::TextEdited(action) => {
-                // reset error
-                self.app_state.error = None;
-                self.app_state.file_dirty = self.app_state.file_dirty || action.is_edit();
-                self.app_state.file_content.perform(action);
-                Task::none()
-            }
-            AppMessage::OpenFileFromDialog => self.open_file(),
-            AppMessage::FileOpened(Ok((file_path, content))) => {
-                self.app_state.file_dirty = false;
-                self.app_state.file_path = Some(file_path);
-                self.app_state.file_content = text_editor::Content::with_text(content.as_ref());
-                Task::none()
-            }
-            AppMessage::FileOpened(Err(error)) => {
-                self.app_state.error = Some(error);
-                Task::none()
-            }
-            AppMessage::NewFile => {
-                self.new_file();
-                Task::none()
-            }
-            AppMessage::SaveFile => self.save_file(),
-            AppMessage::FileSaved(Ok(file_name)) => {
-                self.app_state.file_path = Some(file_name);
-                self.app_state.file_dirty = false;
-                Task::none()
-            }
-            AppMessage::FileSaved(Err(error)) => {
-                self.app_state.error = Some(error);
-                Task::none()
-            }
-            AppMessage::UpdateLanguage(str) => {
-                rust_i18n::set_locale(str.as_ref());
-                Task::none()
-            }
-            AppMessage::UpdateWindowTheme(theme) => {
-                self.app_state.window_theme = theme;
-                Task::none()
-            }
-            AppMessage::UpdateSyntaxTheme(theme) => {
-                self.app_state.syntax_theme = theme;
-                Task::none()
-            }
-            AppMessage::UpdateScale(value) => {
-                self.app_state.scale_factor = value;
-                self.app_configuration.scale_factor = value;
-                self.app_configuration.save();
-                Task::none()
-            }
-            AppMessage::EventOccurred(iced::Event::Keyboard(
-                iced::keyboard::Event::KeyPressed { key, modifiers, .. },
-            )) => {
-                match self
-                    .app_state
-                    .keybind_manager
-                    .get_app_action(&key, modifiers)
-                {
-                    Some(app_action) => match app_action {
-                        KeybindAction::ShowSettings => {
-                            //self.app_state.current_view = MainWindowView::Settings;
-                            self.show_app_configuration_modal = true;
-                        }
-                        KeybindAction::QuitApplication => {
-                            self.quit();
-                        }
-                        KeybindAction::CutText => {}
-                        KeybindAction::CopyText => {}
-                        KeybindAction::PasteText => {}
-                        KeybindAction::NewFile => {
-                            self.new_file();
-                        }
-                        KeybindAction::OpenFile => {
-                            return self.open_file();
-                        }
-                        KeybindAction::SaveFile => {
-                            return self.save_file();
-                        }
-                        KeybindAction::CloseFile => {}
-                    },
-                    None => {}
-                }
-                Task::none()
-            }
-            AppMessage::EventOccurred(iced::Event::Mouse(_)) => Task::none(),
-            AppMessage::EventOccurred(iced::Event::Window(event)) => {
-                println!("EVENT {:?}", event);
-                match event {
-                    Event::Moved(point) => {
-                        //
-                        // This does not currently work. The iced docs state "Note: Not available in Wayland."
-                        //
-                        // https://docs.rs/iced_core/0.13.2/iced_core/window/enum.Event.html
-                        //
-                        self.app_configuration.window_x = point.x;
-                        self.app_configuration.window_y = point.y;
-                        self.app_configuration.save();
-                    }
-                    Event::Opened { .. } => {}
-                    Event::Closed => {}
-                    Event::Resized(size) => {
-                        self.app_configuration.window_w =
-                            size.width * self.app_state.scale_factor as f32;
-                        self.app_configuration.window_h =
-                            size.height * self.app_state.scale_factor as f32;
-                        self.app_configuration.save();
-                    }
-                    Event::RedrawRequested(_) => {}
-                    Event::CloseRequested => {}
-                    Event::Focused => {}
-                    Event::Unfocused => {}
-                    Event::FileHovered(_) => {}
-                    Event::FileDropped(_) => {}
-                    Event::FilesHoveredLeft => {}
-                }
-                Task::none()
-            }
-            AppMessage::EventOccurred(iced::Event::Touch(_)) => Task::none(),
-            AppMessage::EventOccurred(iced::Event::Keyboard(
-                iced::keyboard::Event::ModifiersChanged(_),
-            )) => Task::none(),
-            AppMessage::EventOccurred(iced::Event::Keyboard(
-                iced::keyboard::Event::KeyReleased { .. },
-            )) => Task::none(),
-            // AppMessage::WindowResized(w, h) => {
-            //     self.app_configuration.window_w = w;
-            //     self.app_configuration.window_h = h;
-            //     self.app_configuration.save();
-            //     Task::none()
-            // }
-            AppMessage::SaveAppConfiguration => {
-                // if self.app_state.app_configuration_changed {
-                //     self.app_configuration.save();
-                //     self.app_state.app_configuration_changed = false;
-                // }
-                self.app_configuration.save();
-                Task::none()
-            }
-            AppMessage::TabPressed => Task::none(),
-            AppMessage::FocusChanged(_id) => Task::none(),
-            AppMessage::OpenAppConfigurationModal => {
-                self.show_app_configuration_modal = true;
-                Task::none()
-            }
-            AppMessage::CloseAppConfigurationModal => {
-                self.show_app_configuration_modal = false;
-                Task::none()
+    fn select_current_match(&mut self) -> Task<AppMessage> {
+        match self.app_state.find_state.current() {
+            Some(current) => {
+                let content = &mut self.app_state.document_manager.active_mut().file_content;
+                select_range(content, current.start_position(), current.end_position());
+                self.scroll_to_line(current.line)
             }
+            None => Task::none(),
         }
     }
 
-    fn save_file(&mut self) -> Task<AppMessage> {
-        Task::perform(
-            async_save_file_to_path(
-                self.app_state.file_path.clone(),
-                self.app_state.file_content.text().clone(),
-            ),
-            AppMessage::FileSaved,
-        )
+    ///
+    /// Replace the current match and move on to the next one.
+    ///
+    fn replace_one(&mut self) -> Task<AppMessage> {
+        let Some(current) = self.app_state.find_state.current() else {
+            return Task::none();
+        };
+        let content = &mut self.app_state.document_manager.active_mut().file_content;
+        select_range(content, current.start_position(), current.end_position());
+        let line = content
+            .line(current.line)
+            .map(|line| line.to_string())
+            .unwrap_or_default();
+        let replacement = self.app_state.find_state.replacement_for(&line, current);
+        self.replace_selection(replacement);
+        self.find_next()
     }
 
-    fn open_file(&mut self) -> Task<AppMessage> {
-        Task::perform(async_open_file_from_dialog(), AppMessage::FileOpened)
+    ///
+    /// Replace every match in the active document as a single edit.
+    ///
+    fn replace_all(&mut self) {
+        let content = &self.app_state.document_manager.active().file_content;
+        let Some((text, _count)) = self.app_state.find_state.replace_all(content) else {
+            return;
+        };
+        let cursor = content.cursor_position();
+        self.perform_text_action(Action::SelectAll);
+        self.replace_selection(text);
+        let content = &mut self.app_state.document_manager.active_mut().file_content;
+        move_cursor_to(content, cursor);
     }
 
-    fn new_file(&mut self) {
-        if self.app_state.file_dirty {
-            //
-            // TODO: Show error dialog.
-            //
-            println!("File Modified");
+    ///
+    /// Replace the selection in the active document with `text`.
+    ///
+    fn replace_selection(&mut self, text: String) {
+        if text.is_empty() {
+            self.perform_text_action(Action::Edit(Edit::Backspace));
         } else {
-            self.app_state.file_dirty = false;
-            self.app_state.file_path = None;
-            self.app_state.file_content = text_editor::Content::new();
+            self.perform_text_action(Action::Edit(Edit::Paste(Arc::new(text))));
         }
     }
 
+    ///
+    /// Scroll the editor so that `line` is visible.
+    ///
+    fn scroll_to_line(&self, line: usize) -> Task<AppMessage> {
+        let offset = self.app_state.document_manager.active().scroll_offset;
+        let line_top = line as f32 * UI_EDITOR_LINE_HEIGHT;
+        let visible = line_top >= offset.y
+            && line_top + UI_EDITOR_LINE_HEIGHT <= offset.y + self.editor_viewport_height;
+        if visible {
+            return Task::none();
+        }
+        scrollable::scroll_to(
+            scrollable::Id::new(UI_EDITOR_SCROLLABLE_ID),
+            AbsoluteOffset {
+                x: offset.x,
+                y: (line_top - self.editor_viewport_height / 2.0).max(0.0),
+            },
+        )
+    }
+
     ///
     /// Iced function to get the window title.
     ///
     pub(crate) fn title(&self) -> String {
-        t!("app_name").to_string()
+        format!(
+            "{} - {}",
+            self.app_state.document_manager.active().title(),
+            t!("app_name")
+        )
     }
 
     // pub(crate) fn window(&self) -> iced::window::Settings {
@@ -282,44 +2724,184 @@ impl AppMain {
     /// Iced function to render the view.
     ///
     pub(crate) fn view(&self) -> Element<'_, AppMessage> {
-        let file_extension = self
-            .app_state
-            .file_path
-            .as_ref()
-            .and_then(|path| path.extension()?.to_str())
-            .unwrap_or("md") // TODO: App config setting for default file extension.
-            .to_string();
-        let editor = text_editor(&self.app_state.file_content)
-            .highlight(file_extension.as_str(), self.app_state.syntax_theme)
+        let document = self.app_state.document_manager.active();
+        let file_extension = document.syntax_extension();
+        let keybind_manager = &self.app_state.keybind_manager;
+        let recording_keybind = self.keybinding_editor.recording.is_some();
+        let highlighter_settings = FindHighlighterSettings {
+            syntax: iced::highlighter::Settings {
+                theme: self.app_state.syntax_theme,
+                token: file_extension,
+            },
+            pattern: self.app_state.find_state.pattern(),
+            current_match: self.app_state.find_state.current(),
+        };
+        let editor = text_editor(&document.file_content)
+            .highlight_with::<FindHighlighter>(highlighter_settings, FindHighlighter::to_format)
             .on_action(AppMessage::TextEdited)
+            .key_binding(move |key_press: KeyPress| {
+                //
+                // Let the application handle key presses that are bound to an action,
+                // and every key press while the keybinding editor is recording.
+                //
+                if recording_keybind {
+                    return None;
+                }
+                match keybind_manager.get_app_action(&key_press.key, key_press.modifiers) {
+                    Some(_) => None,
+                    None => Binding::from_key_press(key_press),
+                }
+            })
             .font(self.app_state.font_monospaced.unwrap_or(Font::MONOSPACE));
         let scrollable_container = iced::widget::Scrollable::new(editor)
+            .id(scrollable::Id::new(UI_EDITOR_SCROLLABLE_ID))
+            .on_scroll(AppMessage::EditorScrolled)
             .width(Length::Fill)
             .height(Length::Fill);
         //.style(iced::widget::container::bordered_box);
 
         //
-        // [ TOOLBAR   ]
-        // [ EDITOR    ]
-        // [ STATUSBAR ]
+        // [ TOOLBAR             ]
+        // [ SIDEBAR | TABS      ] (sidebar optional)
+        // [         | FIND      ] (optional)
+        // [         | EDITOR    | PREVIEW ] (preview optional)
+        // [ STATUSBAR           ]
+        //
+        let findbar = self
+            .app_state
+            .find_state
+            .visible
+            .then(|| self.findbar.view(&self.app_state));
+        //
+        // The Markdown preview is shown beside the editor, below the tabs.
         //
+        let editor_area: Element<'_, AppMessage> = match self.app_state.preview_state.visible {
+            true => row![
+                scrollable_container,
+                vertical_rule(1),
+                self.markdown_preview.view(&self.app_state),
+            ]
+            .into(),
+            false => scrollable_container.into(),
+        };
+        let editor_contents = column![self.tabbar.view(&self.app_state)]
+            .push_maybe(findbar)
+            .push(editor_area);
+        let main_contents: Element<'_, AppMessage> = match &self.app_state.file_tree {
+            Some(file_tree) => {
+                let sidebar_contents = match self.app_state.folder_search_state.visible {
+                    true => self.search_panel.view(&self.app_state, &file_tree.root),
+                    false => self.sidebar.view(file_tree, document.file_path.as_deref()),
+                };
+                let sidebar = container(sidebar_contents)
+                    .width(self.app_configuration.sidebar_width)
+                    .height(Length::Fill)
+                    .padding(UR_LEFT_SIDEBAR_PADDING)
+                    .style(AppStyle::style_sidebar);
+                let divider = mouse_area(
+                    container(vertical_rule(1))
+                        .center_x(UI_SIDEBAR_DIVIDER_WIDTH)
+                        .height(Length::Fill),
+                )
+                .on_press(AppMessage::SidebarResizeStarted)
+                .interaction(iced::mouse::Interaction::ResizingHorizontally);
+                row![sidebar, divider, editor_contents].into()
+            }
+            None => editor_contents.into(),
+        };
         let base_contents = container(column![
-            self.toolbar.view(&self.app_state),
-            scrollable_container,
+            self.toolbar
+                .view(&self.app_state, &self.app_configuration.recent_files),
+            main_contents,
             self.statusbar.view(&self.app_state),
         ])
         .padding(0);
 
-        if self.show_app_configuration_modal {
+        let contents = if let Some(prompt) = &self.recovery_prompt {
+            let on_press_event = match prompt.compare {
+                Some(_) => AppMessage::RecoveryCompareClosed,
+                None => AppMessage::RecoveryLater,
+            };
+            AppMain::modal(
+                base_contents,
+                prompt.view(self.app_state.font_monospaced.unwrap_or(Font::MONOSPACE)),
+                on_press_event,
+            )
+        } else if let Some((prompt, document)) =
+            self.file_changed_prompts.first().and_then(|prompt| {
+                let document = self.app_state.document_manager.get(prompt.document_id)?;
+                Some((prompt, document))
+            })
+        {
+            let on_press_event = match prompt.diff_view {
+                Some(_) => AppMessage::FileChangedHideDiff,
+                None => AppMessage::FileChangedKeepMine,
+            };
+            AppMain::modal(
+                base_contents,
+                prompt.view(
+                    document,
+                    self.app_state.font_monospaced.unwrap_or(Font::MONOSPACE),
+                ),
+                on_press_event,
+            )
+        } else if let Some((file_tree, replace_preview)) =
+            self.app_state.file_tree.as_ref().and_then(|file_tree| {
+                let replace_preview = self
+                    .app_state
+                    .folder_search_state
+                    .replace_preview
+                    .as_ref()?;
+                Some((file_tree, replace_preview))
+            })
+        {
+            AppMain::modal(
+                base_contents,
+                AppSearchPanel::replace_preview_view(
+                    replace_preview,
+                    &file_tree.root,
+                    self.app_state.font_monospaced.unwrap_or(Font::MONOSPACE),
+                ),
+                AppMessage::FolderReplaceCancel,
+            )
+        } else if let Some((file_tree, path)) =
+            self.app_state.file_tree.as_ref().and_then(|file_tree| {
+                let path = file_tree.delete_prompt.as_ref()?;
+                Some((file_tree, path))
+            })
+        {
+            AppMain::modal(
+                base_contents,
+                AppSidebar::delete_prompt_view(file_tree, path),
+                AppMessage::FileTreeDeleteCancel,
+            )
+        } else if let Some(prompt) = self.unsaved_changes_prompt.filter(|prompt| !prompt.saving) {
+            let document = self.app_state.document_manager.active();
+            AppMain::modal(
+                base_contents,
+                prompt.view(document),
+                AppMessage::UnsavedChangesCancel,
+            )
+        } else if self.app_state.palette_state.visible {
+            AppMain::modal(
+                base_contents,
+                self.command_palette.view(&self.app_state),
+                AppMessage::CloseCommandPalette,
+            )
+        } else if self.app_state.quick_open_state.visible {
+            AppMain::modal(
+                base_contents,
+                self.quick_open.view(&self.app_state),
+                AppMessage::CloseQuickOpen,
+            )
+        } else if self.show_app_configuration_modal {
             let modal_contents = container(
                 column![
-                    iced::widget::text("Settings"),
-                    iced::widget::text("Setting 1"),
-                    iced::widget::text("Setting 2"),
-                    iced::widget::text("Setting 3"),
+                    iced::widget::text(t!("app_configuration")).size(20),
+                    self.keybinding_editor.view(&self.app_state),
                     row![
                         horizontal_space(),
-                        iced::widget::button(iced::widget::text("OK"))
+                        iced::widget::button(iced::widget::text(t!("ok")))
                             .on_press(AppMessage::CloseAppConfigurationModal),
                     ]
                 ]
@@ -336,9 +2918,40 @@ impl AppMain {
             )
         } else {
             base_contents.into()
+        };
+
+        if self.files_hovered {
+            AppMain::drop_overlay(contents)
+        } else {
+            contents
         }
     }
 
+    ///
+    /// Show a hint over the `contents` of the window while files are dragged over it.
+    ///
+    fn drop_overlay(contents: Element<'_, AppMessage>) -> Element<'_, AppMessage> {
+        stack![
+            contents,
+            center(text(t!("drop_files_to_open")).size(UI_DROP_OVERLAY_TEXT_SIZE)).style(
+                |theme: &Theme| {
+                    let palette = theme.extended_palette();
+                    container::Style {
+                        background: Some(palette.background.base.color.scale_alpha(0.85).into()),
+                        border: iced::Border {
+                            color: palette.primary.strong.color,
+                            width: 4.0,
+                            radius: 0.into(),
+                        },
+                        text_color: Some(palette.primary.strong.color),
+                        ..container::Style::default()
+                    }
+                }
+            )
+        ]
+        .into()
+    }
+
     ///
     /// Iced function to get the Theme.
     ///
@@ -363,14 +2976,146 @@ impl AppMain {
         // })
         let mut subscriptions = Vec::new();
         subscriptions.push(iced::event::listen().map(AppMessage::EventOccurred));
+        if self.app_state.quick_open_state.visible {
+            subscriptions.push(iced::event::listen_with(
+                |event, _status, _window| match event {
+                    iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                        key: iced::keyboard::Key::Named(named),
+                        ..
+                    }) => match named {
+                        Named::Escape => Some(AppMessage::CloseQuickOpen),
+                        Named::ArrowDown => Some(AppMessage::QuickOpenSelectNext),
+                        Named::ArrowUp => Some(AppMessage::QuickOpenSelectPrevious),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+            ));
+        }
+        if self.app_state.palette_state.visible {
+            //
+            // The palette input captures Escape, so listen for captured events too.
+            //
+            subscriptions.push(iced::event::listen_with(
+                |event, _status, _window| match event {
+                    iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                        key: iced::keyboard::Key::Named(named),
+                        ..
+                    }) => match named {
+                        Named::Escape => Some(AppMessage::CloseCommandPalette),
+                        Named::ArrowDown => Some(AppMessage::CommandPaletteSelectNext),
+                        Named::ArrowUp => Some(AppMessage::CommandPaletteSelectPrevious),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+            ));
+        }
+        //
+        // The cursor leaves the divider while it is dragged, and the editor captures
+        // the mouse events, so listen for captured events too.
+        //
+        if self.sidebar_resizing {
+            subscriptions.push(iced::event::listen_with(
+                |event, _status, _window| match event {
+                    iced::Event::Mouse(iced::mouse::Event::CursorMoved { position }) => {
+                        Some(AppMessage::SidebarResized(position.x))
+                    }
+                    iced::Event::Mouse(iced::mouse::Event::ButtonReleased(
+                        iced::mouse::Button::Left,
+                    )) => Some(AppMessage::SidebarResizeFinished),
+                    _ => None,
+                },
+            ));
+        }
+        //
+        // Notice files that are added, removed or renamed in the open folder.
+        //
+        if let Some(file_tree) = &self.app_state.file_tree {
+            subscriptions.push(watch_folder(file_tree.root.clone()));
+        }
+        //
+        // Check for a pause in editing, and hide the autosave notice again.
+        //
+        let autosave_pending = self.last_edit_at.is_some()
+            && self.app_configuration.autosave_mode == AutosaveMode::AfterDelay;
+        if autosave_pending || self.app_state.autosaved_at.is_some() {
+            subscriptions.push(iced::time::every(AUTOSAVE_TICK).map(AppMessage::AutosaveTick));
+        }
+        //
+        // Notice changes to the open files made by other programs.
+        //
+        let mut file_paths: Vec<PathBuf> = self
+            .app_state
+            .document_manager
+            .documents()
+            .iter()
+            .filter_map(|document| document.file_path.clone())
+            .collect();
+        if !file_paths.is_empty() {
+            file_paths.sort();
+            file_paths.dedup();
+            subscriptions.push(watch_files(file_paths));
+        }
+        //
+        // Keep the recovery journal up to date while documents have unsaved changes.
+        //
+        let has_dirty_documents = self
+            .app_state
+            .document_manager
+            .documents()
+            .iter()
+            .any(|document| document.file_dirty);
+        if has_dirty_documents || self.recovery_journal.has_snapshots() {
+            subscriptions
+                .push(iced::time::every(RECOVERY_INTERVAL).map(|_| AppMessage::RecoveryTick));
+        }
         Subscription::batch(subscriptions)
     }
 
     ///
-    /// Exit the application process.
+    /// Exit the application, asking the user about each document with unsaved
+    /// changes first. The prompts are shown one at a time; `quit()` is called
+    /// again after each document is saved or discarded.
     ///
-    fn quit(&self) {
-        std::process::exit(0);
+    fn quit(&mut self) -> Task<AppMessage> {
+        let dirty_document_id = self
+            .app_state
+            .document_manager
+            .documents()
+            .iter()
+            .find(|document| document.file_dirty)
+            .map(|document| document.id);
+        match dirty_document_id {
+            Some(document_id) => {
+                self.app_state.document_manager.select_document(document_id);
+                self.unsaved_changes_prompt = Some(UnsavedChangesPrompt::new(
+                    document_id,
+                    CloseIntent::QuitApplication,
+                ));
+                self.active_document_changed()
+            }
+            None => {
+                //
+                // Nothing is left to recover after a normal exit. The session of an
+                // editor started with `--wait`, e.g. for a commit message, would
+                // replace the session the user works with.
+                //
+                self.recovery_journal.remove_session();
+                if self.wait_documents.is_some() {
+                    return iced::exit();
+                }
+                let session = AppSession {
+                    folder: self
+                        .app_state
+                        .file_tree
+                        .as_ref()
+                        .map(|file_tree| file_tree.root.clone()),
+                    ..AppSession::of(&self.app_state.document_manager)
+                };
+                Task::future(session.save()).discard().chain(iced::exit())
+            }
+        }
     }
 
     ///
@@ -0,0 +1,79 @@
+//!
+//! Notespace-Editor
+//!
+//! Syntax highlighting of the code blocks of a Markdown document, with the same
+//! highlighter and theme as the editor.
+//!
+
+use iced::Color;
+use iced::advanced::text::Highlighter;
+use iced::highlighter::{self, Theme};
+
+///
+/// The parts of a line of code, each with the color of its syntax, or `None` for
+/// the default text color.
+///
+pub type HighlightedLine = Vec<(String, Option<Color>)>;
+
+///
+/// Highlight `code` as the `language` of a fenced code block, e.g. `rust`, or as
+/// plain text if the language is unknown.
+///
+pub fn highlight_code(code: &str, language: &str, theme: Theme) -> Vec<HighlightedLine> {
+    let mut highlighter = highlighter::Highlighter::new(&highlighter::Settings {
+        theme,
+        token: language.to_string(),
+    });
+    code.lines()
+        .map(|line| {
+            let mut parts: HighlightedLine = Vec::new();
+            let mut position = 0;
+            for (range, highlight) in highlighter.highlight_line(line) {
+                if range.start > position {
+                    parts.push((line[position..range.start].to_string(), None));
+                }
+                let color = highlight.color();
+                match parts.last_mut() {
+                    Some((text, last_color)) if *last_color == color => {
+                        text.push_str(&line[range.clone()])
+                    }
+                    _ => parts.push((line[range.clone()].to_string(), color)),
+                }
+                position = range.end;
+            }
+            if position < line.len() {
+                parts.push((line[position..].to_string(), None));
+            }
+            parts
+        })
+        .collect()
+}
+
+///
+/// The background and default text color of `theme`, which its syntax colors
+/// are chosen for.
+///
+pub fn code_colors(theme: Theme) -> (Color, Color) {
+    match theme {
+        Theme::SolarizedDark => (
+            Color::from_rgb8(0x00, 0x2b, 0x36),
+            Color::from_rgb8(0x83, 0x94, 0x96),
+        ),
+        Theme::Base16Mocha => (
+            Color::from_rgb8(0x3b, 0x32, 0x28),
+            Color::from_rgb8(0xd0, 0xc8, 0xc6),
+        ),
+        Theme::Base16Ocean => (
+            Color::from_rgb8(0x2b, 0x30, 0x3b),
+            Color::from_rgb8(0xc0, 0xc5, 0xce),
+        ),
+        Theme::Base16Eighties => (
+            Color::from_rgb8(0x2d, 0x2d, 0x2d),
+            Color::from_rgb8(0xd3, 0xd0, 0xc8),
+        ),
+        Theme::InspiredGitHub => (
+            Color::from_rgb8(0xff, 0xff, 0xff),
+            Color::from_rgb8(0x32, 0x32, 0x32),
+        ),
+    }
+}
@@ -0,0 +1,420 @@
+//!
+//! Notespace-Editor
+//!
+//! Export of a Markdown document to a standalone HTML file.
+//!
+//! The file has no references to other files: the style sheet is part of the page,
+//! and local images are embedded as `data:` URLs. Images on the web stay links.
+//! Code blocks are highlighted with the colors of the syntax theme, as in the
+//! preview.
+//!
+
+use crate::app_io::{
+    AppIOError, FileBackup, SavedFile, async_choose_export_path, async_save_file_to_path,
+};
+use crate::document::text_encoding::TextEncoding;
+use crate::markdown::code_highlight::{HighlightedLine, code_colors};
+use crate::markdown::markdown_document::{
+    Inlines, ListItem, MarkdownBlock, MarkdownDocument, inlines_text, local_image_path,
+};
+use base64::Engine;
+use iced::Color;
+use iced::highlighter::Theme;
+use pulldown_cmark::Alignment;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct HtmlExportOptions {
+    ///
+    /// The title of the page, e.g. the name of the file.
+    ///
+    pub(crate) title: String,
+    pub(crate) theme: Theme,
+    ///
+    /// Start the page with a list of links to its headings.
+    ///
+    pub(crate) table_of_contents: bool,
+    ///
+    /// The directory of the document, which relative image paths are resolved
+    /// against.
+    ///
+    pub(crate) base_directory: Option<PathBuf>,
+}
+
+///
+/// Render `text` as Markdown and save it as an HTML file next to `file_path`,
+/// asking the user for the file name. The file is written the same way as a
+/// document, keeping the previous version as configured by `file_backup`.
+///
+pub async fn async_export_html(
+    file_path: Option<PathBuf>,
+    text: String,
+    options: HtmlExportOptions,
+    file_backup: FileBackup,
+) -> Result<SavedFile, AppIOError> {
+    let path = async_choose_export_path(file_path, "html").await?;
+    //
+    // Highlighting and reading the images can take a while for a long document.
+    //
+    let html = tokio::task::spawn_blocking(move || {
+        let document = MarkdownDocument::parse(&text, options.theme);
+        render_html(&document, &options)
+    })
+    .await
+    .map_err(|_| AppIOError::IOFailedError(ErrorKind::Other))?;
+    async_save_file_to_path(Some(path), html, TextEncoding::default(), file_backup).await
+}
+
+///
+/// The complete HTML page of `document`.
+///
+pub fn render_html(document: &MarkdownDocument, options: &HtmlExportOptions) -> String {
+    let (code_background, code_foreground) = code_colors(options.theme);
+    let mut writer = HtmlWriter {
+        html: String::new(),
+        base_directory: options.base_directory.as_deref(),
+        code_foreground,
+        headings: Vec::new(),
+        anchors: HashMap::new(),
+    };
+    writer.blocks(&document.blocks);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(&options.title));
+    let _ = writeln!(
+        html,
+        "<style>\n{}\npre, code {{ background: {}; color: {}; }}\n</style>",
+        STYLE_SHEET,
+        css_color(code_background),
+        css_color(code_foreground)
+    );
+    html.push_str("</head>\n<body>\n");
+    if options.table_of_contents && !writer.headings.is_empty() {
+        html.push_str(&table_of_contents(&writer.headings));
+    }
+    html.push_str(&writer.html);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const STYLE_SHEET: &str = "body { max-width: 52em; margin: 2em auto; padding: 0 1em; \
+font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; line-height: 1.5; \
+color: #24292f; }
+h1, h2 { border-bottom: 1px solid #d0d7de; padding-bottom: 0.3em; }
+a { color: #0969da; }
+pre { padding: 1em; border-radius: 4px; overflow-x: auto; line-height: 1.4; }
+code { padding: 0.1em 0.3em; border-radius: 3px; font-family: 'Fira Code', Consolas, monospace; \
+font-size: 0.9em; }
+pre code { padding: 0; background: none; }
+blockquote { margin: 0; padding: 0 1em; border-left: 4px solid #d0d7de; color: #57606a; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d7de; padding: 0.3em 0.7em; }
+img { max-width: 100%; }
+li.task { list-style: none; }
+li.task input { margin: 0 0.4em 0 -1.4em; }
+li > p { margin: 0; }
+nav.toc { border-bottom: 1px solid #d0d7de; margin-bottom: 1em; }
+nav.toc li { list-style: none; }";
+
+///
+/// A heading of the document, for the table of contents.
+///
+struct Heading {
+    level: u8,
+    anchor: String,
+    text: String,
+}
+
+struct HtmlWriter<'a> {
+    html: String,
+    base_directory: Option<&'a Path>,
+    code_foreground: Color,
+    headings: Vec<Heading>,
+    ///
+    /// How often each anchor was used, so the anchors of headings with the same
+    /// text are unique.
+    ///
+    anchors: HashMap<String, usize>,
+}
+
+impl HtmlWriter<'_> {
+    fn blocks(&mut self, blocks: &[MarkdownBlock]) {
+        for block in blocks {
+            self.block(block);
+        }
+    }
+
+    fn block(&mut self, block: &MarkdownBlock) {
+        match block {
+            MarkdownBlock::Heading { level, content } => {
+                let level = (*level).clamp(1, 6);
+                let text = inlines_text(content);
+                let anchor = self.anchor(&text);
+                let _ = write!(self.html, "<h{} id=\"{}\">", level, anchor);
+                self.inlines(content);
+                let _ = writeln!(self.html, "</h{}>", level);
+                self.headings.push(Heading {
+                    level,
+                    anchor,
+                    text,
+                });
+            }
+            MarkdownBlock::Paragraph(content) => {
+                self.html.push_str("<p>");
+                self.inlines(content);
+                self.html.push_str("</p>\n");
+            }
+            MarkdownBlock::CodeBlock(lines) => self.code_block(lines),
+            MarkdownBlock::List { start, items } => {
+                match start {
+                    Some(1) => self.html.push_str("<ol>\n"),
+                    Some(start) => {
+                        let _ = writeln!(self.html, "<ol start=\"{}\">", start);
+                    }
+                    None => self.html.push_str("<ul>\n"),
+                }
+                for item in items {
+                    self.list_item(item);
+                }
+                match start {
+                    Some(_) => self.html.push_str("</ol>\n"),
+                    None => self.html.push_str("</ul>\n"),
+                }
+            }
+            MarkdownBlock::Quote(blocks) => {
+                self.html.push_str("<blockquote>\n");
+                self.blocks(blocks);
+                self.html.push_str("</blockquote>\n");
+            }
+            MarkdownBlock::Table {
+                alignments,
+                header,
+                rows,
+            } => self.table(alignments, header, rows),
+            MarkdownBlock::Image { url, alt } => {
+                let source = local_image_path(url, self.base_directory)
+                    .and_then(|path| image_data_url(&path))
+                    .unwrap_or_else(|| url.clone());
+                let _ = writeln!(
+                    self.html,
+                    "<p><img src=\"{}\" alt=\"{}\"></p>",
+                    escape(&source),
+                    escape(alt)
+                );
+            }
+            MarkdownBlock::Rule => self.html.push_str("<hr>\n"),
+        }
+    }
+
+    fn inlines(&mut self, inlines: &Inlines) {
+        for inline_text in inlines {
+            let style = &inline_text.style;
+            let mut tags = Vec::new();
+            if let Some(link) = &style.link {
+                let _ = write!(self.html, "<a href=\"{}\">", escape(link));
+                tags.push("a");
+            }
+            for (enabled, tag) in [
+                (style.bold, "strong"),
+                (style.italic, "em"),
+                (style.strikethrough, "del"),
+                (style.code, "code"),
+            ] {
+                if enabled {
+                    let _ = write!(self.html, "<{}>", tag);
+                    tags.push(tag);
+                }
+            }
+            let text = escape(&inline_text.text).replace('\n', "<br>\n");
+            self.html.push_str(&text);
+            for tag in tags.iter().rev() {
+                let _ = write!(self.html, "</{}>", tag);
+            }
+        }
+    }
+
+    fn code_block(&mut self, lines: &[HighlightedLine]) {
+        self.html.push_str("<pre><code>");
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                self.html.push('\n');
+            }
+            for (part, color) in line {
+                match color {
+                    Some(color) if *color != self.code_foreground => {
+                        let _ = write!(
+                            self.html,
+                            "<span style=\"color: {}\">{}</span>",
+                            css_color(*color),
+                            escape(part)
+                        );
+                    }
+                    _ => self.html.push_str(&escape(part)),
+                }
+            }
+        }
+        self.html.push_str("</code></pre>\n");
+    }
+
+    fn list_item(&mut self, item: &ListItem) {
+        match item.task {
+            Some(done) => {
+                let checked = if done { " checked" } else { "" };
+                let _ = write!(
+                    self.html,
+                    "<li class=\"task\"><input type=\"checkbox\" disabled{}>",
+                    checked
+                );
+            }
+            None => self.html.push_str("<li>"),
+        }
+        //
+        // The text of an item in a tight list is a single paragraph, which is
+        // written without `<p>` to keep the items close together.
+        //
+        match item.blocks.as_slice() {
+            [MarkdownBlock::Paragraph(content)] => self.inlines(content),
+            blocks => {
+                self.html.push('\n');
+                self.blocks(blocks);
+            }
+        }
+        self.html.push_str("</li>\n");
+    }
+
+    fn table(&mut self, alignments: &[Alignment], header: &[Inlines], rows: &[Vec<Inlines>]) {
+        self.html.push_str("<table>\n<thead>\n");
+        self.table_row(alignments, header, "th");
+        self.html.push_str("</thead>\n<tbody>\n");
+        for cells in rows {
+            self.table_row(alignments, cells, "td");
+        }
+        self.html.push_str("</tbody>\n</table>\n");
+    }
+
+    fn table_row(&mut self, alignments: &[Alignment], cells: &[Inlines], tag: &str) {
+        self.html.push_str("<tr>");
+        for (index, cell) in cells.iter().enumerate() {
+            match alignments.get(index) {
+                Some(Alignment::Left) => {
+                    let _ = write!(self.html, "<{} style=\"text-align: left\">", tag);
+                }
+                Some(Alignment::Center) => {
+                    let _ = write!(self.html, "<{} style=\"text-align: center\">", tag);
+                }
+                Some(Alignment::Right) => {
+                    let _ = write!(self.html, "<{} style=\"text-align: right\">", tag);
+                }
+                _ => {
+                    let _ = write!(self.html, "<{}>", tag);
+                }
+            }
+            self.inlines(cell);
+            let _ = write!(self.html, "</{}>", tag);
+        }
+        self.html.push_str("</tr>\n");
+    }
+
+    ///
+    /// A unique anchor for a heading with `text`, e.g. `getting-started` for
+    /// "Getting Started", and `getting-started-1` for the second one.
+    ///
+    fn anchor(&mut self, text: &str) -> String {
+        let mut anchor = String::new();
+        for c in text.trim().chars() {
+            if c.is_alphanumeric() {
+                anchor.extend(c.to_lowercase());
+            } else if (c.is_whitespace() || c == '-') && !anchor.ends_with('-') {
+                anchor.push('-');
+            }
+        }
+        let anchor = match anchor.trim_matches('-') {
+            "" => "section".to_string(),
+            anchor => anchor.to_string(),
+        };
+        let count = self.anchors.entry(anchor.clone()).or_insert(0);
+        *count += 1;
+        match *count {
+            1 => anchor,
+            count => format!("{}-{}", anchor, count - 1),
+        }
+    }
+}
+
+///
+/// A nested list of links to the `headings`, indented by their level relative to
+/// the highest level.
+///
+fn table_of_contents(headings: &[Heading]) -> String {
+    let top_level = headings
+        .iter()
+        .map(|heading| heading.level)
+        .min()
+        .unwrap_or(1);
+    let mut html = String::from("<nav class=\"toc\">\n<ul>\n");
+    for heading in headings {
+        let indent = (heading.level - top_level) as f32 * 1.5;
+        let _ = writeln!(
+            html,
+            "<li style=\"margin-left: {}em\"><a href=\"#{}\">{}</a></li>",
+            indent,
+            heading.anchor,
+            escape(&heading.text)
+        );
+    }
+    html.push_str("</ul>\n</nav>\n");
+    html
+}
+
+///
+/// The image at `path` as a `data:` URL, or `None` if it cannot be read or is not
+/// an image type browsers show.
+///
+fn image_data_url(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    let mime_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        _ => return None,
+    };
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+fn css_color(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+///
+/// `text` with the characters that have a meaning in HTML replaced by entities,
+/// for both text and attribute values.
+///
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,387 @@
+//!
+//! Notespace-Editor
+//!
+//! A Markdown document parsed into blocks, for the preview and the exports.
+//!
+//! The text is parsed with `pulldown-cmark`, with the tables, task lists and
+//! strikethrough extensions. Inline content is kept as runs of text with a style,
+//! so it can be shown as rich text. Images are blocks of their own, as they cannot
+//! be part of a line of rich text.
+//!
+
+use crate::markdown::code_highlight::{HighlightedLine, highlight_code};
+use iced::highlighter::Theme;
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InlineStyle {
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    pub(crate) strikethrough: bool,
+    pub(crate) code: bool,
+    ///
+    /// The destination of a link.
+    ///
+    pub(crate) link: Option<String>,
+}
+
+///
+/// A run of text with the same style.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineText {
+    pub(crate) text: String,
+    pub(crate) style: InlineStyle,
+}
+
+pub type Inlines = Vec<InlineText>;
+
+///
+/// The text of `inlines` without its style, e.g. for the table of contents.
+///
+pub fn inlines_text(inlines: &Inlines) -> String {
+    inlines
+        .iter()
+        .map(|inline_text| inline_text.text.as_str())
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ListItem {
+    ///
+    /// `Some` for an item of a task list, `true` if the task is done.
+    ///
+    pub(crate) task: Option<bool>,
+    pub(crate) blocks: Vec<MarkdownBlock>,
+}
+
+#[derive(Debug, Clone)]
+pub enum MarkdownBlock {
+    Heading {
+        level: u8,
+        content: Inlines,
+    },
+    Paragraph(Inlines),
+    CodeBlock(Vec<HighlightedLine>),
+    List {
+        ///
+        /// The number of the first item of an ordered list.
+        ///
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    Quote(Vec<MarkdownBlock>),
+    Table {
+        alignments: Vec<Alignment>,
+        header: Vec<Inlines>,
+        rows: Vec<Vec<Inlines>>,
+    },
+    Image {
+        url: String,
+        alt: String,
+    },
+    Rule,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownDocument {
+    pub(crate) blocks: Vec<MarkdownBlock>,
+}
+
+impl MarkdownDocument {
+    ///
+    /// Parse `text`, highlighting code blocks with the syntax `theme`.
+    ///
+    pub fn parse(text: &str, theme: Theme) -> Self {
+        let options =
+            Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+        let mut builder = DocumentBuilder {
+            theme,
+            containers: vec![Container::Blocks(Vec::new())],
+            inlines: Vec::new(),
+            style: InlineStyle::default(),
+            code: None,
+            image: None,
+            table: None,
+            heading: None,
+        };
+        for event in Parser::new_ext(text, options) {
+            builder.push(event);
+        }
+        builder.finish()
+    }
+}
+
+///
+/// The path of the local image at `url`, relative to the `base_directory` of the
+/// document, or `None` for an image on the web.
+///
+pub fn local_image_path(url: &str, base_directory: Option<&Path>) -> Option<PathBuf> {
+    if url.contains("://") {
+        return url.strip_prefix("file://").map(PathBuf::from);
+    }
+    let path = PathBuf::from(url);
+    match base_directory {
+        Some(base_directory) if path.is_relative() => Some(base_directory.join(path)),
+        _ => Some(path),
+    }
+}
+
+///
+/// A block that contains other blocks, while its content is parsed.
+///
+enum Container {
+    ///
+    /// The document, or a block quote.
+    ///
+    Blocks(Vec<MarkdownBlock>),
+    List {
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    Item(ListItem),
+}
+
+#[derive(Default)]
+struct TableBuilder {
+    alignments: Vec<Alignment>,
+    header: Vec<Inlines>,
+    rows: Vec<Vec<Inlines>>,
+    row: Vec<Inlines>,
+}
+
+struct DocumentBuilder {
+    theme: Theme,
+    containers: Vec<Container>,
+    ///
+    /// The inline content of the paragraph, heading or table cell being parsed.
+    ///
+    inlines: Inlines,
+    style: InlineStyle,
+    ///
+    /// The language and text of the code block being parsed.
+    ///
+    code: Option<(String, String)>,
+    ///
+    /// The destination and description of the image being parsed.
+    ///
+    image: Option<(String, String)>,
+    table: Option<TableBuilder>,
+    heading: Option<u8>,
+}
+
+impl DocumentBuilder {
+    fn push(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag_end) => self.end(tag_end),
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                if let Some((_, code)) = &mut self.code {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = &mut self.image {
+                    alt.push_str(&text);
+                } else {
+                    self.push_text(&text, self.style.clone());
+                }
+            }
+            Event::Code(text) => {
+                let style = InlineStyle {
+                    code: true,
+                    ..self.style.clone()
+                };
+                self.push_text(&text, style);
+            }
+            Event::SoftBreak => self.push_text(" ", self.style.clone()),
+            Event::HardBreak => self.push_text("\n", self.style.clone()),
+            Event::Rule => {
+                self.flush_paragraph();
+                self.push_block(MarkdownBlock::Rule);
+            }
+            Event::TaskListMarker(checked) => {
+                if let Some(Container::Item(item)) = self.containers.last_mut() {
+                    item.task = Some(checked);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.flush_paragraph(),
+            Tag::Heading { level, .. } => {
+                self.flush_paragraph();
+                self.heading = Some(level as u8);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.containers.push(Container::Blocks(Vec::new()));
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_paragraph();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush_paragraph();
+                self.containers.push(Container::List {
+                    start,
+                    items: Vec::new(),
+                });
+            }
+            Tag::Item => self.containers.push(Container::Item(ListItem {
+                task: None,
+                blocks: Vec::new(),
+            })),
+            Tag::Table(alignments) => {
+                self.flush_paragraph();
+                self.table = Some(TableBuilder {
+                    alignments,
+                    ..TableBuilder::default()
+                });
+            }
+            Tag::Emphasis => self.style.italic = true,
+            Tag::Strong => self.style.bold = true,
+            Tag::Strikethrough => self.style.strikethrough = true,
+            Tag::Link { dest_url, .. } => self.style.link = Some(dest_url.to_string()),
+            Tag::Image { dest_url, .. } => self.image = Some((dest_url.to_string(), String::new())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag_end: TagEnd) {
+        match tag_end {
+            TagEnd::Paragraph => self.flush_paragraph(),
+            TagEnd::Heading(_) => {
+                let content = std::mem::take(&mut self.inlines);
+                if let Some(level) = self.heading.take() {
+                    self.push_block(MarkdownBlock::Heading { level, content });
+                }
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_paragraph();
+                if let Some(Container::Blocks(blocks)) = self.containers.pop() {
+                    self.push_block(MarkdownBlock::Quote(blocks));
+                }
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code.take() {
+                    let lines = highlight_code(&code, &language, self.theme);
+                    self.push_block(MarkdownBlock::CodeBlock(lines));
+                }
+            }
+            TagEnd::List(_) => {
+                if let Some(Container::List { start, items }) = self.containers.pop() {
+                    self.push_block(MarkdownBlock::List { start, items });
+                }
+            }
+            TagEnd::Item => {
+                //
+                // The text of an item in a tight list is not in a paragraph.
+                //
+                self.flush_paragraph();
+                if let Some(Container::Item(item)) = self.containers.pop()
+                    && let Some(Container::List { items, .. }) = self.containers.last_mut()
+                {
+                    items.push(item);
+                }
+            }
+            TagEnd::TableCell => {
+                let cell = std::mem::take(&mut self.inlines);
+                if let Some(table) = &mut self.table {
+                    table.row.push(cell);
+                }
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.header = std::mem::take(&mut table.row);
+                }
+            }
+            TagEnd::TableRow => {
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.push_block(MarkdownBlock::Table {
+                        alignments: table.alignments,
+                        header: table.header,
+                        rows: table.rows,
+                    });
+                }
+            }
+            TagEnd::Emphasis => self.style.italic = false,
+            TagEnd::Strong => self.style.bold = false,
+            TagEnd::Strikethrough => self.style.strikethrough = false,
+            TagEnd::Link => self.style.link = None,
+            TagEnd::Image => {
+                let Some((url, alt)) = self.image.take() else {
+                    return;
+                };
+                //
+                // Headings and table cells are a single line of rich text, so an
+                // image in them is shown by its description.
+                //
+                if self.heading.is_some() || self.table.is_some() {
+                    self.push_text(&alt, self.style.clone());
+                } else {
+                    self.flush_paragraph();
+                    self.push_block(MarkdownBlock::Image { url, alt });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ///
+    /// Add `text` to the inline content, joining it with the text before if it has
+    /// the same style.
+    ///
+    fn push_text(&mut self, text: &str, style: InlineStyle) {
+        match self.inlines.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.inlines.push(InlineText {
+                text: text.to_string(),
+                style,
+            }),
+        }
+    }
+
+    ///
+    /// End the paragraph with the inline content parsed so far, if there is any.
+    ///
+    fn flush_paragraph(&mut self) {
+        if self.heading.is_some() || self.table.is_some() || self.inlines.is_empty() {
+            return;
+        }
+        let inlines = std::mem::take(&mut self.inlines);
+        self.push_block(MarkdownBlock::Paragraph(inlines));
+    }
+
+    fn push_block(&mut self, block: MarkdownBlock) {
+        match self.containers.last_mut() {
+            Some(Container::Blocks(blocks)) => blocks.push(block),
+            Some(Container::Item(item)) => item.blocks.push(block),
+            //
+            // Lists only contain items.
+            //
+            Some(Container::List { .. }) | None => {}
+        }
+    }
+
+    fn finish(mut self) -> MarkdownDocument {
+        self.flush_paragraph();
+        match self.containers.into_iter().next() {
+            Some(Container::Blocks(blocks)) => MarkdownDocument { blocks },
+            _ => MarkdownDocument::default(),
+        }
+    }
+}
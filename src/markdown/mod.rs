@@ -0,0 +1,8 @@
+//!
+//! Markdown preview and export modules.
+//!
+pub(crate) mod code_highlight;
+pub(crate) mod html_export;
+pub(crate) mod markdown_document;
+pub(crate) mod pdf_export;
+pub(crate) mod preview_state;
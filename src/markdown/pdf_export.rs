@@ -0,0 +1,897 @@
+//!
+//! Notespace-Editor
+//!
+//! Export of a document to a PDF file, in the bundled FiraCode fonts.
+//!
+//! Markdown documents are laid out from their blocks, other documents as plain
+//! text. FiraCode is monospaced, so lines are wrapped by counting characters. The
+//! whole document is laid out into pages before the PDF is written, so the footer
+//! of every page can show the number of pages. The fonts are embedded in the file,
+//! nothing is downloaded or run outside the editor.
+//!
+
+use crate::app_configuration::PdfPageSize;
+use crate::app_io::{
+    AppIOError, FileBackup, SavedFile, async_choose_export_path, async_save_bytes_to_path,
+};
+use crate::markdown::code_highlight::{HighlightedLine, code_colors};
+use crate::markdown::markdown_document::{Inlines, MarkdownBlock, MarkdownDocument};
+use iced::Color;
+use iced::highlighter::Theme;
+use printpdf::{IndirectFontRef, Line, Mm, PdfDocument, Point, Rect, Rgb};
+use pulldown_cmark::Alignment;
+use rust_i18n::t;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+const FONT_REGULAR: &[u8] = include_bytes!("../../fonts/FiraCode-Regular.ttf");
+const FONT_BOLD: &[u8] = include_bytes!("../../fonts/FiraCode-Bold.ttf");
+const FONT_LIGHT: &[u8] = include_bytes!("../../fonts/FiraCode-Light.ttf");
+
+///
+/// The advance width of every character of FiraCode, in em.
+///
+const CHARACTER_WIDTH: f32 = 1200.0 / 1950.0;
+const MM_PER_PT: f32 = 25.4 / 72.0;
+
+const TEXT_SIZE: f32 = 10.0;
+const CODE_SIZE: f32 = 9.0;
+const HEADER_SIZE: f32 = 8.0;
+const HEADING_SIZES: [f32; 6] = [18.0, 15.0, 13.0, 11.5, 10.0, 10.0];
+///
+/// The height of a line of text, relative to the font size.
+///
+const LINE_HEIGHT: f32 = 1.45;
+///
+/// The space between blocks, the indentation of lists and quotes, and the padding
+/// of code blocks and table cells, in millimeters.
+///
+const BLOCK_SPACING: f32 = 3.0;
+const INDENT: f32 = 6.0;
+const PADDING: f32 = 1.5;
+///
+/// The smallest margin in millimeters, which leaves room for the header and
+/// footer lines between the text and the edge of the page.
+///
+const MIN_MARGIN: f32 = 10.0;
+///
+/// The smallest width of a table column, in characters.
+///
+const MIN_TABLE_COLUMNS: usize = 3;
+
+const TEXT_COLOR: Color = Color::from_rgb(0.1, 0.1, 0.1);
+const SECONDARY_COLOR: Color = Color::from_rgb(0.45, 0.45, 0.45);
+const LINK_COLOR: Color = Color::from_rgb(0.02, 0.41, 0.85);
+const RULE_COLOR: Color = Color::from_rgb(0.8, 0.8, 0.8);
+
+#[derive(Debug, Clone)]
+pub struct PdfExportOptions {
+    ///
+    /// The name of the document, shown in the header of every page.
+    ///
+    pub(crate) file_name: String,
+    pub(crate) page_size: PdfPageSize,
+    ///
+    /// The margins in millimeters.
+    ///
+    pub(crate) margin: f32,
+    ///
+    /// Lay out the text as Markdown, instead of as plain text.
+    ///
+    pub(crate) markdown: bool,
+    ///
+    /// The syntax theme code blocks are highlighted with.
+    ///
+    pub(crate) theme: Theme,
+}
+
+///
+/// Lay out `text` and save it as a PDF file next to `file_path`, asking the user
+/// for the file name. The file is written the same way as a document, keeping the
+/// previous version as configured by `file_backup`.
+///
+pub async fn async_export_pdf(
+    file_path: Option<PathBuf>,
+    text: String,
+    options: PdfExportOptions,
+    file_backup: FileBackup,
+) -> Result<SavedFile, AppIOError> {
+    let path = async_choose_export_path(file_path, "pdf").await?;
+    let bytes = tokio::task::spawn_blocking(move || render_pdf(&text, &options))
+        .await
+        .map_err(|_| AppIOError::IOFailedError(ErrorKind::Other))?
+        .map_err(|error| AppIOError::ExportError(error.to_string()))?;
+    async_save_bytes_to_path(path, &bytes, &file_backup).await
+}
+
+///
+/// The PDF file of `text`.
+///
+pub fn render_pdf(text: &str, options: &PdfExportOptions) -> Result<Vec<u8>, printpdf::Error> {
+    let pages = layout_pages(text, options);
+    write_pdf(&pages, options)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PdfFont {
+    Regular,
+    Bold,
+    ///
+    /// Used for emphasis, as FiraCode has no italic style.
+    ///
+    Light,
+}
+
+///
+/// Something drawn on a page. Positions are in millimeters from the top left
+/// corner of the page, `y` of text is its baseline.
+///
+#[derive(Debug, Clone, PartialEq)]
+enum PdfItem {
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        font: PdfFont,
+        color: Color,
+        text: String,
+    },
+    Rectangle {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: Color,
+    },
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        color: Color,
+    },
+}
+
+///
+/// A run of text with the same style, as laid out on a line.
+///
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    text: String,
+    font: PdfFont,
+    color: Color,
+    background: Option<Color>,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl Run {
+    fn new(text: &str, font: PdfFont, color: Color) -> Self {
+        Self {
+            text: text.to_string(),
+            font,
+            color,
+            background: None,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+}
+
+///
+/// The width of a character at the font `size`, in millimeters.
+///
+fn character_width(size: f32) -> f32 {
+    size * CHARACTER_WIDTH * MM_PER_PT
+}
+
+fn line_height(size: f32) -> f32 {
+    size * LINE_HEIGHT * MM_PER_PT
+}
+
+///
+/// The number of characters at the font `size` that fit in `width`.
+///
+fn columns(width: f32, size: f32) -> usize {
+    ((width / character_width(size)).floor() as usize).max(1)
+}
+
+///
+/// Split `runs` into lines of at most `columns` characters, at spaces where
+/// possible, and at line breaks. The spaces a line is wrapped at are dropped.
+///
+fn wrap_runs(runs: &[Run], columns: usize) -> Vec<Vec<Run>> {
+    let characters: Vec<(char, usize)> = runs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, run)| run.text.chars().map(move |c| (c, index)))
+        .collect();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start..characters.len())
+            .find(|index| characters[*index].0 == '\n')
+            .unwrap_or(characters.len());
+        let (line_end, next) = if end - start <= columns {
+            (end, end + 1)
+        } else {
+            let limit = start + columns;
+            match (start + 1..=limit)
+                .rev()
+                .find(|index| characters[*index].0 == ' ')
+            {
+                Some(space) => (space, space + 1),
+                None => (limit, limit),
+            }
+        };
+        lines.push(runs_of(runs, &characters[start..line_end]));
+        start = next;
+        if line_end < end {
+            while start < end && characters[start].0 == ' ' {
+                start += 1;
+            }
+        }
+        if start >= characters.len() {
+            return lines;
+        }
+    }
+}
+
+///
+/// The `characters` of a line, grouped into runs with the style of the `runs`
+/// they are from.
+///
+fn runs_of(runs: &[Run], characters: &[(char, usize)]) -> Vec<Run> {
+    let mut line: Vec<(usize, Run)> = Vec::new();
+    for (c, index) in characters {
+        match line.last_mut() {
+            Some((last_index, run)) if last_index == index => run.text.push(*c),
+            _ => line.push((
+                *index,
+                Run {
+                    text: c.to_string(),
+                    ..runs[*index].clone()
+                },
+            )),
+        }
+    }
+    line.into_iter().map(|(_, run)| run).collect()
+}
+
+///
+/// The widths of table columns whose longest cells are `lengths` characters long,
+/// so they fit in `available` characters. The columns share the width by the
+/// length of their longest cell, and are at least `MIN_TABLE_COLUMNS` wide.
+///
+fn column_widths(lengths: &[usize], available: usize) -> Vec<usize> {
+    let total: usize = lengths.iter().sum();
+    if total <= available {
+        return lengths.to_vec();
+    }
+    let mut widths: Vec<usize> = lengths
+        .iter()
+        .map(|length| (length * available / total).max(MIN_TABLE_COLUMNS))
+        .collect();
+    //
+    // Narrow columns that were widened to the minimum take their width from the
+    // widest columns.
+    //
+    let mut excess = widths.iter().sum::<usize>().saturating_sub(available);
+    while excess > 0 {
+        let Some(widest) = widths
+            .iter_mut()
+            .max_by_key(|width| **width)
+            .filter(|width| **width > MIN_TABLE_COLUMNS)
+        else {
+            break;
+        };
+        *widest -= 1;
+        excess -= 1;
+    }
+    widths
+}
+
+fn runs_length(runs: &[Run]) -> usize {
+    runs.iter().map(|run| run.text.chars().count()).sum()
+}
+
+///
+/// Lays out a document into pages.
+///
+struct PdfLayout {
+    page_width: f32,
+    page_height: f32,
+    margin: f32,
+    pages: Vec<Vec<PdfItem>>,
+    ///
+    /// The top of the next line on the current page.
+    ///
+    y: f32,
+    ///
+    /// The left edges of the quotes the next line is in, where a bar is drawn.
+    ///
+    quote_bars: Vec<f32>,
+    ///
+    /// The bullet or number of a list item, drawn on the next line at `x`.
+    ///
+    marker: Option<(f32, Run)>,
+    ///
+    /// The left edge, width and color of the background of the next lines, e.g.
+    /// of a code block.
+    ///
+    line_background: Option<(f32, f32, Color)>,
+    code_background: Color,
+    code_foreground: Color,
+}
+
+impl PdfLayout {
+    fn new(options: &PdfExportOptions) -> Self {
+        let (page_width, page_height) = options.page_size.size();
+        let (code_background, code_foreground) = code_colors(options.theme);
+        //
+        // Margins that leave no room for the text are reduced, but not below the
+        // room needed by the header and footer.
+        //
+        let max_margin = (page_width.min(page_height) / 2.0 - 20.0).max(MIN_MARGIN);
+        let margin = options.margin.clamp(MIN_MARGIN, max_margin);
+        Self {
+            page_width,
+            page_height,
+            margin,
+            pages: vec![Vec::new()],
+            y: margin,
+            quote_bars: Vec::new(),
+            marker: None,
+            line_background: None,
+            code_background,
+            code_foreground,
+        }
+    }
+
+    fn right(&self) -> f32 {
+        self.page_width - self.margin
+    }
+
+    fn items(&mut self) -> &mut Vec<PdfItem> {
+        self.pages.last_mut().expect("there is always a page")
+    }
+
+    ///
+    /// The top of a line of `height`, on a new page if it does not fit on the
+    /// current one. The quote bars and the line background are drawn beside and
+    /// behind it.
+    ///
+    fn space(&mut self, height: f32) -> f32 {
+        if self.y + height > self.page_height - self.margin && self.y > self.margin {
+            self.pages.push(Vec::new());
+            self.y = self.margin;
+        }
+        let top = self.y;
+        self.y += height;
+        if let Some((x, width, color)) = self.line_background {
+            self.items().push(PdfItem::Rectangle {
+                x,
+                y: top,
+                width,
+                height,
+                color,
+            });
+        }
+        for x in self.quote_bars.clone() {
+            self.items().push(PdfItem::Line {
+                x1: x,
+                y1: top,
+                x2: x,
+                y2: top + height,
+                color: RULE_COLOR,
+            });
+        }
+        top
+    }
+
+    ///
+    /// Add vertical space between blocks. Space at the top of a page is dropped.
+    ///
+    fn skip(&mut self, height: f32) {
+        if self.y > self.margin {
+            self.y += height;
+        }
+    }
+
+    ///
+    /// Lay out a line of `runs` at `x`, with the list marker if there is one.
+    ///
+    fn text_line(&mut self, x: f32, runs: &[Run], size: f32) {
+        let height = line_height(size);
+        let top = self.space(height);
+        if let Some((marker_x, marker)) = self.marker.take() {
+            self.draw_runs(marker_x, top, &[marker], size);
+        }
+        self.draw_runs(x, top, runs, size);
+    }
+
+    ///
+    /// Draw `runs` on the line at `top`.
+    ///
+    fn draw_runs(&mut self, x: f32, top: f32, runs: &[Run], size: f32) {
+        let height = line_height(size);
+        let baseline = top + (height + size * MM_PER_PT * 0.7) / 2.0;
+        let width = character_width(size);
+        let mut run_x = x;
+        for run in runs {
+            let run_width = run.text.chars().count() as f32 * width;
+            if let Some(background) = run.background {
+                self.items().push(PdfItem::Rectangle {
+                    x: run_x,
+                    y: top,
+                    width: run_width,
+                    height,
+                    color: background,
+                });
+            }
+            self.items().push(PdfItem::Text {
+                x: run_x,
+                y: baseline,
+                size,
+                font: run.font,
+                color: run.color,
+                text: run.text.clone(),
+            });
+            for (enabled, y) in [
+                (run.underline, baseline + size * MM_PER_PT * 0.15),
+                (run.strikethrough, baseline - size * MM_PER_PT * 0.3),
+            ] {
+                if enabled {
+                    self.items().push(PdfItem::Line {
+                        x1: run_x,
+                        y1: y,
+                        x2: run_x + run_width,
+                        y2: y,
+                        color: run.color,
+                    });
+                }
+            }
+            run_x += run_width;
+        }
+    }
+
+    fn rule(&mut self, x: f32) {
+        let top = self.space(BLOCK_SPACING);
+        let y = top + BLOCK_SPACING / 2.0;
+        let right = self.right();
+        self.items().push(PdfItem::Line {
+            x1: x,
+            y1: y,
+            x2: right,
+            y2: y,
+            color: RULE_COLOR,
+        });
+    }
+
+    ///
+    /// Lay out the lines of plain text, keeping their indentation.
+    ///
+    fn plain_text(&mut self, text: &str) {
+        let columns = columns(self.right() - self.margin, TEXT_SIZE);
+        for line in text.lines() {
+            let run = Run::new(&line.replace('\t', "    "), PdfFont::Regular, TEXT_COLOR);
+            for runs in wrap_runs(&[run], columns) {
+                self.text_line(self.margin, &runs, TEXT_SIZE);
+            }
+        }
+    }
+
+    fn blocks(&mut self, blocks: &[MarkdownBlock], x: f32, spacing: f32) {
+        for (index, block) in blocks.iter().enumerate() {
+            if index > 0 {
+                self.skip(spacing);
+            }
+            self.block(block, x);
+        }
+    }
+
+    fn block(&mut self, block: &MarkdownBlock, x: f32) {
+        match block {
+            MarkdownBlock::Heading { level, content } => {
+                let size = HEADING_SIZES[(*level as usize).clamp(1, 6) - 1];
+                self.skip(BLOCK_SPACING);
+                self.paragraph(content, x, size, PdfFont::Bold);
+                if *level <= 2 {
+                    self.rule(x);
+                }
+            }
+            MarkdownBlock::Paragraph(content) => {
+                self.paragraph(content, x, TEXT_SIZE, PdfFont::Regular)
+            }
+            MarkdownBlock::CodeBlock(lines) => self.code_block(lines, x),
+            MarkdownBlock::List { start, items } => {
+                for (index, item) in items.iter().enumerate() {
+                    let marker = match (item.task, start) {
+                        (Some(true), _) => "☑".to_string(),
+                        (Some(false), _) => "☐".to_string(),
+                        (None, Some(start)) => format!("{}.", start + index as u64),
+                        (None, None) => "•".to_string(),
+                    };
+                    if index > 0 {
+                        self.skip(BLOCK_SPACING / 3.0);
+                    }
+                    self.marker = Some((x, Run::new(&marker, PdfFont::Regular, TEXT_COLOR)));
+                    self.blocks(&item.blocks, x + INDENT, BLOCK_SPACING / 2.0);
+                    self.marker = None;
+                }
+            }
+            MarkdownBlock::Quote(blocks) => {
+                self.quote_bars.push(x + 0.5);
+                self.blocks(blocks, x + INDENT, BLOCK_SPACING);
+                self.quote_bars.pop();
+            }
+            MarkdownBlock::Table {
+                alignments,
+                header,
+                rows,
+            } => self.table(alignments, header, rows, x),
+            //
+            // Images are shown by their description.
+            //
+            MarkdownBlock::Image { alt, .. } => {
+                let run = Run::new(&format!("[{}]", alt), PdfFont::Regular, LINK_COLOR);
+                let columns = columns(self.right() - x, TEXT_SIZE);
+                for runs in wrap_runs(&[run], columns) {
+                    self.text_line(x, &runs, TEXT_SIZE);
+                }
+            }
+            MarkdownBlock::Rule => self.rule(x),
+        }
+    }
+
+    fn runs(&self, inlines: &Inlines, font: PdfFont) -> Vec<Run> {
+        inlines
+            .iter()
+            .map(|inline_text| {
+                let style = &inline_text.style;
+                let font = match (style.bold, style.italic) {
+                    (true, _) => PdfFont::Bold,
+                    (false, true) => PdfFont::Light,
+                    (false, false) => font,
+                };
+                let color = match (&style.link, style.code) {
+                    (Some(_), _) => LINK_COLOR,
+                    (None, true) => self.code_foreground,
+                    (None, false) => TEXT_COLOR,
+                };
+                Run {
+                    text: inline_text.text.clone(),
+                    font,
+                    color,
+                    background: style.code.then_some(self.code_background),
+                    underline: style.link.is_some(),
+                    strikethrough: style.strikethrough,
+                }
+            })
+            .collect()
+    }
+
+    fn paragraph(&mut self, content: &Inlines, x: f32, size: f32, font: PdfFont) {
+        let runs = self.runs(content, font);
+        let columns = columns(self.right() - x, size);
+        for line in wrap_runs(&runs, columns) {
+            self.text_line(x, &line, size);
+        }
+    }
+
+    fn code_block(&mut self, lines: &[HighlightedLine], x: f32) {
+        let width = self.right() - x;
+        let columns = columns(width - 2.0 * PADDING, CODE_SIZE);
+        //
+        // The background is drawn line by line, so it continues on the next page
+        // when the block is split.
+        //
+        self.line_background = Some((x, width, self.code_background));
+        self.space(PADDING);
+        for line in lines {
+            let runs: Vec<Run> = line
+                .iter()
+                .map(|(part, color)| {
+                    Run::new(
+                        &part.replace('\t', "    "),
+                        PdfFont::Regular,
+                        color.unwrap_or(self.code_foreground),
+                    )
+                })
+                .collect();
+            for runs in wrap_runs(&runs, columns) {
+                self.text_line(x + PADDING, &runs, CODE_SIZE);
+            }
+        }
+        self.space(PADDING);
+        self.line_background = None;
+    }
+
+    fn table(
+        &mut self,
+        alignments: &[Alignment],
+        header: &[Inlines],
+        rows: &[Vec<Inlines>],
+        x: f32,
+    ) {
+        let column_count = rows
+            .iter()
+            .map(Vec::len)
+            .chain([header.len()])
+            .max()
+            .unwrap_or(0);
+        if column_count == 0 {
+            return;
+        }
+        let width = self.right() - x;
+        let cell_padding = 2.0 * PADDING;
+        let available = columns(width - column_count as f32 * cell_padding, TEXT_SIZE);
+        let mut lengths = vec![MIN_TABLE_COLUMNS; column_count];
+        for cells in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+            for (index, cell) in cells.iter().enumerate() {
+                let length = cell.iter().map(|text| text.text.chars().count()).sum();
+                lengths[index] = lengths[index].max(length);
+            }
+        }
+        let widths = column_widths(&lengths, available);
+        let top = self.table_row(header, alignments, &widths, x, PdfFont::Bold);
+        self.table_rule(x, top, &widths, cell_padding);
+        for cells in rows {
+            self.table_row(cells, alignments, &widths, x, PdfFont::Regular);
+        }
+    }
+
+    ///
+    /// Lay out a row of the table, and return its top.
+    ///
+    fn table_row(
+        &mut self,
+        cells: &[Inlines],
+        alignments: &[Alignment],
+        widths: &[usize],
+        x: f32,
+        font: PdfFont,
+    ) -> f32 {
+        let cell_padding = 2.0 * PADDING;
+        let cell_lines: Vec<Vec<Vec<Run>>> = widths
+            .iter()
+            .enumerate()
+            .map(|(index, columns)| match cells.get(index) {
+                Some(cell) => wrap_runs(&self.runs(cell, font), *columns),
+                None => Vec::new(),
+            })
+            .collect();
+        let line_count = cell_lines.iter().map(Vec::len).max().unwrap_or(1).max(1);
+        let height = line_count as f32 * line_height(TEXT_SIZE) + cell_padding;
+        let top = self.space(height);
+        let width = character_width(TEXT_SIZE);
+        let mut cell_x = x;
+        for (index, lines) in cell_lines.iter().enumerate() {
+            for (line_index, runs) in lines.iter().enumerate() {
+                let free = widths[index].saturating_sub(runs_length(runs)) as f32 * width;
+                let offset = match alignments.get(index) {
+                    Some(Alignment::Center) => free / 2.0,
+                    Some(Alignment::Right) => free,
+                    _ => 0.0,
+                };
+                let line_top = top + PADDING + line_index as f32 * line_height(TEXT_SIZE);
+                self.draw_runs(cell_x + PADDING + offset, line_top, runs, TEXT_SIZE);
+            }
+            cell_x += widths[index] as f32 * width + cell_padding;
+        }
+        self.table_rule(x, top + height, widths, cell_padding);
+        top
+    }
+
+    fn table_rule(&mut self, x: f32, y: f32, widths: &[usize], cell_padding: f32) {
+        let width: f32 = widths
+            .iter()
+            .map(|columns| *columns as f32 * character_width(TEXT_SIZE) + cell_padding)
+            .sum();
+        self.items().push(PdfItem::Line {
+            x1: x,
+            y1: y,
+            x2: x + width,
+            y2: y,
+            color: RULE_COLOR,
+        });
+    }
+
+    ///
+    /// Add the header with the file name and the footer with the page number to
+    /// every page.
+    ///
+    fn finish(mut self, file_name: &str) -> Vec<Vec<PdfItem>> {
+        let page_count = self.pages.len();
+        let header_y = self.margin * 0.6;
+        let footer_y = self.page_height - self.margin * 0.4;
+        let width = character_width(HEADER_SIZE);
+        for (index, items) in self.pages.iter_mut().enumerate() {
+            items.push(PdfItem::Text {
+                x: self.margin,
+                y: header_y,
+                size: HEADER_SIZE,
+                font: PdfFont::Light,
+                color: SECONDARY_COLOR,
+                text: file_name.to_string(),
+            });
+            let page_number =
+                t!("pdf_page_number", page = index + 1, pages = page_count).to_string();
+            let page_number_width = page_number.chars().count() as f32 * width;
+            items.push(PdfItem::Text {
+                x: (self.page_width - page_number_width) / 2.0,
+                y: footer_y,
+                size: HEADER_SIZE,
+                font: PdfFont::Light,
+                color: SECONDARY_COLOR,
+                text: page_number,
+            });
+        }
+        self.pages
+    }
+}
+
+///
+/// The items of every page of `text`.
+///
+fn layout_pages(text: &str, options: &PdfExportOptions) -> Vec<Vec<PdfItem>> {
+    let mut layout = PdfLayout::new(options);
+    if options.markdown {
+        let document = MarkdownDocument::parse(text, options.theme);
+        layout.blocks(&document.blocks, layout.margin, BLOCK_SPACING);
+    } else {
+        layout.plain_text(text);
+    }
+    layout.finish(&options.file_name)
+}
+
+fn write_pdf(
+    pages: &[Vec<PdfItem>],
+    options: &PdfExportOptions,
+) -> Result<Vec<u8>, printpdf::Error> {
+    let (page_width, page_height) = options.page_size.size();
+    let (document, page, layer) = PdfDocument::new(
+        options.file_name.as_str(),
+        Mm(page_width),
+        Mm(page_height),
+        "Text",
+    );
+    let regular = document.add_external_font(FONT_REGULAR)?;
+    let bold = document.add_external_font(FONT_BOLD)?;
+    let light = document.add_external_font(FONT_LIGHT)?;
+    let font = |font: PdfFont| -> &IndirectFontRef {
+        match font {
+            PdfFont::Regular => &regular,
+            PdfFont::Bold => &bold,
+            PdfFont::Light => &light,
+        }
+    };
+    let rgb = |color: Color| printpdf::Color::Rgb(Rgb::new(color.r, color.g, color.b, None));
+    //
+    // PDF positions are from the bottom left corner of the page.
+    //
+    let point = |x: f32, y: f32| Point::new(Mm(x), Mm(page_height - y));
+
+    for (index, items) in pages.iter().enumerate() {
+        let layer = match index {
+            0 => document.get_page(page).get_layer(layer),
+            _ => {
+                let (page, layer) = document.add_page(Mm(page_width), Mm(page_height), "Text");
+                document.get_page(page).get_layer(layer)
+            }
+        };
+        for item in items {
+            match item {
+                PdfItem::Text {
+                    x,
+                    y,
+                    size,
+                    font: text_font,
+                    color,
+                    text,
+                } => {
+                    layer.set_fill_color(rgb(*color));
+                    layer.use_text(
+                        text.as_str(),
+                        *size,
+                        Mm(*x),
+                        Mm(page_height - y),
+                        font(*text_font),
+                    );
+                }
+                PdfItem::Rectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color,
+                } => {
+                    layer.set_fill_color(rgb(*color));
+                    layer.add_rect(Rect::new(
+                        Mm(*x),
+                        Mm(page_height - y - height),
+                        Mm(x + width),
+                        Mm(page_height - y),
+                    ));
+                }
+                PdfItem::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                } => {
+                    layer.set_outline_color(rgb(*color));
+                    layer.set_outline_thickness(0.5);
+                    layer.add_line(Line {
+                        points: vec![(point(*x1, *y1), false), (point(*x2, *y2), false)],
+                        is_closed: false,
+                    });
+                }
+            }
+        }
+    }
+    document.save_to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[Vec<Run>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.iter().map(|run| run.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn column_widths_fit_the_available_width() {
+        assert_eq!(column_widths(&[5, 10], 20), [5, 10]);
+        assert_eq!(column_widths(&[20, 20], 20), [10, 10]);
+        let widths = column_widths(&[3, 3, 3, 100], 20);
+        assert_eq!(widths.iter().sum::<usize>(), 20);
+        assert!(widths.iter().all(|width| *width >= MIN_TABLE_COLUMNS));
+    }
+
+    #[test]
+    fn column_widths_keep_the_minimum_when_nothing_fits() {
+        assert_eq!(column_widths(&[10, 10, 10], 6), [3, 3, 3]);
+    }
+
+    #[test]
+    fn wrap_runs_breaks_at_spaces() {
+        let runs = [Run::new(
+            "the quick brown fox",
+            PdfFont::Regular,
+            TEXT_COLOR,
+        )];
+        assert_eq!(texts(&wrap_runs(&runs, 10)), ["the quick", "brown fox"]);
+    }
+
+    #[test]
+    fn wrap_runs_breaks_long_words() {
+        let runs = [Run::new("abcdefghij", PdfFont::Regular, TEXT_COLOR)];
+        assert_eq!(texts(&wrap_runs(&runs, 4)), ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn wrap_runs_keeps_line_breaks() {
+        let runs = [Run::new("one\n\ntwo", PdfFont::Regular, TEXT_COLOR)];
+        assert_eq!(texts(&wrap_runs(&runs, 80)), ["one", "", "two"]);
+    }
+
+    #[test]
+    fn wrap_runs_keeps_the_style_of_each_run() {
+        let runs = [
+            Run::new("plain ", PdfFont::Regular, TEXT_COLOR),
+            Run::new("bold text", PdfFont::Bold, TEXT_COLOR),
+        ];
+        let lines = wrap_runs(&runs, 10);
+        assert_eq!(texts(&lines), ["plain bold", "text"]);
+        assert_eq!(lines[0][0].font, PdfFont::Regular);
+        assert_eq!(lines[0][1].font, PdfFont::Bold);
+        assert_eq!(lines[1][0].font, PdfFont::Bold);
+        assert_eq!(runs_length(&lines[0]), 10);
+    }
+}
@@ -0,0 +1,40 @@
+//!
+//! Notespace-Editor
+//!
+//! State of the Markdown preview, shown beside the editor.
+//!
+
+use crate::markdown::markdown_document::MarkdownDocument;
+use iced::highlighter::Theme;
+use std::path::PathBuf;
+
+#[derive(Debug, Default)]
+pub struct PreviewState {
+    pub(crate) visible: bool,
+    ///
+    /// The active document, parsed when it was last edited.
+    ///
+    pub(crate) document: MarkdownDocument,
+    ///
+    /// The directory of the active document, which relative image paths are
+    /// resolved against.
+    ///
+    pub(crate) base_directory: Option<PathBuf>,
+    ///
+    /// The editor line the preview was last scrolled to.
+    ///
+    pub(crate) synced_line: Option<usize>,
+}
+
+impl PreviewState {
+    ///
+    /// Parse the `text` of the active document, if the preview is shown.
+    ///
+    pub fn update(&mut self, text: &str, file_path: Option<PathBuf>, theme: Theme) {
+        if !self.visible {
+            return;
+        }
+        self.document = MarkdownDocument::parse(text, theme);
+        self.base_directory = file_path.and_then(|path| Some(path.parent()?.to_path_buf()));
+    }
+}
@@ -0,0 +1,65 @@
+//!
+//! Notespace-Editor
+//!
+//! Fuzzy matching of a query against a candidate string.
+//!
+//! The query matches if its characters appear in the candidate in order (ignoring
+//! case). Matches at the start of a word and runs of consecutive characters score
+//! higher, and gaps between matched characters score lower.
+//!
+
+const SCORE_MATCH: i64 = 1;
+const SCORE_CONSECUTIVE: i64 = 5;
+const SCORE_WORD_START: i64 = 8;
+const PENALTY_GAP: i64 = 1;
+const PENALTY_GAP_MAX: i64 = 5;
+
+///
+/// Returns the score of `query` against `candidate`, or `None` if it does not match.
+/// An empty query matches everything with a score of `0`.
+///
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<i64> {
+    let mut query_chars = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for (index, c) in candidate.chars().enumerate() {
+        let Some(&query_char) = query_chars.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(query_char.to_lowercase()) {
+            score += SCORE_MATCH;
+            if is_word_start(previous, c) {
+                score += SCORE_WORD_START;
+            }
+            match last_match {
+                Some(last) if last + 1 == index => score += SCORE_CONSECUTIVE,
+                Some(last) => {
+                    score -= (PENALTY_GAP * (index - last - 1) as i64).min(PENALTY_GAP_MAX)
+                }
+                None => {}
+            }
+            last_match = Some(index);
+            query_chars.next();
+        }
+        previous = Some(c);
+    }
+    query_chars.peek().is_none().then_some(score)
+}
+
+///
+/// A character starts a word if it follows a separator, or is an upper case letter
+/// that follows a lower case letter (`camelCase`).
+///
+fn is_word_start(previous: Option<char>, c: char) -> bool {
+    match previous {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && c.is_uppercase())
+        }
+    }
+}
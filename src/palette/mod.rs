@@ -0,0 +1,6 @@
+//!
+//! Command palette modules.
+//!
+pub(crate) mod fuzzy_match;
+pub(crate) mod palette_command;
+pub(crate) mod palette_state;
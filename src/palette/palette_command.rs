@@ -0,0 +1,216 @@
+//!
+//! Notespace-Editor
+//!
+//! Registry of the commands available in the command palette.
+//!
+
+use crate::app_configuration::{AutosaveMode, BackupMode, PdfPageSize};
+use crate::app_const::{PDF_MARGINS, SYNTAX_MODES};
+use crate::app_message::AppMessage;
+use crate::document::line_ending::LineEnding;
+use crate::document::text_encoding::TextEncoding;
+use crate::keyboard::keybind_action::KeybindAction;
+use crate::keyboard::keybind_manager::KeybindManager;
+use crate::ui_const::UI_SCALE_FACTORS;
+use iced::Theme;
+use rust_i18n::t;
+use std::path::PathBuf;
+
+///
+/// A command that can be run from the command palette.
+///
+#[derive(Debug, Clone)]
+pub enum PaletteCommand {
+    Keybind(KeybindAction),
+    WindowTheme(Theme),
+    SyntaxTheme(iced::highlighter::Theme),
+    Language(String),
+    Scale(f64),
+    ReopenWithEncoding(TextEncoding),
+    SaveWithEncoding(TextEncoding),
+    LineEnding(LineEnding),
+    DefaultLineEnding(LineEnding),
+    BackupMode(BackupMode),
+    AutosaveMode(AutosaveMode),
+    ExportTableOfContents(bool),
+    PdfPageSize(PdfPageSize),
+    ///
+    /// The margins of exported PDF pages in millimeters.
+    ///
+    PdfMargin(f32),
+    ///
+    /// The syntax mode of the active document, as an index into `SYNTAX_MODES`.
+    /// `None` uses the extension of the file.
+    ///
+    SyntaxMode(Option<usize>),
+    OpenRecentFile(PathBuf),
+}
+
+impl PaletteCommand {
+    ///
+    /// Every command, in display order, with a command to open each of the
+    /// `recent_files`.
+    ///
+    pub fn all(recent_files: &[PathBuf]) -> Vec<PaletteCommand> {
+        let mut commands: Vec<PaletteCommand> = KeybindAction::ALL
+            .into_iter()
+            .filter(|action| *action != KeybindAction::CommandPalette)
+            .map(PaletteCommand::Keybind)
+            .collect();
+        commands.extend(Theme::ALL.iter().cloned().map(PaletteCommand::WindowTheme));
+        commands.extend(
+            iced::highlighter::Theme::ALL
+                .iter()
+                .copied()
+                .map(PaletteCommand::SyntaxTheme),
+        );
+        commands.extend(
+            rust_i18n::available_locales!()
+                .into_iter()
+                .map(|locale| PaletteCommand::Language(locale.to_string())),
+        );
+        commands.extend(UI_SCALE_FACTORS.into_iter().map(PaletteCommand::Scale));
+        //
+        // Reopening decodes the file again, the BOM is taken from the file.
+        //
+        commands.extend(
+            TextEncoding::all()
+                .into_iter()
+                .filter(|encoding| !encoding.bom)
+                .map(PaletteCommand::ReopenWithEncoding),
+        );
+        commands.extend(
+            TextEncoding::all()
+                .into_iter()
+                .map(PaletteCommand::SaveWithEncoding),
+        );
+        commands.extend(LineEnding::ALL.into_iter().map(PaletteCommand::LineEnding));
+        commands.extend(
+            LineEnding::ALL
+                .into_iter()
+                .map(PaletteCommand::DefaultLineEnding),
+        );
+        commands.extend(BackupMode::ALL.into_iter().map(PaletteCommand::BackupMode));
+        commands.extend(
+            AutosaveMode::ALL
+                .into_iter()
+                .map(PaletteCommand::AutosaveMode),
+        );
+        commands.extend([true, false].map(PaletteCommand::ExportTableOfContents));
+        commands.extend(
+            PdfPageSize::ALL
+                .into_iter()
+                .map(PaletteCommand::PdfPageSize),
+        );
+        commands.extend(PDF_MARGINS.into_iter().map(PaletteCommand::PdfMargin));
+        commands.push(PaletteCommand::SyntaxMode(None));
+        commands
+            .extend((0..SYNTAX_MODES.len()).map(|index| PaletteCommand::SyntaxMode(Some(index))));
+        commands.extend(
+            recent_files
+                .iter()
+                .cloned()
+                .map(PaletteCommand::OpenRecentFile),
+        );
+        commands
+    }
+
+    ///
+    /// The localized name shown in the palette.
+    ///
+    pub fn label(&self) -> String {
+        match self {
+            PaletteCommand::Keybind(action) => t!(action.i18n_key()).to_string(),
+            PaletteCommand::WindowTheme(theme) => format!("{}: {}", t!("window_theme"), theme),
+            PaletteCommand::SyntaxTheme(theme) => format!("{}: {}", t!("syntax_theme"), theme),
+            PaletteCommand::Language(locale) => format!("{}: {}", t!("language"), locale),
+            PaletteCommand::Scale(scale) => format!("{}: {}", t!("scale_factor"), scale),
+            PaletteCommand::ReopenWithEncoding(encoding) => {
+                format!("{}: {}", t!("reopen_with_encoding"), encoding.label())
+            }
+            PaletteCommand::SaveWithEncoding(encoding) => {
+                format!("{}: {}", t!("save_with_encoding"), encoding.label())
+            }
+            PaletteCommand::LineEnding(line_ending) => {
+                format!("{}: {}", t!("line_ending"), line_ending.label())
+            }
+            PaletteCommand::DefaultLineEnding(line_ending) => {
+                format!("{}: {}", t!("default_line_ending"), line_ending.label())
+            }
+            PaletteCommand::BackupMode(backup_mode) => {
+                format!("{}: {}", t!("backup_files"), t!(backup_mode.i18n_key()))
+            }
+            PaletteCommand::AutosaveMode(autosave_mode) => {
+                format!("{}: {}", t!("autosave"), t!(autosave_mode.i18n_key()))
+            }
+            PaletteCommand::ExportTableOfContents(enabled) => {
+                let value = match enabled {
+                    true => t!("option_on"),
+                    false => t!("option_off"),
+                };
+                format!("{}: {}", t!("export_table_of_contents"), value)
+            }
+            PaletteCommand::PdfPageSize(page_size) => {
+                format!("{}: {}", t!("pdf_page_size"), page_size.label())
+            }
+            PaletteCommand::PdfMargin(margin) => {
+                format!("{}: {} mm", t!("pdf_margin"), margin)
+            }
+            PaletteCommand::SyntaxMode(Some(index)) => {
+                format!("{}: {}", t!("syntax_mode"), SYNTAX_MODES[*index].0)
+            }
+            PaletteCommand::SyntaxMode(None) => {
+                format!("{}: {}", t!("syntax_mode"), t!("syntax_mode_automatic"))
+            }
+            PaletteCommand::OpenRecentFile(file_path) => {
+                format!("{}: {}", t!("open_recent"), file_path.display())
+            }
+        }
+    }
+
+    ///
+    /// The keybind that runs the command, or an empty string.
+    ///
+    pub fn shortcut(&self, keybind_manager: &KeybindManager) -> String {
+        match self {
+            PaletteCommand::Keybind(action) => keybind_manager.get_display_text_for_action(*action),
+            _ => String::new(),
+        }
+    }
+
+    ///
+    /// The message that runs the command.
+    ///
+    pub fn message(&self) -> AppMessage {
+        match self {
+            PaletteCommand::Keybind(action) => AppMessage::RunKeybindAction(*action),
+            PaletteCommand::WindowTheme(theme) => AppMessage::UpdateWindowTheme(theme.clone()),
+            PaletteCommand::SyntaxTheme(theme) => AppMessage::UpdateSyntaxTheme(*theme),
+            PaletteCommand::Language(locale) => AppMessage::UpdateLanguage(locale.clone()),
+            PaletteCommand::Scale(scale) => AppMessage::UpdateScale(*scale),
+            PaletteCommand::ReopenWithEncoding(encoding) => {
+                AppMessage::ReopenWithEncoding(*encoding)
+            }
+            PaletteCommand::SaveWithEncoding(encoding) => AppMessage::SaveWithEncoding(*encoding),
+            PaletteCommand::LineEnding(line_ending) => AppMessage::ConvertLineEnding(*line_ending),
+            PaletteCommand::DefaultLineEnding(line_ending) => {
+                AppMessage::UpdateDefaultLineEnding(*line_ending)
+            }
+            PaletteCommand::BackupMode(backup_mode) => AppMessage::UpdateBackupMode(*backup_mode),
+            PaletteCommand::AutosaveMode(autosave_mode) => {
+                AppMessage::UpdateAutosaveMode(*autosave_mode)
+            }
+            PaletteCommand::ExportTableOfContents(enabled) => {
+                AppMessage::UpdateExportTableOfContents(*enabled)
+            }
+            PaletteCommand::PdfPageSize(page_size) => AppMessage::UpdatePdfPageSize(*page_size),
+            PaletteCommand::PdfMargin(margin) => AppMessage::UpdatePdfMargin(*margin),
+            PaletteCommand::SyntaxMode(index) => {
+                AppMessage::UpdateSyntaxMode(index.map(|index| SYNTAX_MODES[index].1.to_string()))
+            }
+            PaletteCommand::OpenRecentFile(file_path) => {
+                AppMessage::OpenRecentFile(file_path.clone())
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//!
+//! Notespace-Editor
+//!
+//! State of the command palette.
+//!
+
+use crate::palette::fuzzy_match::fuzzy_match;
+use crate::palette::palette_command::PaletteCommand;
+use std::cmp::Reverse;
+use std::path::PathBuf;
+
+#[derive(Debug, Default)]
+pub struct PaletteState {
+    pub(crate) visible: bool,
+    pub(crate) query: String,
+    pub(crate) results: Vec<PaletteCommand>,
+    pub(crate) selected: usize,
+    ///
+    /// The recent files offered by the palette while it is open.
+    ///
+    pub(crate) recent_files: Vec<PathBuf>,
+}
+
+impl PaletteState {
+    ///
+    /// Show the palette with `query` already entered, e.g. an empty query, or one
+    /// that offers a group of commands.
+    ///
+    pub fn open_with_query(&mut self, query: String, recent_files: Vec<PathBuf>) {
+        self.visible = true;
+        self.query = query;
+        self.recent_files = recent_files;
+        self.update_results();
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.query.clear();
+        self.results.clear();
+        self.recent_files.clear();
+    }
+
+    ///
+    /// Filter the commands by the query, best match first. Commands with the same
+    /// score keep their registry order.
+    ///
+    pub fn update_results(&mut self) {
+        let mut scored: Vec<(i64, PaletteCommand)> = PaletteCommand::all(&self.recent_files)
+            .into_iter()
+            .filter_map(|command| {
+                fuzzy_match(&self.query, &command.label()).map(|score| (score, command))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| Reverse(*score));
+        self.results = scored.into_iter().map(|(_, command)| command).collect();
+        self.selected = 0;
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.results.is_empty() {
+            self.selected = (self.selected + 1) % self.results.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.results.is_empty() {
+            self.selected = self
+                .selected
+                .checked_sub(1)
+                .unwrap_or(self.results.len() - 1);
+        }
+    }
+}
@@ -71,6 +71,46 @@ pub(crate) const UI_CONTROL_PADDING: u16 = 5;
 ///
 pub(crate) const UI_STATUSBAR_TEXT_SIZE: u16 = 12;
 
+///
+/// Font Awesome (solid) glyphs that are not exported by `fa_iced`.
+///
+pub(crate) const UI_ICON_FIND: &str = "\u{f002}";
+pub(crate) const UI_ICON_ARROW_UP: &str = "\u{f062}";
+pub(crate) const UI_ICON_ARROW_DOWN: &str = "\u{f063}";
+pub(crate) const UI_ICON_CUT: &str = "\u{f0c4}";
+pub(crate) const UI_ICON_COPY: &str = "\u{f0c5}";
+pub(crate) const UI_ICON_PASTE: &str = "\u{f0ea}";
+pub(crate) const UI_ICON_UNDO: &str = "\u{f0e2}";
+pub(crate) const UI_ICON_REDO: &str = "\u{f01e}";
+pub(crate) const UI_ICON_FILE: &str = "\u{f15b}";
+pub(crate) const UI_ICON_FOLDER: &str = "\u{f07b}";
+pub(crate) const UI_ICON_FOLDER_OPEN: &str = "\u{f07c}";
+pub(crate) const UI_ICON_CHEVRON_RIGHT: &str = "\u{f054}";
+pub(crate) const UI_ICON_CHEVRON_DOWN: &str = "\u{f078}";
+pub(crate) const UI_ICON_XMARK: &str = "\u{f00d}";
+pub(crate) const UI_ICON_EYE: &str = "\u{f06e}";
+
+///
+/// Approximate height of one line in the editor: the default text size (16) times
+/// the default relative line height (1.3). Used to scroll a line into view.
+///
+pub(crate) const UI_EDITOR_LINE_HEIGHT: f32 = 20.8;
+
+///
+/// Widget id of the find query text input.
+///
+pub(crate) const UI_FIND_INPUT_ID: &str = "find_input";
+
+///
+/// Widget id of the scrollable that contains the editor.
+///
+pub(crate) const UI_EDITOR_SCROLLABLE_ID: &str = "editor_scrollable";
+
+///
+/// The size of text in the document tabs.
+///
+pub(crate) const UI_TAB_TEXT_SIZE: u16 = 14;
+
 ///
 /// The size of toolbar buttons.
 ///
@@ -80,3 +120,145 @@ pub(crate) const UI_TOOLBAR_BUTTON_SIZE: u16 = 30;
 /// The size of toolbar button icons.
 ///
 pub(crate) const UI_TOOLBAR_ICON_SIZE: u16 = 16;
+
+///
+/// The width of the recent files list next to the toolbar open button.
+///
+pub(crate) const UI_RECENT_FILES_WIDTH: u16 = 160;
+
+///
+/// The size of text in the folder sidebar.
+///
+pub(crate) const UI_FILE_TREE_TEXT_SIZE: u16 = 14;
+
+///
+/// How far the entries of a directory are indented in the folder sidebar.
+///
+pub(crate) const UI_FILE_TREE_INDENT: u16 = 12;
+
+///
+/// Widget id of the name input in the folder sidebar.
+///
+pub(crate) const UI_FILE_TREE_INPUT_ID: &str = "file_tree_input";
+
+///
+/// The narrowest and widest the folder sidebar can be made.
+///
+pub(crate) const UI_SIDEBAR_MIN_WIDTH: f32 = 120.0;
+pub(crate) const UI_SIDEBAR_MAX_WIDTH: f32 = 800.0;
+
+///
+/// The width of the handle between the sidebar and the editor that is dragged to
+/// resize the sidebar.
+///
+pub(crate) const UI_SIDEBAR_DIVIDER_WIDTH: u16 = 6;
+
+///
+/// The height of the keybinding list in the settings modal.
+///
+pub(crate) const UI_KEYBINDING_EDITOR_HEIGHT: u16 = 360;
+
+///
+/// The window scale factors offered in the toolbar and command palette.
+///
+pub(crate) const UI_SCALE_FACTORS: [f64; 11] =
+    [0.5, 0.75, 1.0, 1.25, 1.50, 1.75, 2.0, 2.25, 2.5, 3.0, 4.0];
+
+///
+/// Widget id of the command palette text input.
+///
+pub(crate) const UI_PALETTE_INPUT_ID: &str = "palette_input";
+
+///
+/// Widget id of the command palette result list.
+///
+pub(crate) const UI_PALETTE_SCROLLABLE_ID: &str = "palette_scrollable";
+
+///
+/// The height of the command palette result list.
+///
+pub(crate) const UI_PALETTE_HEIGHT: u16 = 360;
+
+///
+/// Widget id of the quick open text input.
+///
+pub(crate) const UI_QUICK_OPEN_INPUT_ID: &str = "quick_open_input";
+
+///
+/// Widget id of the quick open result list.
+///
+pub(crate) const UI_QUICK_OPEN_SCROLLABLE_ID: &str = "quick_open_scrollable";
+
+///
+/// The number of files quick open shows for a query.
+///
+pub(crate) const UI_QUICK_OPEN_MAX_RESULTS: usize = 200;
+
+///
+/// The number of lines of the selected file shown by quick open.
+///
+pub(crate) const UI_QUICK_OPEN_PREVIEW_LINES: usize = 40;
+
+///
+/// Widget id of the query input of the folder search.
+///
+pub(crate) const UI_FOLDER_SEARCH_INPUT_ID: &str = "folder_search_input";
+
+///
+/// The number of bytes of a matching line shown before the match in the folder
+/// search results, and the length of the part of the line that is shown.
+///
+pub(crate) const UI_FOLDER_SEARCH_EXCERPT_CONTEXT: usize = 24;
+pub(crate) const UI_FOLDER_SEARCH_EXCERPT_LENGTH: usize = 160;
+
+///
+/// The width of the line numbers in the folder search results.
+///
+pub(crate) const UI_FOLDER_SEARCH_LINE_NUMBER_WIDTH: u16 = 36;
+
+///
+/// Widget id of the Markdown preview.
+///
+pub(crate) const UI_MARKDOWN_PREVIEW_SCROLLABLE_ID: &str = "markdown_preview_scrollable";
+
+///
+/// The size of text in the Markdown preview, and of headings by level.
+///
+pub(crate) const UI_MARKDOWN_TEXT_SIZE: u16 = 16;
+pub(crate) const UI_MARKDOWN_HEADING_SIZES: [u16; 6] = [30, 25, 21, 18, 16, 16];
+
+///
+/// The space between the blocks of the Markdown preview, and around them.
+///
+pub(crate) const UI_MARKDOWN_SPACING: u16 = 12;
+pub(crate) const UI_MARKDOWN_PADDING: u16 = 20;
+
+///
+/// The width of the bullet or number of a list item in the Markdown preview.
+///
+pub(crate) const UI_MARKDOWN_LIST_MARKER_WIDTH: u16 = 28;
+
+///
+/// The size of the hint shown while files are dragged over the window.
+///
+pub(crate) const UI_DROP_OVERLAY_TEXT_SIZE: u16 = 24;
+
+///
+/// The size of text in the side-by-side diff.
+///
+pub(crate) const UI_DIFF_TEXT_SIZE: u16 = 13;
+
+///
+/// The height of the side-by-side diff.
+///
+pub(crate) const UI_DIFF_HEIGHT: u16 = 420;
+
+///
+/// The number of unchanged lines shown around changed lines in a diff.
+///
+pub(crate) const UI_DIFF_CONTEXT_LINES: usize = 3;
+
+///
+/// The maximum height of the snapshot list in the recovery dialog.
+///
+pub(crate) const UI_RECOVERY_LIST_HEIGHT: u16 = 360;
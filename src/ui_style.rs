@@ -103,6 +103,81 @@ impl AppStyle {
         }
     }
 
+    ///
+    /// Style for the document tab strip, based on the `theme`.
+    ///
+    pub(crate) fn style_tabbar(theme: &Theme) -> Style {
+        let palette = theme.extended_palette();
+        Style {
+            background: Some(palette.background.weak.color.into()),
+            ..Style::default()
+        }
+    }
+
+    ///
+    /// Style for the folder sidebar, based on the `theme`.
+    ///
+    pub(crate) fn style_sidebar(theme: &Theme) -> Style {
+        let palette = theme.extended_palette();
+        Style {
+            background: Some(palette.background.weak.color.into()),
+            ..Style::default()
+        }
+    }
+
+    ///
+    /// Style for a block quote in the Markdown preview.
+    ///
+    pub(crate) fn style_markdown_quote(theme: &Theme) -> Style {
+        let palette = theme.extended_palette();
+        Style {
+            background: Some(palette.background.weak.color.into()),
+            border: Border {
+                color: palette.primary.weak.color,
+                width: 1.0,
+                radius: 4.0.into(),
+            },
+            ..Style::default()
+        }
+    }
+
+    ///
+    /// Style for a cell of a table in the Markdown preview.
+    ///
+    pub(crate) fn style_markdown_table_cell(theme: &Theme) -> Style {
+        let palette = theme.extended_palette();
+        Style {
+            border: Border {
+                color: palette.background.strong.color,
+                width: 1.0,
+                radius: 0.0.into(),
+            },
+            ..Style::default()
+        }
+    }
+
+    ///
+    /// Style for a line that was removed, in a diff.
+    ///
+    pub(crate) fn style_diff_removed(theme: &Theme) -> Style {
+        let palette = theme.extended_palette();
+        Style {
+            background: Some(palette.danger.weak.color.scale_alpha(0.4).into()),
+            ..Style::default()
+        }
+    }
+
+    ///
+    /// Style for a line that was added, in a diff.
+    ///
+    pub(crate) fn style_diff_added(theme: &Theme) -> Style {
+        let palette = theme.extended_palette();
+        Style {
+            background: Some(palette.success.weak.color.scale_alpha(0.4).into()),
+            ..Style::default()
+        }
+    }
+
     ///
     /// Style for a numpad button, used for the login PIN entry.
     ///
@@ -0,0 +1,146 @@
+//!
+//! Notespace-Editor
+//!
+//! The list of every file under the open folder, for quick open.
+//!
+//! The list is built once when the folder is opened, and then kept up to date from
+//! the changes reported by the folder watcher. Files ignored by `.gitignore` (and
+//! `.ignore`) files are not listed.
+//!
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct FileIndex {
+    pub(crate) root: PathBuf,
+    files: BTreeSet<PathBuf>,
+    ///
+    /// The files are being listed for the first time.
+    ///
+    pub(crate) building: bool,
+}
+
+///
+/// The changes to the index found for the paths reported by the folder watcher.
+///
+#[derive(Debug, Clone, Default)]
+pub struct FileIndexChanges {
+    ///
+    /// Files and directories that no longer exist.
+    ///
+    pub(crate) removed: Vec<PathBuf>,
+    ///
+    /// Files that exist and are not ignored.
+    ///
+    pub(crate) added: Vec<PathBuf>,
+}
+
+impl FileIndex {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            files: BTreeSet::new(),
+            building: true,
+        }
+    }
+
+    ///
+    /// The files, in path order.
+    ///
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter()
+    }
+
+    pub fn set_files(&mut self, files: Vec<PathBuf>) {
+        self.files = files.into_iter().collect();
+        self.building = false;
+    }
+
+    pub fn apply(&mut self, changes: FileIndexChanges) {
+        for removed in changes.removed {
+            self.files.retain(|file| !file.starts_with(&removed));
+        }
+        self.files.extend(changes.added);
+    }
+}
+
+///
+/// List every file under `root` that is not ignored.
+///
+/// The `ignore` crate walks the directories synchronously, so it is run on the
+/// blocking thread pool of the Tokio runtime.
+///
+pub async fn list_files(root: PathBuf) -> Vec<PathBuf> {
+    tokio::task::spawn_blocking(move || walk_files(&root))
+        .await
+        .unwrap_or_default()
+}
+
+///
+/// Find the changes to the index for the `paths` that were created, modified,
+/// removed or renamed under `root`. A directory that was added is listed with
+/// everything in it.
+///
+pub async fn find_changes(root: PathBuf, paths: Vec<PathBuf>) -> FileIndexChanges {
+    tokio::task::spawn_blocking(move || {
+        let mut changes = FileIndexChanges::default();
+        for path in paths.into_iter().filter(|path| path.starts_with(&root)) {
+            match std::fs::metadata(&path) {
+                Err(_) => changes.removed.push(path),
+                Ok(_) if !is_included(&root, &path) => changes.removed.push(path),
+                Ok(metadata) if metadata.is_dir() => changes.added.extend(walk_files(&path)),
+                Ok(_) => changes.added.push(path),
+            }
+        }
+        changes
+    })
+    .await
+    .unwrap_or_default()
+}
+
+///
+/// A walk of `path` that skips ignored files and `.git` directories, but not
+/// hidden files.
+///
+pub(crate) fn walk_builder(path: &Path) -> ignore::WalkBuilder {
+    let mut builder = ignore::WalkBuilder::new(path);
+    builder
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git");
+    builder
+}
+
+fn walk_files(directory: &Path) -> Vec<PathBuf> {
+    walk_builder(directory)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+///
+/// Returns `true` if neither `path` nor any directory between `root` and `path` is
+/// ignored. Ignore files only apply to the entries of a directory that is walked,
+/// so the directory of each of them is walked to find it.
+///
+fn is_included(root: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .take_while(|ancestor| *ancestor != root && ancestor.starts_with(root))
+        .all(|ancestor| {
+            let Some(parent) = ancestor.parent() else {
+                return false;
+            };
+            walk_builder(parent)
+                .max_depth(Some(1))
+                .build()
+                .filter_map(Result::ok)
+                .any(|entry| entry.depth() == 1 && entry.path() == ancestor)
+        })
+}
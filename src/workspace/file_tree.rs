@@ -0,0 +1,334 @@
+//!
+//! Notespace-Editor
+//!
+//! State of the folder sidebar: a tree of the files under the open folder.
+//!
+//! Directories are read when they are expanded for the first time, so opening a
+//! large folder does not read every directory under it. Files ignored by
+//! `.gitignore` (and `.ignore`) files are not shown.
+//!
+
+use crate::app_io::AppIOError;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+///
+/// A file or directory in the tree.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTreeEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+impl FileTreeEntry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+///
+/// A change to the files of the tree that needs a name from the user.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileTreeEdit {
+    ///
+    /// Create a file in the directory.
+    ///
+    NewFile(PathBuf),
+    ///
+    /// Create a directory in the directory.
+    ///
+    NewFolder(PathBuf),
+    Rename(PathBuf),
+    ///
+    /// Move the file or directory to another directory, relative to the root.
+    ///
+    Move(PathBuf),
+}
+
+///
+/// A change to the files of the tree that was made.
+///
+#[derive(Debug, Clone)]
+pub enum FileOperation {
+    CreatedFile(PathBuf),
+    CreatedFolder(PathBuf),
+    ///
+    /// A file or directory was renamed or moved, from the first to the second path.
+    ///
+    Renamed(PathBuf, PathBuf),
+    Deleted(PathBuf),
+}
+
+impl FileOperation {
+    ///
+    /// The directories whose entries changed.
+    ///
+    pub fn changed_directories(&self) -> Vec<PathBuf> {
+        let paths = match self {
+            FileOperation::CreatedFile(path)
+            | FileOperation::CreatedFolder(path)
+            | FileOperation::Deleted(path) => vec![path],
+            FileOperation::Renamed(from, to) => vec![from, to],
+        };
+        paths
+            .into_iter()
+            .filter_map(|path| path.parent())
+            .map(Path::to_path_buf)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct FileTree {
+    pub(crate) root: PathBuf,
+    ///
+    /// The entries of each directory that was read, directories first.
+    ///
+    children: HashMap<PathBuf, Vec<FileTreeEntry>>,
+    expanded: HashSet<PathBuf>,
+    pub(crate) selected: Option<PathBuf>,
+    ///
+    /// The entry whose actions are shown, after it was right-clicked.
+    ///
+    pub(crate) context_menu: Option<PathBuf>,
+    ///
+    /// The change being entered, and the name or directory typed so far.
+    ///
+    pub(crate) edit: Option<(FileTreeEdit, String)>,
+    ///
+    /// The file or directory the user is asked to confirm deleting.
+    ///
+    pub(crate) delete_prompt: Option<PathBuf>,
+}
+
+impl FileTree {
+    pub fn new(root: PathBuf) -> Self {
+        let mut expanded = HashSet::new();
+        expanded.insert(root.clone());
+        Self {
+            root,
+            children: HashMap::new(),
+            expanded,
+            selected: None,
+            context_menu: None,
+            edit: None,
+            delete_prompt: None,
+        }
+    }
+
+    ///
+    /// The name of the root directory, shown in the sidebar header.
+    ///
+    pub fn root_name(&self) -> String {
+        FileTreeEntry {
+            path: self.root.clone(),
+            is_dir: true,
+        }
+        .name()
+    }
+
+    pub fn is_expanded(&self, directory: &Path) -> bool {
+        self.expanded.contains(directory)
+    }
+
+    ///
+    /// Expand or collapse `directory`. Returns `true` if it was expanded and its
+    /// entries have not been read yet.
+    ///
+    pub fn toggle(&mut self, directory: &Path) -> bool {
+        if !self.expanded.remove(directory) {
+            self.expanded.insert(directory.to_path_buf());
+            return !self.children.contains_key(directory);
+        }
+        false
+    }
+
+    pub fn set_children(&mut self, directory: PathBuf, entries: Vec<FileTreeEntry>) {
+        self.children.insert(directory, entries);
+    }
+
+    ///
+    /// Forget a directory that could not be read, e.g. because it was removed.
+    ///
+    pub fn remove_directory(&mut self, directory: &Path) {
+        self.children.retain(|path, _| !path.starts_with(directory));
+        self.expanded.retain(|path| !path.starts_with(directory));
+    }
+
+    ///
+    /// Returns `true` if the entries of `directory` were read, so they need to be
+    /// read again when they change.
+    ///
+    pub fn is_loaded(&self, directory: &Path) -> bool {
+        self.children.contains_key(directory)
+    }
+
+    ///
+    /// Keep a renamed or moved directory expanded at its new path.
+    ///
+    pub fn path_renamed(&mut self, from: &Path, to: &Path) {
+        let renamed = |path: &PathBuf| match path.strip_prefix(from) {
+            Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+            Ok(rest) => to.join(rest),
+            Err(_) => path.clone(),
+        };
+        self.expanded = self.expanded.iter().map(renamed).collect();
+        self.children.retain(|path, _| !path.starts_with(from));
+        if let Some(selected) = &self.selected {
+            self.selected = Some(renamed(selected));
+        }
+    }
+
+    ///
+    /// The expanded directories that were not read yet, e.g. after a rename.
+    ///
+    pub fn unloaded_expanded_directories(&self) -> Vec<PathBuf> {
+        self.expanded
+            .iter()
+            .filter(|directory| !self.children.contains_key(*directory))
+            .filter(|directory| self.is_visible(directory))
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Returns `true` if every directory between the root and `path` is expanded.
+    ///
+    fn is_visible(&self, path: &Path) -> bool {
+        path.ancestors()
+            .skip(1)
+            .take_while(|ancestor| ancestor.starts_with(&self.root))
+            .all(|ancestor| self.expanded.contains(ancestor))
+    }
+
+    ///
+    /// The entries shown in the sidebar, in order, with their depth below the root.
+    /// Expanded directories are followed by their entries.
+    ///
+    pub fn visible_entries(&self) -> Vec<(usize, &FileTreeEntry)> {
+        let mut entries = Vec::new();
+        self.push_visible_entries(&self.root, 0, &mut entries);
+        entries
+    }
+
+    fn push_visible_entries<'a>(
+        &'a self,
+        directory: &Path,
+        depth: usize,
+        entries: &mut Vec<(usize, &'a FileTreeEntry)>,
+    ) {
+        let Some(children) = self.children.get(directory) else {
+            return;
+        };
+        for entry in children {
+            entries.push((depth, entry));
+            if entry.is_dir && self.expanded.contains(&entry.path) {
+                self.push_visible_entries(&entry.path, depth + 1, entries);
+            }
+        }
+    }
+
+    ///
+    /// The directory new files are created in for the `path` that was clicked: the
+    /// path itself if it is a directory, otherwise the directory it is in.
+    ///
+    pub fn directory_of(&self, path: &Path) -> PathBuf {
+        if path == self.root || self.is_directory(path) {
+            return path.to_path_buf();
+        }
+        path.parent().unwrap_or(&self.root).to_path_buf()
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        path.parent()
+            .and_then(|parent| self.children.get(parent))
+            .and_then(|children| children.iter().find(|entry| entry.path == path))
+            .is_some_and(|entry| entry.is_dir)
+    }
+
+    ///
+    /// The text the user starts with for `edit`.
+    ///
+    pub fn initial_edit_text(&self, edit: &FileTreeEdit) -> String {
+        match edit {
+            FileTreeEdit::NewFile(_) | FileTreeEdit::NewFolder(_) => String::new(),
+            FileTreeEdit::Rename(path) => path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            FileTreeEdit::Move(path) => path
+                .parent()
+                .and_then(|parent| parent.strip_prefix(&self.root).ok())
+                .map(|parent| parent.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    ///
+    /// The path the entered name or directory stands for: where a file or
+    /// directory is created, or where it is renamed or moved to. `None` if the
+    /// text is empty or leads outside of the root.
+    ///
+    pub fn edit_target(&self, edit: &FileTreeEdit, text: &str) -> Option<PathBuf> {
+        let text = text.trim();
+        let target = match edit {
+            FileTreeEdit::NewFile(directory) | FileTreeEdit::NewFolder(directory) => {
+                if text.is_empty() {
+                    return None;
+                }
+                directory.join(text)
+            }
+            FileTreeEdit::Rename(path) => {
+                if text.is_empty() || text.contains(['/', '\\']) {
+                    return None;
+                }
+                path.with_file_name(text)
+            }
+            FileTreeEdit::Move(path) => self.root.join(text).join(path.file_name()?),
+        };
+        let inside_root = target
+            .components()
+            .all(|component| component != std::path::Component::ParentDir)
+            && target.starts_with(&self.root);
+        inside_root.then_some(target)
+    }
+}
+
+///
+/// Read the entries of `directory` that are not ignored, directories first and
+/// then by name.
+///
+/// The `ignore` crate walks the directory synchronously, so it is run on the
+/// blocking thread pool of the Tokio runtime.
+///
+pub async fn read_directory(directory: PathBuf) -> Result<Vec<FileTreeEntry>, AppIOError> {
+    tokio::task::spawn_blocking(move || {
+        std::fs::metadata(&directory).map_err(|error| AppIOError::IOFailedError(error.kind()))?;
+        let mut entries: Vec<FileTreeEntry> = ignore::WalkBuilder::new(&directory)
+            .max_depth(Some(1))
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.depth() == 1)
+            .map(|entry| FileTreeEntry {
+                is_dir: entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir()),
+                path: entry.into_path(),
+            })
+            .collect();
+        entries.sort_by_cached_key(|entry| (!entry.is_dir, entry.name().to_lowercase()));
+        Ok(entries)
+    })
+    .await
+    .unwrap_or(Err(AppIOError::IOFailedError(std::io::ErrorKind::Other)))
+}
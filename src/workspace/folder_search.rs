@@ -0,0 +1,349 @@
+//!
+//! Notespace-Editor
+//!
+//! Search in the files of the open folder, and replace across them.
+//!
+//! Files are matched line by line, like find in the active document, so a pattern
+//! never matches across a line break. Files that cannot be opened in the editor
+//! (binary or too large) are skipped.
+//!
+
+use crate::app_const::MAX_FOLDER_SEARCH_MATCHES;
+use crate::app_io::{
+    AppIOError, FileBackup, LoadedFile, SavedFile, async_open_file_from_path,
+    async_save_file_to_path,
+};
+use crate::document::text_cursor::floor_char_boundary;
+use crate::document::text_encoding::TextEncoding;
+use crate::find::find_state::{FindMatch, replace_matches};
+use crate::ui_const::{UI_FOLDER_SEARCH_EXCERPT_CONTEXT, UI_FOLDER_SEARCH_EXCERPT_LENGTH};
+use crate::workspace::file_index::walk_builder;
+use iced::futures::{SinkExt, Stream};
+use ignore::overrides::{Override, OverrideBuilder};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+///
+/// What to search for, and in which files.
+///
+#[derive(Debug, Clone)]
+pub struct FolderSearchRequest {
+    pub(crate) root: PathBuf,
+    pub(crate) regex: Regex,
+    ///
+    /// The include and exclude globs.
+    ///
+    pub(crate) overrides: Override,
+    ///
+    /// The files that may match, best match first, found in the search index. If
+    /// `None`, every file under the root is searched.
+    ///
+    pub(crate) files: Option<Vec<PathBuf>>,
+}
+
+///
+/// A line with at least one match, and the part of it shown in the results.
+///
+#[derive(Debug, Clone)]
+pub struct LineMatch {
+    pub(crate) matches: Vec<FindMatch>,
+    pub(crate) excerpt: String,
+    ///
+    /// The byte index in the line where the excerpt starts.
+    ///
+    pub(crate) excerpt_start: usize,
+}
+
+///
+/// The matching lines of a file.
+///
+#[derive(Debug, Clone)]
+pub struct FileMatches {
+    pub(crate) path: PathBuf,
+    pub(crate) lines: Vec<LineMatch>,
+}
+
+///
+/// Sent while a search runs: a file with matches, and at the end the number of
+/// files that were searched.
+///
+#[derive(Debug, Clone)]
+pub enum FolderSearchEvent {
+    Found(FileMatches),
+    ///
+    /// The search has finished. `truncated` is `true` if it stopped after
+    /// `MAX_FOLDER_SEARCH_MATCHES` matching lines.
+    ///
+    Finished {
+        searched: usize,
+        truncated: bool,
+    },
+}
+
+///
+/// The new text of a file with every match replaced, and the lines that change.
+///
+#[derive(Debug, Clone)]
+pub struct FileReplacement {
+    pub(crate) path: PathBuf,
+    pub(crate) encoding: TextEncoding,
+    ///
+    /// The modification time of the file when the replacement was made, so it is
+    /// not written if the file changed since.
+    ///
+    pub(crate) modified: Option<SystemTime>,
+    pub(crate) text: String,
+    ///
+    /// The index, old text and new text of each changed line.
+    ///
+    pub(crate) changes: Vec<(usize, String, String)>,
+    pub(crate) count: usize,
+}
+
+///
+/// A file and its replacement, or why it could not be read.
+///
+pub type FileReplacementResult = (PathBuf, Result<FileReplacement, AppIOError>);
+
+///
+/// Build the include and exclude globs for a search under `root`. Both are lists
+/// of globs separated by commas, e.g. `*.md, journal/**`. If there are include
+/// globs, only files that match one of them are searched.
+///
+pub fn build_overrides(root: &Path, include: &str, exclude: &str) -> Result<Override, String> {
+    let globs = |list: &str| {
+        list.split(',')
+            .map(str::trim)
+            .filter(|glob| !glob.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs(include) {
+        builder.add(&glob).map_err(|error| error.to_string())?;
+    }
+    for glob in globs(exclude) {
+        builder
+            .add(&format!("!{}", glob))
+            .map_err(|error| error.to_string())?;
+    }
+    builder.build().map_err(|error| error.to_string())
+}
+
+///
+/// Search the files under the root of the `request`, in path order, or the files
+/// found in the search index in the order they were ranked. Each file with matches
+/// is sent as soon as it was searched.
+///
+pub fn search_folder(request: FolderSearchRequest) -> impl Stream<Item = FolderSearchEvent> {
+    iced::stream::channel(100, move |mut output| async move {
+        let FolderSearchRequest {
+            root,
+            regex,
+            overrides,
+            files,
+        } = request;
+        let files: Vec<PathBuf> = match files {
+            Some(files) => files
+                .into_iter()
+                .filter(|path| !is_excluded(&overrides, &root, path))
+                .collect(),
+            //
+            // The `ignore` crate walks the directories synchronously, so it is run
+            // on the blocking thread pool of the Tokio runtime.
+            //
+            None => {
+                let mut files: Vec<PathBuf> = tokio::task::spawn_blocking(move || {
+                    walk_builder(&root)
+                        .overrides(overrides)
+                        .build()
+                        .filter_map(Result::ok)
+                        .filter(|entry| {
+                            entry
+                                .file_type()
+                                .is_some_and(|file_type| file_type.is_file())
+                        })
+                        .map(|entry| entry.into_path())
+                        .collect()
+                })
+                .await
+                .unwrap_or_default();
+                files.sort();
+                files
+            }
+        };
+
+        let mut match_count = 0;
+        let mut truncated = false;
+        for path in &files {
+            let Ok(loaded_file) = async_open_file_from_path(path.clone()).await else {
+                continue;
+            };
+            let lines = find_lines(&regex, &loaded_file.text);
+            if lines.is_empty() {
+                continue;
+            }
+            match_count += lines.len();
+            let _ = output
+                .send(FolderSearchEvent::Found(FileMatches {
+                    path: path.clone(),
+                    lines,
+                }))
+                .await;
+            if match_count >= MAX_FOLDER_SEARCH_MATCHES {
+                truncated = true;
+                break;
+            }
+        }
+        let _ = output
+            .send(FolderSearchEvent::Finished {
+                searched: files.len(),
+                truncated,
+            })
+            .await;
+    })
+}
+
+///
+/// Whether the include and exclude globs leave out the file at `path`, like the
+/// walk of the folder would: the file, or one of the directories it is in, is
+/// excluded, or the file is not included.
+///
+fn is_excluded(overrides: &Override, root: &Path, path: &Path) -> bool {
+    if overrides.matched(path, false).is_ignore() {
+        return true;
+    }
+    path.ancestors()
+        .skip(1)
+        .take_while(|directory| directory.starts_with(root) && *directory != root)
+        .any(|directory| overrides.matched(directory, true).is_ignore())
+}
+
+///
+/// The lines of `text` that match `regex`.
+///
+fn find_lines(regex: &Regex, text: &str) -> Vec<LineMatch> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line_index, line)| {
+            let matches: Vec<FindMatch> = regex
+                .find_iter(line)
+                .filter(|m| !m.is_empty())
+                .map(|m| FindMatch {
+                    line: line_index,
+                    start: m.start(),
+                    end: m.end(),
+                })
+                .collect();
+            let first = matches.first()?;
+            let (excerpt_start, excerpt) = excerpt(line, first.start);
+            Some(LineMatch {
+                matches,
+                excerpt: excerpt.to_string(),
+                excerpt_start,
+            })
+        })
+        .collect()
+}
+
+///
+/// The part of `line` shown for a match at byte `start`: a little of the text
+/// before the match, without leading whitespace, and as much after it as fits.
+///
+fn excerpt(line: &str, start: usize) -> (usize, &str) {
+    let mut excerpt_start =
+        floor_char_boundary(line, start.saturating_sub(UI_FOLDER_SEARCH_EXCERPT_CONTEXT));
+    let before = &line[excerpt_start..start];
+    excerpt_start += before.len() - before.trim_start().len();
+    let excerpt_end = floor_char_boundary(line, excerpt_start + UI_FOLDER_SEARCH_EXCERPT_LENGTH);
+    (excerpt_start, line[excerpt_start..excerpt_end].trim_end())
+}
+
+///
+/// Replace the matches of `regex` in each of the `paths`. The files are read
+/// again, so the replacement is made in their current text. Files without
+/// matches are left out.
+///
+pub async fn replace_in_files(
+    paths: Vec<PathBuf>,
+    regex: Regex,
+    replacement: String,
+    expand: bool,
+) -> Vec<FileReplacementResult> {
+    let mut replacements = Vec::new();
+    for path in paths {
+        match async_open_file_from_path(path.clone()).await {
+            Ok(loaded_file) => {
+                if let Some(file_replacement) =
+                    replace_in_file(loaded_file, &regex, &replacement, expand)
+                {
+                    replacements.push((path, Ok(file_replacement)));
+                }
+            }
+            Err(error) => replacements.push((path, Err(error))),
+        }
+    }
+    replacements
+}
+
+///
+/// Replace the matches line by line, keeping the line endings of the file. Returns
+/// `None` if nothing matched.
+///
+fn replace_in_file(
+    loaded_file: LoadedFile,
+    regex: &Regex,
+    replacement: &str,
+    expand: bool,
+) -> Option<FileReplacement> {
+    let text = loaded_file.text.as_str();
+    let mut new_text = String::with_capacity(text.len());
+    let mut changes = Vec::new();
+    let mut count = 0;
+    for (line_index, line) in text.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let line_count = regex.find_iter(content).filter(|m| !m.is_empty()).count();
+        if line_count == 0 {
+            new_text.push_str(line);
+            continue;
+        }
+        count += line_count;
+        let replaced = replace_matches(regex, content, replacement, expand);
+        new_text.push_str(&replaced);
+        new_text.push_str(&line[content.len()..]);
+        changes.push((line_index, content.to_string(), replaced));
+    }
+    (count > 0).then_some(FileReplacement {
+        path: loaded_file.path,
+        encoding: loaded_file.encoding,
+        modified: loaded_file.modified,
+        text: new_text,
+        changes,
+        count,
+    })
+}
+
+///
+/// Write a replacement made by `replace_in_files`, unless the file was changed
+/// after it was read.
+///
+pub async fn write_replacement(
+    file_replacement: FileReplacement,
+    file_backup: FileBackup,
+) -> Result<SavedFile, AppIOError> {
+    let modified = tokio::fs::metadata(&file_replacement.path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if modified != file_replacement.modified {
+        return Err(AppIOError::FileChangedError(file_replacement.path));
+    }
+    async_save_file_to_path(
+        Some(file_replacement.path),
+        file_replacement.text,
+        file_replacement.encoding,
+        file_backup,
+    )
+    .await
+}
@@ -0,0 +1,151 @@
+//!
+//! Notespace-Editor
+//!
+//! State of the folder search panel, shown in the sidebar instead of the file tree.
+//!
+
+use crate::find::find_state::{FindOptions, build_pattern};
+use crate::workspace::folder_search::{
+    FileMatches, FileReplacement, FolderSearchEvent, FolderSearchRequest, build_overrides,
+};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+///
+/// The replacements shown to the user before any file is written.
+///
+#[derive(Debug, Default)]
+pub struct ReplacePreview {
+    pub(crate) files: Vec<FileReplacement>,
+    ///
+    /// Files that are not changed, and why.
+    ///
+    pub(crate) skipped: Vec<(PathBuf, String)>,
+}
+
+impl ReplacePreview {
+    ///
+    /// The number of matches that are replaced.
+    ///
+    pub fn count(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file_replacement| file_replacement.count)
+            .sum()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FolderSearchState {
+    pub(crate) visible: bool,
+    pub(crate) query: String,
+    pub(crate) replacement: String,
+    ///
+    /// Globs separated by commas. Only files matching one of the `include` globs
+    /// are searched, if there are any, and files matching an `exclude` glob are not.
+    ///
+    pub(crate) include: String,
+    pub(crate) exclude: String,
+    pub(crate) options: FindOptions,
+    ///
+    /// Identifies the latest search. Results of earlier searches are dropped.
+    ///
+    pub(crate) search_id: u64,
+    ///
+    /// The expression of the latest search. Its matches are the ones replaced, even
+    /// if the query was edited since.
+    ///
+    pub(crate) regex: Option<Regex>,
+    pub(crate) running: bool,
+    pub(crate) results: Vec<FileMatches>,
+    ///
+    /// The number of files that were searched, once the search has finished.
+    ///
+    pub(crate) searched: Option<usize>,
+    pub(crate) truncated: bool,
+    pub(crate) error: Option<String>,
+    pub(crate) replace_preview: Option<ReplacePreview>,
+    ///
+    /// The number of files of a confirmed replacement that are still being written,
+    /// and the number of files that were written.
+    ///
+    pub(crate) pending_writes: usize,
+    pub(crate) written_files: usize,
+}
+
+impl FolderSearchState {
+    ///
+    /// The search for the current query, options and globs under `root`, or the
+    /// reason it cannot be run.
+    ///
+    pub fn request(&self, root: &Path) -> Result<FolderSearchRequest, String> {
+        let regex = Regex::new(&build_pattern(&self.query, self.options))
+            .map_err(|error| error.to_string())?;
+        let overrides = build_overrides(root, &self.include, &self.exclude)?;
+        Ok(FolderSearchRequest {
+            root: root.to_path_buf(),
+            regex,
+            overrides,
+            files: None,
+        })
+    }
+
+    ///
+    /// Forget the results and start a new search for `regex`. Returns the id of
+    /// the search.
+    ///
+    pub fn start(&mut self, regex: Regex) -> u64 {
+        self.clear();
+        self.regex = Some(regex);
+        self.running = true;
+        self.search_id
+    }
+
+    ///
+    /// Forget the results, and the results of a search that is still running.
+    ///
+    pub fn clear(&mut self) {
+        self.search_id += 1;
+        self.regex = None;
+        self.running = false;
+        self.results.clear();
+        self.searched = None;
+        self.truncated = false;
+        self.error = None;
+    }
+
+    pub fn apply(&mut self, event: FolderSearchEvent) {
+        match event {
+            FolderSearchEvent::Found(file_matches) => self.results.push(file_matches),
+            FolderSearchEvent::Finished {
+                searched,
+                truncated,
+            } => {
+                self.running = false;
+                self.searched = Some(searched);
+                self.truncated = truncated;
+            }
+        }
+    }
+
+    ///
+    /// The number of matches found so far.
+    ///
+    pub fn match_count(&self) -> usize {
+        self.results
+            .iter()
+            .flat_map(|file_matches| &file_matches.lines)
+            .map(|line_match| line_match.matches.len())
+            .sum()
+    }
+
+    ///
+    /// The files with matches.
+    ///
+    pub fn matched_paths(&self) -> Vec<PathBuf> {
+        self.results
+            .iter()
+            .map(|file_matches| file_matches.path.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,10 @@
+//!
+//! Modules for the folder opened in the sidebar.
+//!
+pub(crate) mod file_index;
+pub(crate) mod file_tree;
+pub(crate) mod folder_search;
+pub(crate) mod folder_search_state;
+pub(crate) mod quick_open_state;
+pub(crate) mod search_index;
+pub(crate) mod search_query;
@@ -0,0 +1,180 @@
+//!
+//! Notespace-Editor
+//!
+//! State of quick open, the fuzzy finder for the files of the open folder.
+//!
+
+use crate::palette::fuzzy_match::fuzzy_match;
+use crate::ui_const::UI_QUICK_OPEN_MAX_RESULTS;
+use crate::workspace::file_index::FileIndex;
+use crate::workspace::search_index::SearchIndex;
+use crate::workspace::search_query::SearchQuery;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+///
+/// A match in the file name counts this many times as much as a match elsewhere
+/// in the path.
+///
+const FILE_NAME_WEIGHT: i64 = 2;
+
+///
+/// The most recently opened file scores this much higher, the next one less.
+///
+const RECENT_FILE_BONUS: i64 = 20;
+
+///
+/// The contents of the files are searched once the query has this many characters.
+///
+const CONTENT_QUERY_MIN_LENGTH: usize = 3;
+
+///
+/// The first lines of the selected file, shown next to the results.
+///
+#[derive(Debug, Clone)]
+pub struct QuickOpenPreview {
+    pub(crate) path: PathBuf,
+    pub(crate) text: String,
+}
+
+#[derive(Debug, Default)]
+pub struct QuickOpenState {
+    pub(crate) visible: bool,
+    pub(crate) query: String,
+    ///
+    /// The matching files, best match first, with the path shown for them. Files
+    /// whose path matches come first, then files whose contents match.
+    ///
+    pub(crate) results: Vec<(PathBuf, String)>,
+    ///
+    /// The number of `results` whose path matches.
+    ///
+    pub(crate) path_matches: usize,
+    pub(crate) selected: usize,
+    pub(crate) preview: Option<QuickOpenPreview>,
+}
+
+impl QuickOpenState {
+    pub fn open(&mut self) {
+        self.visible = true;
+        self.query.clear();
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.query.clear();
+        self.results.clear();
+        self.path_matches = 0;
+        self.preview = None;
+    }
+
+    pub fn selected_path(&self) -> Option<&Path> {
+        self.results
+            .get(self.selected)
+            .map(|(path, _)| path.as_path())
+    }
+
+    ///
+    /// Rank the files of the `file_index` by the query, and by how recently they
+    /// were opened. If there is a `search_index`, the files whose contents match
+    /// the query follow. Only the best `UI_QUICK_OPEN_MAX_RESULTS` are kept.
+    ///
+    pub fn update_results(
+        &mut self,
+        file_index: &FileIndex,
+        recent_files: &[PathBuf],
+        search_index: Option<&SearchIndex>,
+    ) {
+        let mut scored: Vec<(i64, PathBuf, String)> = file_index
+            .files()
+            .filter_map(|path| {
+                let relative = path
+                    .strip_prefix(&file_index.root)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .into_owned();
+                let mut score = path_score(&self.query, &relative)?;
+                if let Some(position) = recent_files.iter().position(|recent| recent == path) {
+                    score += (RECENT_FILE_BONUS - position as i64).max(1);
+                }
+                Some((score, path.clone(), relative))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _, relative)| (Reverse(*score), relative.len()));
+        scored.truncate(UI_QUICK_OPEN_MAX_RESULTS);
+        self.results = scored
+            .into_iter()
+            .map(|(_, path, relative)| (path, relative))
+            .collect();
+        self.path_matches = self.results.len();
+        if let Some(search_index) = search_index
+            && self.query.trim().chars().count() >= CONTENT_QUERY_MIN_LENGTH
+        {
+            let found: HashSet<PathBuf> =
+                self.results.iter().map(|(path, _)| path.clone()).collect();
+            let content_matches = search_index
+                .search(&SearchQuery::parse(&self.query))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(path, _)| !found.contains(path))
+                .take(UI_QUICK_OPEN_MAX_RESULTS - self.results.len())
+                .map(|(path, _)| {
+                    let relative = path
+                        .strip_prefix(&file_index.root)
+                        .unwrap_or(&path)
+                        .to_string_lossy()
+                        .into_owned();
+                    (path, relative)
+                });
+            self.results.extend(content_matches);
+        }
+        self.selected = 0;
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.results.is_empty() {
+            self.selected = (self.selected + 1) % self.results.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.results.is_empty() {
+            self.selected = self
+                .selected
+                .checked_sub(1)
+                .unwrap_or(self.results.len() - 1);
+        }
+    }
+}
+
+///
+/// Score the `relative` path of a file against the `query`, or `None` if it does
+/// not match.
+///
+/// The query is split into segments at `/`. The last segment is matched against the
+/// file name, or against the whole path if it does not match the file name. Each
+/// segment before it has to match one of the directories, in order, so `no/todo`
+/// finds `notes/2025/todo.md`.
+///
+pub fn path_score(query: &str, relative: &str) -> Option<i64> {
+    let query_segments: Vec<&str> = query
+        .split(['/', '\\'])
+        .filter(|segment| !segment.trim().is_empty())
+        .collect();
+    let Some((name_query, directory_queries)) = query_segments.split_last() else {
+        return Some(0);
+    };
+    let path_segments: Vec<&str> = relative.split(['/', '\\']).collect();
+    let (file_name, directories) = path_segments.split_last()?;
+    let mut score = match fuzzy_match(name_query, file_name) {
+        Some(score) => score * FILE_NAME_WEIGHT,
+        None if directory_queries.is_empty() => fuzzy_match(name_query, relative)?,
+        None => return None,
+    };
+    let mut directories = directories.iter();
+    for directory_query in directory_queries {
+        score += directories.find_map(|directory| fuzzy_match(directory_query, directory))?;
+    }
+    Some(score)
+}
@@ -0,0 +1,606 @@
+//!
+//! Notespace-Editor
+//!
+//! A full-text index of the files of the open folder, so searches do not have to
+//! read every file.
+//!
+//! The index maps each term to the files it occurs in, and its positions in each
+//! file. It is kept on disk in the application data directory, one file per
+//! folder, and loaded when the folder is opened. Files whose modification time
+//! changed since are read again in the background, and changes reported by the
+//! folder watcher are added as they happen.
+//!
+
+use crate::app_configuration::AppConfiguration;
+use crate::app_const::MAX_OPEN_FILE_SIZE;
+use crate::app_io::{path_hash, write_file_atomic};
+use crate::document::text_encoding::TextEncoding;
+use crate::workspace::file_index::{FileIndexChanges, walk_builder};
+use crate::workspace::search_query::{QueryTerm, SearchQuery, TermMatch, tokenize};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+///
+/// The directory of the index files, in the application data directory.
+///
+const INDEX_DIRECTORY: &str = "index";
+
+///
+/// Changed whenever the layout of `SearchIndex` changes, so old index files are
+/// built again instead of being read wrongly.
+///
+const INDEX_VERSION: u32 = 1;
+
+///
+/// BM25 ranking parameters: how quickly more occurrences of a term stop counting,
+/// and how much long files are ranked down.
+///
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+///
+/// The positions of a term in each file it occurs in, by file id.
+///
+type Postings = BTreeMap<u32, Vec<u32>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    ///
+    /// The number of terms in the file.
+    ///
+    length: u32,
+    ///
+    /// The distinct terms of the file, to remove it from the index.
+    ///
+    terms: Vec<String>,
+}
+
+///
+/// The terms of a file that was read, to be added to the index.
+///
+#[derive(Debug, Clone)]
+pub struct IndexedContent {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    length: u32,
+    positions: HashMap<String, Vec<u32>>,
+}
+
+///
+/// The changes to the index found by reading files, made on the blocking thread
+/// pool and then applied to the index.
+///
+#[derive(Debug, Clone, Default)]
+pub struct IndexUpdate {
+    ///
+    /// Files and directories that no longer exist.
+    ///
+    removed: Vec<PathBuf>,
+    indexed: Vec<IndexedContent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    pub(crate) root: PathBuf,
+    ///
+    /// The indexed files by id. Ids of removed files are reused.
+    ///
+    files: Vec<Option<IndexedFile>>,
+    terms: BTreeMap<String, Postings>,
+    #[serde(skip)]
+    ids: HashMap<PathBuf, u32>,
+    #[serde(skip)]
+    free_ids: Vec<u32>,
+    ///
+    /// The files are being compared with the index, after it was loaded.
+    ///
+    #[serde(skip)]
+    pub(crate) building: bool,
+}
+
+impl SearchIndex {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            version: INDEX_VERSION,
+            root,
+            files: Vec::new(),
+            terms: BTreeMap::new(),
+            ids: HashMap::new(),
+            free_ids: Vec::new(),
+            building: true,
+        }
+    }
+
+    ///
+    /// The indexed files.
+    ///
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter().flatten().map(|file| &file.path)
+    }
+
+    ///
+    /// The modification time of each file when it was indexed.
+    ///
+    pub fn modified_times(&self) -> HashMap<PathBuf, Option<SystemTime>> {
+        self.files
+            .iter()
+            .flatten()
+            .map(|file| (file.path.clone(), file.modified))
+            .collect()
+    }
+
+    pub fn apply(&mut self, update: IndexUpdate) {
+        for removed in update.removed {
+            let ids: Vec<u32> = self
+                .ids
+                .iter()
+                .filter(|(path, _)| path.starts_with(&removed))
+                .map(|(_, id)| *id)
+                .collect();
+            for id in ids {
+                self.remove_file(id);
+            }
+        }
+        for content in update.indexed {
+            //
+            // A file read by the initial comparison can arrive after a newer
+            // version of it that the watcher reported.
+            //
+            if let Some(&id) = self.ids.get(&content.path) {
+                let indexed = self.files[id as usize].as_ref();
+                if indexed.is_some_and(|file| file.modified > content.modified) {
+                    continue;
+                }
+                self.remove_file(id);
+            }
+            self.insert_file(content);
+        }
+    }
+
+    fn remove_file(&mut self, id: u32) {
+        let Some(file) = self.files[id as usize].take() else {
+            return;
+        };
+        for term in &file.terms {
+            if let Some(postings) = self.terms.get_mut(term) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.terms.remove(term);
+                }
+            }
+        }
+        self.ids.remove(&file.path);
+        self.free_ids.push(id);
+    }
+
+    fn insert_file(&mut self, content: IndexedContent) {
+        let id = match self.free_ids.pop() {
+            Some(id) => id,
+            None => {
+                self.files.push(None);
+                (self.files.len() - 1) as u32
+            }
+        };
+        let mut terms = Vec::with_capacity(content.positions.len());
+        for (term, positions) in content.positions {
+            self.terms
+                .entry(term.clone())
+                .or_default()
+                .insert(id, positions);
+            terms.push(term);
+        }
+        self.ids.insert(content.path.clone(), id);
+        self.files[id as usize] = Some(IndexedFile {
+            path: content.path,
+            modified: content.modified,
+            length: content.length,
+            terms,
+        });
+    }
+
+    ///
+    /// Fill in the lookup tables that are not stored on disk.
+    ///
+    fn rebuild_lookup(&mut self) {
+        self.ids.clear();
+        self.free_ids.clear();
+        for (id, file) in self.files.iter().enumerate() {
+            match file {
+                Some(file) => {
+                    self.ids.insert(file.path.clone(), id as u32);
+                }
+                None => self.free_ids.push(id as u32),
+            }
+        }
+    }
+
+    ///
+    /// The files that contain every phrase of the `query`, best match first, ranked
+    /// by BM25. `None` if the query has no terms, so it cannot narrow the files
+    /// down.
+    ///
+    pub fn search(&self, query: &SearchQuery) -> Option<Vec<(PathBuf, f32)>> {
+        if query.is_empty() {
+            return None;
+        }
+        let file_count = self.ids.len().max(1) as f32;
+        let average_length = self
+            .files
+            .iter()
+            .flatten()
+            .map(|file| file.length as f32)
+            .sum::<f32>()
+            / file_count;
+
+        let mut scores: Option<HashMap<u32, f32>> = None;
+        for phrase in &query.phrases {
+            let occurrences = self.phrase_occurrences(phrase);
+            let idf = ((file_count - occurrences.len() as f32 + 0.5)
+                / (occurrences.len() as f32 + 0.5))
+                .ln_1p();
+            let phrase_scores: HashMap<u32, f32> = occurrences
+                .into_iter()
+                .filter(|(id, _)| scores.as_ref().is_none_or(|scores| scores.contains_key(id)))
+                .map(|(id, count)| {
+                    let length = self.files[id as usize]
+                        .as_ref()
+                        .map_or(0.0, |file| file.length as f32);
+                    let count = count as f32;
+                    let score = idf * count * (BM25_K1 + 1.0)
+                        / (count
+                            + BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length.max(1.0)));
+                    (id, score)
+                })
+                .collect();
+            scores = Some(match scores {
+                Some(scores) => phrase_scores
+                    .into_iter()
+                    .map(|(id, score)| (id, score + scores[&id]))
+                    .collect(),
+                None => phrase_scores,
+            });
+        }
+
+        let mut ranked: Vec<(PathBuf, f32)> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, score)| Some((self.files[id as usize].as_ref()?.path.clone(), score)))
+            .collect();
+        ranked.sort_by(|(path_a, score_a), (path_b, score_b)| {
+            score_b.total_cmp(score_a).then_with(|| path_a.cmp(path_b))
+        });
+        Some(ranked)
+    }
+
+    ///
+    /// The number of times the terms of `phrase` follow each other in each file
+    /// they all occur in.
+    ///
+    fn phrase_occurrences(&self, phrase: &[QueryTerm]) -> HashMap<u32, usize> {
+        let term_positions: Vec<HashMap<u32, Vec<u32>>> = phrase
+            .iter()
+            .map(|query_term| self.term_positions(query_term))
+            .collect();
+        let Some((first, rest)) = term_positions.split_first() else {
+            return HashMap::new();
+        };
+        first
+            .iter()
+            .filter_map(|(id, positions)| {
+                let rest: Vec<&Vec<u32>> = rest
+                    .iter()
+                    .map(|term_positions| term_positions.get(id))
+                    .collect::<Option<_>>()?;
+                let count = positions
+                    .iter()
+                    .filter(|&&position| {
+                        rest.iter().enumerate().all(|(offset, positions)| {
+                            positions
+                                .binary_search(&(position + offset as u32 + 1))
+                                .is_ok()
+                        })
+                    })
+                    .count();
+                (count > 0).then_some((*id, count))
+            })
+            .collect()
+    }
+
+    ///
+    /// The sorted positions of the terms matching `query_term`, by file id.
+    ///
+    fn term_positions(&self, query_term: &QueryTerm) -> HashMap<u32, Vec<u32>> {
+        let matching: Box<dyn Iterator<Item = (&String, &Postings)>> = match query_term.term_match {
+            TermMatch::Exact => Box::new(self.terms.get_key_value(&query_term.text).into_iter()),
+            TermMatch::Prefix => Box::new(
+                self.terms
+                    .range(query_term.text.clone()..)
+                    .take_while(|(term, _)| term.starts_with(&query_term.text)),
+            ),
+            _ => Box::new(
+                self.terms
+                    .iter()
+                    .filter(|(term, _)| query_term.matches(term)),
+            ),
+        };
+        let mut positions: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (_, postings) in matching {
+            for (id, term_positions) in postings {
+                positions.entry(*id).or_default().extend(term_positions);
+            }
+        }
+        for term_positions in positions.values_mut() {
+            term_positions.sort_unstable();
+        }
+        positions
+    }
+}
+
+///
+/// The index file of the folder at `root`, named by a hash of the path.
+///
+fn index_path(root: &Path) -> PathBuf {
+    AppConfiguration::data_path()
+        .join(INDEX_DIRECTORY)
+        .join(format!("{:016x}.bin", path_hash(root)))
+}
+
+///
+/// Load the index of the folder at `root`, or an empty index if there is none yet
+/// or it cannot be read.
+///
+pub async fn load_search_index(root: PathBuf) -> Arc<SearchIndex> {
+    tokio::task::spawn_blocking(move || {
+        let index = std::fs::read(index_path(&root))
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<SearchIndex>(&bytes).ok())
+            .filter(|index| index.version == INDEX_VERSION && index.root == root);
+        let mut index = index.unwrap_or_else(|| SearchIndex::new(root));
+        index.rebuild_lookup();
+        index.building = true;
+        Arc::new(index)
+    })
+    .await
+    .unwrap_or_else(|_| Arc::new(SearchIndex::new(PathBuf::new())))
+}
+
+///
+/// Write the index to its file in the application data directory.
+///
+pub async fn save_search_index(index: Arc<SearchIndex>) {
+    let path = index_path(&index.root);
+    let bytes = tokio::task::spawn_blocking(move || bincode::serialize(index.as_ref()).ok())
+        .await
+        .ok()
+        .flatten();
+    let Some(bytes) = bytes else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    if let Err(error) = write_file_atomic(&path, &bytes).await {
+        eprintln!("Could not save the search index: {:?}", error);
+    }
+}
+
+///
+/// Compare the files under `root` with the `indexed` modification times. Files
+/// that are new or were modified are read, files that no longer exist are removed.
+///
+pub async fn scan_folder(
+    root: PathBuf,
+    indexed: HashMap<PathBuf, Option<SystemTime>>,
+) -> IndexUpdate {
+    tokio::task::spawn_blocking(move || {
+        let mut update = IndexUpdate::default();
+        let mut found = HashSet::new();
+        for entry in walk_builder(&root).build().filter_map(Result::ok) {
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+            let path = entry.into_path();
+            let modified = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            //
+            // A file that became binary or too large is no longer indexed.
+            //
+            if indexed.get(&path) != Some(&modified) {
+                match read_content(&path) {
+                    Some(content) => update.indexed.push(content),
+                    None if indexed.contains_key(&path) => update.removed.push(path.clone()),
+                    None => {}
+                }
+            }
+            found.insert(path);
+        }
+        update
+            .removed
+            .extend(indexed.into_keys().filter(|path| !found.contains(path)));
+        update
+    })
+    .await
+    .unwrap_or_default()
+}
+
+///
+/// Read the files that the folder watcher reported as changed.
+///
+pub async fn read_changed_files(changes: FileIndexChanges) -> IndexUpdate {
+    tokio::task::spawn_blocking(move || {
+        let mut update = IndexUpdate {
+            removed: changes.removed,
+            indexed: Vec::new(),
+        };
+        //
+        // A file that became binary or too large is removed from the index.
+        //
+        for path in changes.added {
+            match read_content(&path) {
+                Some(content) => update.indexed.push(content),
+                None => update.removed.push(path),
+            }
+        }
+        update
+    })
+    .await
+    .unwrap_or_default()
+}
+
+///
+/// Read and split the text of the file at `path` into terms. Binary files and
+/// files too large to be opened are not indexed.
+///
+fn read_content(path: &Path) -> Option<IndexedContent> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > MAX_OPEN_FILE_SIZE {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    let (text, _) = TextEncoding::detect_and_decode(&bytes)?;
+    let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+    let mut length = 0;
+    for (position, term) in tokenize(&text).enumerate() {
+        positions.entry(term).or_default().push(position as u32);
+        length += 1;
+    }
+    Some(IndexedContent {
+        path: path.to_path_buf(),
+        modified: metadata.modified().ok(),
+        length,
+        positions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn content(path: &str, modified: u64, text: &str) -> IndexedContent {
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        let mut length = 0;
+        for (position, term) in tokenize(text).enumerate() {
+            positions.entry(term).or_default().push(position as u32);
+            length += 1;
+        }
+        IndexedContent {
+            path: PathBuf::from(path),
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(modified)),
+            length,
+            positions,
+        }
+    }
+
+    fn index(files: &[(&str, &str)]) -> SearchIndex {
+        let mut index = SearchIndex::new(PathBuf::from("/root"));
+        index.apply(IndexUpdate {
+            removed: Vec::new(),
+            indexed: files
+                .iter()
+                .map(|(path, text)| content(path, 1, text))
+                .collect(),
+        });
+        index
+    }
+
+    fn found(index: &SearchIndex, query: &str) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = index
+            .search(&SearchQuery::parse(query))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn every_phrase_must_match() {
+        let index = index(&[
+            ("/root/a.md", "the quick brown fox"),
+            ("/root/b.md", "a quick red fox"),
+            ("/root/c.md", "nothing here"),
+        ]);
+        assert_eq!(
+            found(&index, "quick fox "),
+            [PathBuf::from("/root/a.md"), PathBuf::from("/root/b.md")]
+        );
+        assert_eq!(found(&index, "brown fox "), [PathBuf::from("/root/a.md")]);
+        assert!(index.search(&SearchQuery::default()).is_none());
+    }
+
+    #[test]
+    fn phrases_must_follow_each_other() {
+        let index = index(&[
+            ("/root/a.md", "quick brown fox"),
+            ("/root/b.md", "brown quick fox"),
+        ]);
+        assert_eq!(
+            found(&index, "\"quick brown\""),
+            [PathBuf::from("/root/a.md")]
+        );
+    }
+
+    #[test]
+    fn prefix_matches_longer_terms() {
+        let index = index(&[("/root/a.md", "searching"), ("/root/b.md", "sea")]);
+        assert_eq!(found(&index, "search"), [PathBuf::from("/root/a.md")]);
+        assert_eq!(found(&index, "sea "), [PathBuf::from("/root/b.md")]);
+    }
+
+    #[test]
+    fn more_occurrences_rank_higher() {
+        let index = index(&[
+            ("/root/a.md", "note one two three"),
+            ("/root/b.md", "note note note one"),
+        ]);
+        let ranked = index.search(&SearchQuery::parse("note ")).unwrap();
+        assert_eq!(ranked[0].0, PathBuf::from("/root/b.md"));
+    }
+
+    #[test]
+    fn removed_directories_remove_their_files() {
+        let mut index = index(&[
+            ("/root/dir/a.md", "word"),
+            ("/root/dir/b.md", "word"),
+            ("/root/c.md", "word"),
+        ]);
+        index.apply(IndexUpdate {
+            removed: vec![PathBuf::from("/root/dir")],
+            indexed: Vec::new(),
+        });
+        assert_eq!(found(&index, "word "), [PathBuf::from("/root/c.md")]);
+        assert!(!index.terms.is_empty());
+        index.apply(IndexUpdate {
+            removed: vec![PathBuf::from("/root/c.md")],
+            indexed: Vec::new(),
+        });
+        assert!(index.terms.is_empty());
+    }
+
+    #[test]
+    fn older_content_does_not_replace_newer() {
+        let mut index = SearchIndex::new(PathBuf::from("/root"));
+        index.apply(IndexUpdate {
+            removed: Vec::new(),
+            indexed: vec![content("/root/a.md", 2, "new")],
+        });
+        index.apply(IndexUpdate {
+            removed: Vec::new(),
+            indexed: vec![content("/root/a.md", 1, "old")],
+        });
+        assert_eq!(found(&index, "new "), [PathBuf::from("/root/a.md")]);
+        assert!(found(&index, "old ").is_empty());
+    }
+}
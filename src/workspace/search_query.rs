@@ -0,0 +1,228 @@
+//!
+//! Notespace-Editor
+//!
+//! Queries of the search index: words, prefixes and phrases.
+//!
+//! Text is split into terms at every character that is not a letter, an ASCII
+//! digit or `_`, and terms are compared in lowercase. The same split is used for
+//! the files in the index and for queries, so a query finds the terms of a file
+//! that it was built from.
+//!
+
+///
+/// How a term of a query matches the terms of the index.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermMatch {
+    Exact,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerm {
+    pub(crate) text: String,
+    pub(crate) term_match: TermMatch,
+}
+
+impl QueryTerm {
+    fn exact(text: String) -> Self {
+        Self {
+            text,
+            term_match: TermMatch::Exact,
+        }
+    }
+
+    pub fn matches(&self, term: &str) -> bool {
+        match self.term_match {
+            TermMatch::Exact => term == self.text,
+            TermMatch::Prefix => term.starts_with(&self.text),
+            TermMatch::Suffix => term.ends_with(&self.text),
+            TermMatch::Contains => term.contains(&self.text),
+        }
+    }
+}
+
+///
+/// Terms that follow each other in a file. A single word is a phrase of one term.
+///
+pub type Phrase = Vec<QueryTerm>;
+
+///
+/// A query of the search index. A file matches if it contains every phrase.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub(crate) phrases: Vec<Phrase>,
+}
+
+impl SearchQuery {
+    ///
+    /// Parse a query typed by the user. Words are matched exactly, a word ending
+    /// in `*` as a prefix, and text in double quotes as a phrase. The last word is
+    /// matched as a prefix too, as it may not have been typed completely yet.
+    ///
+    pub fn parse(text: &str) -> Self {
+        let mut phrases: Vec<Phrase> = Vec::new();
+        for (index, part) in text.split('"').enumerate() {
+            if index % 2 == 1 {
+                phrases.push(tokenize(part).map(QueryTerm::exact).collect());
+                continue;
+            }
+            for word in part.split_whitespace() {
+                let mut phrase: Phrase = tokenize(word).map(QueryTerm::exact).collect();
+                if word.ends_with('*')
+                    && let Some(last) = phrase.last_mut()
+                {
+                    last.term_match = TermMatch::Prefix;
+                }
+                phrases.push(phrase);
+            }
+        }
+        phrases.retain(|phrase| !phrase.is_empty());
+        if text.ends_with(is_word_char)
+            && let Some(last) = phrases.last_mut().and_then(|phrase| phrase.last_mut())
+        {
+            last.term_match = TermMatch::Prefix;
+        }
+        Self { phrases }
+    }
+
+    ///
+    /// The query for the files that may contain `text`. The first and last term of
+    /// `text` can be part of a longer term in a file, unless only `whole_word`s
+    /// are searched for.
+    ///
+    pub fn literal(text: &str, whole_word: bool) -> Self {
+        let words = words(text);
+        let phrase: Phrase = words
+            .iter()
+            .map(|&(start, end)| {
+                let open_start = !whole_word && start == 0;
+                let open_end = !whole_word && end == text.len();
+                QueryTerm {
+                    text: text[start..end].to_lowercase(),
+                    term_match: match (open_start, open_end) {
+                        (true, true) => TermMatch::Contains,
+                        (true, false) => TermMatch::Suffix,
+                        (false, true) => TermMatch::Prefix,
+                        (false, false) => TermMatch::Exact,
+                    },
+                }
+            })
+            .collect();
+        let phrases = match phrase.is_empty() {
+            true => Vec::new(),
+            false => vec![phrase],
+        };
+        Self { phrases }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty()
+    }
+}
+
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || c.is_ascii_digit() || c == '_'
+}
+
+///
+/// The terms of `text`, in order and in lowercase.
+///
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !is_word_char(c))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+///
+/// The byte ranges of the terms of `text`.
+///
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices() {
+        match (is_word_char(c), start) {
+            (true, None) => start = Some(index),
+            (false, Some(word_start)) => {
+                words.push((word_start, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(word_start) = start {
+        words.push((word_start, text.len()));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(text: &str, term_match: TermMatch) -> QueryTerm {
+        QueryTerm {
+            text: text.to_string(),
+            term_match,
+        }
+    }
+
+    #[test]
+    fn tokenize_splits_at_non_word_characters() {
+        let terms: Vec<String> = tokenize("Hello, wörld! foo_bar-42").collect();
+        assert_eq!(terms, ["hello", "wörld", "foo_bar", "42"]);
+    }
+
+    #[test]
+    fn parse_words_prefixes_and_phrases() {
+        let query = SearchQuery::parse(r#"rust edit* "side by side" "#);
+        assert_eq!(
+            query.phrases,
+            [
+                vec![term("rust", TermMatch::Exact)],
+                vec![term("edit", TermMatch::Prefix)],
+                vec![
+                    term("side", TermMatch::Exact),
+                    term("by", TermMatch::Exact),
+                    term("side", TermMatch::Exact),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_matches_the_last_word_as_a_prefix() {
+        let query = SearchQuery::parse("quick bro");
+        assert_eq!(query.phrases[1], [term("bro", TermMatch::Prefix)]);
+        assert!(SearchQuery::parse("  \"\" ").is_empty());
+    }
+
+    #[test]
+    fn literal_leaves_the_outer_terms_open() {
+        let query = SearchQuery::literal("ick Bro", false);
+        assert_eq!(
+            query.phrases,
+            [vec![
+                term("ick", TermMatch::Suffix),
+                term("bro", TermMatch::Prefix),
+            ]]
+        );
+        let query = SearchQuery::literal("(middle)", false);
+        assert_eq!(query.phrases, [vec![term("middle", TermMatch::Exact)]]);
+        let query = SearchQuery::literal("own", true);
+        assert_eq!(query.phrases, [vec![term("own", TermMatch::Exact)]]);
+        let query = SearchQuery::literal("own", false);
+        assert_eq!(query.phrases, [vec![term("own", TermMatch::Contains)]]);
+    }
+
+    #[test]
+    fn term_matches() {
+        assert!(term("own", TermMatch::Contains).matches("brown"));
+        assert!(term("bro", TermMatch::Prefix).matches("brown"));
+        assert!(term("own", TermMatch::Suffix).matches("brown"));
+        assert!(!term("own", TermMatch::Exact).matches("brown"));
+    }
+}